@@ -0,0 +1,154 @@
+// Exact parsing and formatting of SOL amounts.  SOL amounts are decimal strings with at most 9 fractional digits,
+// which is the precision of a lamport; they are never converted through floating point.
+
+pub const LAMPORTS_PER_SOL : u64 = 1_000_000_000;
+
+// Number of fractional digits in a SOL amount
+const SOL_DECIMALS : usize = 9;
+
+// An amount to withdraw from a vote account
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Amount
+{
+    // Everything that can be withdrawn
+    All,
+
+    // Everything that can be withdrawn except this many lamports
+    AllBut(u64),
+
+    Lamports(u64)
+}
+
+impl std::str::FromStr for Amount
+{
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err>
+    {
+        let s = s.trim();
+
+        if s.eq_ignore_ascii_case("ALL") {
+            Ok(Amount::All)
+        }
+        else if s.get(..7).map(|prefix| prefix.eq_ignore_ascii_case("ALL-BUT")).unwrap_or(false) {
+            Ok(Amount::AllBut(parse_sol(s[7..].trim_start_matches([' ', ':', '='].as_slice()))?))
+        }
+        else {
+            // The program takes a withdrawal of 0 lamports to mean everything, which must only ever be asked for by ALL
+            match parse_sol(s)? {
+                0 => Err("must be more than 0 SOL; use ALL to withdraw everything".to_string()),
+                lamports => Ok(Amount::Lamports(lamports))
+            }
+        }
+    }
+}
+
+// A SOL amount, parsed exactly into lamports
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sol(pub u64);
+
+impl std::str::FromStr for Sol
+{
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err>
+    {
+        parse_sol(s).map(Sol)
+    }
+}
+
+// Parses a decimal SOL amount into lamports
+pub fn parse_sol(s : &str) -> Result<u64, String>
+{
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+
+    if (whole.is_empty() && fraction.is_empty()) || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(format!("{} is not a SOL amount", s));
+    }
+
+    if fraction.len() > SOL_DECIMALS {
+        return Err(format!("{} has more than {} fractional digits", s, SOL_DECIMALS));
+    }
+
+    let whole = if whole.is_empty() { 0 } else { whole.parse::<u64>().map_err(|_| format!("{} is too large", s))? };
+
+    let fraction = if fraction.is_empty() {
+        0
+    }
+    else {
+        fraction.parse::<u64>().unwrap() * 10_u64.pow((SOL_DECIMALS - fraction.len()) as u32)
+    };
+
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+        .ok_or_else(|| format!("{} is too large", s))
+}
+
+// Formats lamports as a decimal SOL amount, without trailing fractional zeroes
+pub fn format_sol(lamports : u64) -> String
+{
+    let whole = lamports / LAMPORTS_PER_SOL;
+
+    let fraction = lamports % LAMPORTS_PER_SOL;
+
+    if fraction == 0 {
+        whole.to_string()
+    }
+    else {
+        format!("{}.{}", whole, format!("{:09}", fraction).trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn rejects_zero()
+    {
+        for s in ["0", "0.0", "0.000000000"] {
+            assert_eq!(s.parse::<Amount>(), Err("must be more than 0 SOL; use ALL to withdraw everything".to_string()));
+        }
+
+        assert_eq!("0.000000001".parse::<Amount>(), Ok(Amount::Lamports(1)));
+    }
+
+    proptest! {
+        #[test]
+        fn format_then_parse_round_trips(lamports in any::<u64>()) {
+            prop_assert_eq!(parse_sol(&format_sol(lamports)), Ok(lamports));
+        }
+
+        #[test]
+        fn parses_any_precision(whole in 0_u64..1_000_000, fraction in 0_u64..LAMPORTS_PER_SOL, digits in 1_usize..=9) {
+            let fraction = fraction / 10_u64.pow((SOL_DECIMALS - digits) as u32);
+            let s = format!("{}.{:0width$}", whole, fraction, width = digits);
+            prop_assert_eq!(
+                parse_sol(&s),
+                Ok((whole * LAMPORTS_PER_SOL) + (fraction * 10_u64.pow((SOL_DECIMALS - digits) as u32)))
+            );
+        }
+
+        #[test]
+        fn rejects_too_many_fractional_digits(whole in 0_u64..1000, fraction in "[0-9]{10,20}") {
+            let s = format!("{}.{}", whole, fraction);
+            prop_assert!(parse_sol(&s).is_err());
+        }
+
+        #[test]
+        fn parses_all_but(lamports in any::<u64>(), separator in "( |:|=)?") {
+            prop_assert_eq!(
+                format!("ALL-BUT{}{}", separator, format_sol(lamports)).parse::<Amount>(),
+                Ok(Amount::AllBut(lamports))
+            );
+        }
+
+        #[test]
+        fn never_panics(s in "\\PC*") {
+            let _ = s.parse::<Amount>();
+        }
+    }
+}
@@ -1,9 +1,22 @@
+use crate::amount::{Amount, Sol};
+use crate::environment::Environment;
 use crate::error_exit;
+use crate::rotate::Rotation;
+use crate::spec::{
+    find_command, usage_message, ArgSpec, CommandSpec, Group, Occurs, Position, ValueType, COMMANDS, GLOBAL_ARGS,
+    GRAPH_FORMATS, LINT_RULES, SHELLS
+};
 use crate::usage;
 
 #[derive(Debug)]
 pub enum Command
 {
+    Keygen
+    {
+        out_dir : String,
+        seed_phrases : bool
+    },
+
     Enter
     {
         withdraw_authority : String,
@@ -12,9 +25,20 @@ pub enum Command
         max_commission_increase_per_epoch : Option<u8>
     },
 
+    // enter --interactive, with whichever of the arguments of enter were supplied
+    EnterInteractive
+    {
+        withdraw_authority : Option<String>,
+        administrator : Option<String>,
+        max_commission : Option<u8>,
+        max_commission_increase_per_epoch : Option<u8>,
+        fee_payer : Option<String>
+    },
+
     SetLeaveEpoch
     {
-        withdraw_authority : String, leave_epoch : u64
+        withdraw_authority : String,
+        leave_epoch : u64
     },
 
     Leave
@@ -24,262 +48,552 @@ pub enum Command
 
     SetAdministrator
     {
-        withdraw_authority : String, administrator : String
+        withdraw_authority : String,
+        administrator : String
     },
 
     SetOperationalAuthority
     {
-        administrator : String, authority : String
+        administrator : String,
+        authority : String
     },
 
     SetRewardsAuthority
     {
-        administrator : String, authority : String
+        administrator : String,
+        authority : String
     },
 
     SetVoteAuthority
     {
-        operational_authority : String, authority : String
+        operational_authority : String,
+        authority : String
     },
 
     SetValidatorIdentity
     {
-        operational_authority : String, validator_identity : String
+        operational_authority : String,
+        validator_identity : String
+    },
+
+    Rotate
+    {
+        rotation : Rotation,
+        export : Option<String>
+    },
+
+    Incident
+    {
+        compromised : String,
+        // The new authorities and the keypairs of the current authorities that sign their rotation
+        replacements : Rotation,
+        export : Option<String>
     },
 
     Withdraw
     {
-        rewards_authority : String, recipient : String, amount : Option<f64>
+        // Each recipient is paired with its share of the withdrawn SOL, in basis points
+        rewards_authority : String,
+        recipients : Vec<(String, u32)>,
+        amount : Option<Amount>
+    },
+
+    WithdrawAndStake
+    {
+        rewards_authority : String,
+        amount : Option<Amount>,
+        stake_account : Option<String>,
+        seed : Option<String>,
+        stake_authority : Option<String>
     },
 
     SetCommission
     {
-        rewards_authority : String, commission : u8
+        rewards_authority : String,
+        commission : u8
+    },
+
+    Batch
+    {
+        script : String,
+        profile : Option<String>
     },
 
     Show
     {
+        vote_accounts : Vec<String>,
+        json : bool,
+        // The format of the permission graph to show instead of the configured values
+        graph : Option<String>
+    },
+
+    Lint
+    {
+        vote_accounts : Vec<String>,
+        // Compared with the withdraw authority, rather than used to pay for anything
+        fee_payer : Option<String>,
+        allow : Vec<String>,
+        deny : Vec<String>,
         json : bool
-    }
+    },
+
+    PlanCommission
+    {
+        target : Option<u8>,
+        epoch : Option<u64>,
+        json : bool
+    },
+
+    Daemon
+    {
+        rewards_authority : String,
+        recipient : String,
+        threshold : Option<u64>,
+        buffer : Option<u64>,
+        every_epoch : bool,
+        poll_interval : Option<u64>
+    },
+
+    RampCommission
+    {
+        rewards_authority : String,
+        target : u8,
+        state_file : Option<String>,
+        poll_interval : Option<u64>
+    },
+
+    History
+    {
+        limit : Option<usize>,
+        json : bool
+    },
+
+    Decode
+    {
+        input : String
+    },
+
+    Completions
+    {
+        shell : String
+    },
+
+    Man
 }
 
-// Returns (Option<fee_payer>, url, Option<commitment>, vote_account, command)
-pub fn parse_command() -> (Option<String>, String, Option<String>, String, Command)
+// A fixture file that RPC traffic is recorded to or replayed from
+#[derive(Debug)]
+pub enum RpcFixture
 {
-    let args = parse_args();
+    Record(String),
+
+    Replay(String)
+}
+
+// (Option<fee_payer>, url, Option<commitment>, Option<rpc_fixture>, Option<vote_account>, command)
+pub type ParsedCommand = (Option<String>, String, Option<String>, Option<RpcFixture>, Option<String>, Command);
+
+// A command of a batch script: (Option<fee_payer>, vote_account, command)
+pub type BatchStep = (Option<String>, String, Command);
+
+// The commands which may be used in a batch script, each of which is a single instruction
+const BATCH_COMMANDS : &[&str] = &[
+    "enter",
+    "set-leave-epoch",
+    "leave",
+    "set-administrator",
+    "set-operational-authority",
+    "set-rewards-authority",
+    "set-vote-authority",
+    "set-validator-identity",
+    "set-commission"
+];
+
+pub fn parse_command() -> ParsedCommand
+{
+    parse_command_line(std::env::args().skip(1).collect(), &Environment::from_process())
+        .unwrap_or_else(|e| error_exit(&e))
+}
+
+pub fn parse_command_line(
+    input_args : Vec<String>,
+    environment : &Environment
+) -> Result<ParsedCommand, String>
+{
+    let args = parse_args(input_args)?;
 
     if args.help {
         do_help(args.tokens);
     }
 
-    if args.tokens.len() == 0 {
-        error_exit("No command supplied.");
-    }
+    let command = args.tokens.first().ok_or("No command supplied.")?;
 
-    let command = args.tokens[0].clone();
+    let spec = find_command(command).ok_or_else(|| format!("Unknown command: {}", command))?;
 
-    let mut tokens = args.tokens.into_iter().skip(1);
+    let values = resolve(spec, args, environment)?;
+
+    // show takes any number of vote accounts, which are part of its command
+    let vote_account = if (spec.name == "show") || spec.arg("vote-account").is_none() {
+        None
+    }
+    else {
+        values.get_option("vote-account")?
+    };
 
-    let (maybe_fee_payer, vote_account, command) = match command.as_str() {
+    let (maybe_fee_payer, command) = match spec.name {
+        // The fee payer defaults to the withdraw authority, which may not be known until it is prompted for
+        "keygen" => {
+            (None, Command::Keygen { out_dir : values.get("out-dir")?, seed_phrases : values.is_set("seed-phrases") })
+        },
+        "enter" if values.is_set("interactive") => (None, Command::EnterInteractive {
+            withdraw_authority : values.get_option("withdraw-authority")?,
+            administrator : values.get_option("administrator")?,
+            max_commission : values.get_option("max-commission")?,
+            max_commission_increase_per_epoch : values.get_option("max-commission-increase-per-epoch")?,
+            fee_payer : values.get_option("fee-payer")?
+        }),
         "enter" => {
-            let withdraw_authority : String =
-                get_arg_value(tokens.next(), args.withdraw_authority, "enter", "withdraw authority");
-
-            (
-                Some(withdraw_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "enter", "vote account"),
-                Command::Enter {
-                    withdraw_authority,
-                    administrator : get_arg_value(tokens.next(), args.administrator, "enter", "administrator"),
-                    max_commission : get_option_arg_value(tokens.next(), args.max_commission, "max commission"),
-                    max_commission_increase_per_epoch : get_option_arg_value(
-                        tokens.next(),
-                        args.max_commission_increase_per_epoch,
-                        "max commission increase per epoch"
-                    )
-                }
-            )
+            let withdraw_authority : String = values.get("withdraw-authority")?;
+
+            (Some(withdraw_authority.clone()), Command::Enter {
+                withdraw_authority,
+                administrator : values.get("administrator")?,
+                max_commission : values.get_option("max-commission")?,
+                max_commission_increase_per_epoch : values.get_option("max-commission-increase-per-epoch")?
+            })
         },
         "set-leave-epoch" => {
-            let withdraw_authority : String =
-                get_arg_value(tokens.next(), args.withdraw_authority, "set-leave-epoch", "withdraw authority");
-
-            (
-                Some(withdraw_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "set-leave-epoch", "vote account"),
-                Command::SetLeaveEpoch {
-                    withdraw_authority,
-                    leave_epoch : get_arg_value(tokens.next(), args.leave_epoch, "set-leave-epoch", "leave epoch")
-                }
-            )
+            let withdraw_authority : String = values.get("withdraw-authority")?;
+
+            (Some(withdraw_authority.clone()), Command::SetLeaveEpoch {
+                withdraw_authority,
+                leave_epoch : values.get("leave-epoch")?
+            })
         },
         "leave" => {
-            let withdraw_authority : String =
-                get_arg_value(tokens.next(), args.withdraw_authority, "leave", "withdraw authority");
+            let withdraw_authority : String = values.get("withdraw-authority")?;
 
-            (
-                Some(withdraw_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "leave", "vote account"),
-                Command::Leave { withdraw_authority }
-            )
+            (Some(withdraw_authority.clone()), Command::Leave { withdraw_authority })
         },
         "set-administrator" => {
-            let withdraw_authority : String =
-                get_arg_value(tokens.next(), args.withdraw_authority, "set-administrator", "withdraw authority");
-
-            (
-                Some(withdraw_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "set-administrator", "vote account"),
-                Command::SetAdministrator {
-                    withdraw_authority,
-                    administrator : get_arg_value(
-                        tokens.next(),
-                        args.administrator,
-                        "set-administrator",
-                        "administrator"
-                    )
-                }
-            )
+            let withdraw_authority : String = values.get("withdraw-authority")?;
+
+            (Some(withdraw_authority.clone()), Command::SetAdministrator {
+                withdraw_authority,
+                administrator : values.get("administrator")?
+            })
         },
         "set-operational-authority" => {
-            let administrator : String =
-                get_arg_value(tokens.next(), args.administrator, "set-operational-authority", "administrator");
-
-            (
-                Some(administrator.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "set-operational-authority", "vote account"),
-                Command::SetOperationalAuthority {
-                    administrator,
-                    authority : get_arg_value(
-                        tokens.next(),
-                        args.operational_authority,
-                        "set-operational-authority",
-                        "operational authority"
-                    )
-                }
-            )
+            let administrator : String = values.get("administrator")?;
+
+            (Some(administrator.clone()), Command::SetOperationalAuthority {
+                administrator,
+                authority : values.get("operational-authority")?
+            })
         },
         "set-rewards-authority" => {
-            let administrator : String =
-                get_arg_value(tokens.next(), args.administrator, "set-rewards-authority", "administrator");
-
-            (
-                Some(administrator.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "set-rewards-authority", "vote account"),
-                Command::SetRewardsAuthority {
-                    administrator,
-                    authority : get_arg_value(
-                        tokens.next(),
-                        args.rewards_authority,
-                        "set-rewards-authority",
-                        "rewards authority"
-                    )
-                }
-            )
+            let administrator : String = values.get("administrator")?;
+
+            (Some(administrator.clone()), Command::SetRewardsAuthority {
+                administrator,
+                authority : values.get("rewards-authority")?
+            })
         },
         "set-vote-authority" => {
-            let operational_authority : String =
-                get_arg_value(tokens.next(), args.operational_authority, "set-vote-authority", "operational authority");
-
-            (
-                Some(operational_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "set-vote-authority", "vote account"),
-                Command::SetVoteAuthority {
-                    operational_authority,
-                    authority : get_arg_value(
-                        tokens.next(),
-                        args.vote_authority,
-                        "set-vote-authority",
-                        "vote authority"
-                    )
-                }
-            )
+            let operational_authority : String = values.get("operational-authority")?;
+
+            (Some(operational_authority.clone()), Command::SetVoteAuthority {
+                operational_authority,
+                authority : values.get("vote-authority")?
+            })
         },
         "set-validator-identity" => {
-            let operational_authority : String = get_arg_value(
-                tokens.next(),
-                args.operational_authority,
-                "set-validator-authority",
-                "operational authority"
-            );
+            let operational_authority : String = values.get("operational-authority")?;
 
-            (
-                Some(operational_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "set-validator-identity", "vote account"),
-                Command::SetValidatorIdentity {
-                    operational_authority,
-                    validator_identity : get_arg_value(
-                        tokens.next(),
-                        args.validator_identity,
-                        "set-validator-identity",
-                        "validator identity"
-                    )
-                }
-            )
+            (Some(operational_authority.clone()), Command::SetValidatorIdentity {
+                operational_authority,
+                validator_identity : values.get("validator-identity")?
+            })
         },
+        // The fee payer defaults to the signer of the first rotation, which is not known until the rotation is planned
+        "rotate" => (values.get_option("fee-payer")?, Command::Rotate {
+            rotation : Rotation {
+                withdraw_authority : values.get_option("withdraw-authority")?,
+                administrator : values.get_option("administrator")?,
+                operational_authority : values.get_option("operational-authority")?,
+                new_administrator : values.get_option("new-administrator")?,
+                new_rewards_authority : values.get_option("new-rewards-authority")?,
+                new_operational_authority : values.get_option("new-operational-authority")?,
+                new_vote_authority : values.get_option("new-vote-authority")?,
+                new_validator_identity : values.get_option("new-validator-identity")?
+            },
+            export : values.get_option("export")?
+        }),
+        "incident" => (values.get_option("fee-payer")?, Command::Incident {
+            compromised : values.get("compromised")?,
+            replacements : Rotation {
+                withdraw_authority : values.get_option("withdraw-authority")?,
+                administrator : values.get_option("administrator")?,
+                operational_authority : values.get_option("operational-authority")?,
+                new_administrator : values.get_option("new-administrator")?,
+                new_rewards_authority : values.get_option("new-rewards-authority")?,
+                new_operational_authority : values.get_option("new-operational-authority")?,
+                new_vote_authority : values.get_option("new-vote-authority")?,
+                new_validator_identity : values.get_option("new-validator-identity")?
+            },
+            export : values.get_option("export")?
+        }),
         "withdraw" => {
-            let rewards_authority : String =
-                get_arg_value(tokens.next(), args.rewards_authority, "withdraw", "rewards authority");
-
-            (
-                Some(rewards_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "withdraw", "vote account"),
-                Command::Withdraw {
-                    rewards_authority,
-                    recipient : get_arg_value(tokens.next(), args.recipient, "withdraw", "recipient"),
-                    amount : get_option_arg_value(tokens.next(), args.amount, "amount")
+            let rewards_authority : String = values.get("rewards-authority")?;
+
+            let recipients = match (values.get_option("recipient")?, values.get_option::<String>("split")?) {
+                (Some(recipient), _) => vec![(recipient, 10000)],
+                (None, Some(split)) => parse_split(split.split(','))?,
+                (None, None) => {
+                    let split_file : String = values.get("split-file")?;
+                    parse_split(
+                        std::fs::read_to_string(&split_file)
+                            .map_err(|e| format!("Failed to read split file {}: {}", split_file, e))?
+                            .lines()
+                            .map(|line| line.trim())
+                            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    )?
                 }
-            )
+            };
+
+            (Some(rewards_authority.clone()), Command::Withdraw {
+                rewards_authority,
+                recipients,
+                amount : get_amount(&values)?
+            })
+        },
+        "withdraw-and-stake" => {
+            let rewards_authority : String = values.get("rewards-authority")?;
+
+            (Some(rewards_authority.clone()), Command::WithdrawAndStake {
+                rewards_authority,
+                amount : get_amount(&values)?,
+                stake_account : values.get_option("stake-account")?,
+                seed : values.get_option("seed")?,
+                stake_authority : values.get_option("stake-authority")?
+            })
         },
         "set-commission" => {
-            let rewards_authority : String =
-                get_arg_value(tokens.next(), args.rewards_authority, "set-commission", "rewards authority");
-
-            (
-                Some(rewards_authority.clone()),
-                get_arg_value::<String>(tokens.next(), args.vote_account, "set-commission", "vote account"),
-                Command::SetCommission {
-                    rewards_authority,
-                    commission : get_arg_value(tokens.next(), args.commission, "set-commission", "commission")
-                }
-            )
+            let rewards_authority : String = values.get("rewards-authority")?;
+
+            (Some(rewards_authority.clone()), Command::SetCommission {
+                rewards_authority,
+                commission : values.get("commission")?
+            })
         },
+        // The script is parsed by parse_batch_script when the batch is run
+        "batch" => (values.get_option("fee-payer")?, Command::Batch {
+            script : values.get("script")?,
+            profile : values.get_option("profile")?
+        }),
         "show" => {
-            (None, get_arg_value::<String>(tokens.next(), args.vote_account, "show", "vote account"), Command::Show {
-                json : if args.json.is_some() {
-                    true
-                }
-                else {
-                    match tokens.next() {
-                        Some(word) => {
-                            if word == "json" {
-                                true
-                            }
-                            else {
-                                error_exit(&format!("Unexpected argument: {}", word))
-                            }
-                        },
-                        None => false
-                    }
+            // Vote accounts may be given as any combination of positional arguments, --vote-account arguments, and
+            // lines of a --vote-accounts-file; a positional "json" is accepted in place of --json
+            let (json_tokens, mut vote_accounts) : (Vec<String>, Vec<String>) =
+                values.get_all("vote-account").into_iter().partition(|token| token == "json");
+
+            if let Some(vote_accounts_file) = values.get_option::<String>("vote-accounts-file")? {
+                vote_accounts.extend(read_vote_accounts_file(&vote_accounts_file)?);
+            }
+
+            if vote_accounts.is_empty() {
+                return Err("show command requires at least one vote account".to_string());
+            }
+
+            let graph = values.get_option::<String>("graph")?;
+
+            if let Some(graph) = &graph {
+                if !GRAPH_FORMATS.contains(&graph.as_str()) {
+                    return Err(format!(
+                        "Unsupported graph format {}, expected one of {}",
+                        graph,
+                        GRAPH_FORMATS.join(", ")
+                    ));
                 }
+            }
+
+            (None, Command::Show { vote_accounts, json : values.is_set("json") || !json_tokens.is_empty(), graph })
+        },
+        "lint" => {
+            let mut vote_accounts = values.get_all("vote-account");
+
+            if let Some(vote_accounts_file) = values.get_option::<String>("vote-accounts-file")? {
+                vote_accounts.extend(read_vote_accounts_file(&vote_accounts_file)?);
+            }
+
+            if vote_accounts.is_empty() {
+                return Err("lint command requires at least one vote account".to_string());
+            }
+
+            let (allow, deny) = (values.get_all("allow"), values.get_all("deny"));
+
+            if let Some(rule) =
+                allow.iter().chain(&deny).find(|rule| (*rule != "all") && !LINT_RULES.contains(&rule.as_str()))
+            {
+                return Err(format!("Unknown lint rule {}, expected all or one of {}", rule, LINT_RULES.join(", ")));
+            }
+
+            (None, Command::Lint {
+                vote_accounts,
+                fee_payer : values.get_option("fee-payer")?,
+                allow,
+                deny,
+                json : values.is_set("json")
+            })
+        },
+        "plan-commission" => (None, Command::PlanCommission {
+            target : values.get_option("target")?,
+            epoch : values.get_option("epoch")?,
+            json : values.is_set("json")
+        }),
+        "history" => (None, Command::History { limit : values.get_option("limit")?, json : values.is_set("json") }),
+        "decode" => (None, Command::Decode { input : values.get("signature, transaction or message")? }),
+        "completions" => {
+            let shell : String = values.get("shell")?;
+
+            if !SHELLS.contains(&shell.as_str()) {
+                return Err(format!("Unsupported shell {}, expected one of {}", shell, SHELLS.join(", ")));
+            }
+
+            (None, Command::Completions { shell })
+        },
+        "man" => (None, Command::Man),
+        "daemon" => {
+            let rewards_authority : String = values.get("rewards-authority")?;
+
+            (Some(rewards_authority.clone()), Command::Daemon {
+                rewards_authority,
+                recipient : values.get("recipient")?,
+                threshold : values.get_option("threshold")?.map(|Sol(lamports)| lamports),
+                buffer : values.get_option("buffer")?.map(|Sol(lamports)| lamports),
+                every_epoch : values.is_set("every-epoch"),
+                poll_interval : values.get_option("poll-interval")?
+            })
+        },
+        "ramp-commission" => {
+            let rewards_authority : String = values.get("rewards-authority")?;
+
+            (Some(rewards_authority.clone()), Command::RampCommission {
+                rewards_authority,
+                target : values.get("target")?,
+                state_file : values.get_option("state-file")?,
+                poll_interval : values.get_option("poll-interval")?
             })
         },
 
-        _ => error_exit(&format!("Unknown command: {}", command))
+        _ => unreachable!("no parser for command {}", spec.name)
+    };
+
+    // Commands which make no RPC requests do not accept the global arguments
+    let global_value = |name : &str| -> Result<Option<String>, String> {
+        if spec.rpc {
+            values.get_option(name)
+        }
+        else {
+            Ok(None)
+        }
+    };
+
+    let rpc_fixture = match (global_value("record-rpc")?, global_value("replay-rpc")?) {
+        (Some(_), Some(_)) => return Err("Only one of --record-rpc and --replay-rpc may be supplied".to_string()),
+        (Some(record_rpc), None) => Some(RpcFixture::Record(record_rpc)),
+        (None, Some(replay_rpc)) => Some(RpcFixture::Replay(replay_rpc)),
+        (None, None) => None
     };
 
-    match tokens.next() {
-        Some(token) => error_exit(&format!("Unexpected argument: {}", token)),
-        None => ()
+    let fee_payer = match maybe_fee_payer {
+        Some(fee_payer) => Some(values.get_option("fee-payer")?.unwrap_or(fee_payer)),
+        None => None
+    };
+
+    Ok((fee_payer, get_url(global_value("url")?), global_value("commitment")?, rpc_fixture, vote_account, command))
+}
+
+pub fn parse_batch_script(
+    path : &str,
+    profile : Option<String>
+) -> Result<Vec<BatchStep>, String>
+{
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read batch script {}: {}", path, e))?;
+
+    parse_script(&contents, path, profile, &Environment::from_process())
+}
+
+// Parses a batch script: one command per line, written as it would be after solana-vamp on the command line but
+// without the arguments that apply to the whole batch.  Blank lines and lines beginning with # are ignored.  The
+// profile of the batch, if any, applies to every command.
+fn parse_script(
+    contents : &str,
+    path : &str,
+    profile : Option<String>,
+    environment : &Environment
+) -> Result<Vec<BatchStep>, String>
+{
+    let mut steps = vec![];
+
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let step = parse_script_line(line, profile.as_ref(), environment)
+            .map_err(|e| format!("{} line {}: {}", path, number + 1, e))?;
+
+        steps.push(step);
     }
 
-    (
-        maybe_fee_payer.map(|fee_payer| args.fee_payer.unwrap_or(fee_payer)),
-        get_url(args.url),
-        args.commitment,
-        vote_account,
-        command
-    )
+    if steps.is_empty() {
+        return Err(format!("Batch script {} contains no commands", path));
+    }
+
+    Ok(steps)
+}
+
+fn parse_script_line(
+    line : &str,
+    profile : Option<&String>,
+    environment : &Environment
+) -> Result<BatchStep, String>
+{
+    let mut tokens = line.split_whitespace().map(String::from).collect::<Vec<String>>();
+
+    if !BATCH_COMMANDS.contains(&tokens[0].as_str()) {
+        return Err(format!("{} cannot be used in a batch", tokens[0]));
+    }
+
+    for token in &tokens {
+        if (token == "-h") || (token == "--help") || (token == "help") {
+            return Err("help cannot be used in a batch".to_string());
+        }
+        if let Some(arg) = find_flag(token) {
+            if (arg.name == "fee-payer") || GLOBAL_ARGS.iter().any(|global| global.name == arg.name) {
+                return Err(format!(
+                    "--{} applies to the whole batch and must be given to the batch command",
+                    arg.name
+                ));
+            }
+        }
+    }
+
+    if let Some(profile) = profile {
+        tokens.extend(["--profile".to_string(), profile.clone()]);
+    }
+
+    let (fee_payer, _, _, _, vote_account, command) = parse_command_line(tokens, environment)?;
+
+    if let Command::EnterInteractive { .. } = command {
+        return Err("enter --interactive cannot be used in a batch".to_string());
+    }
+
+    // Every command that may be batched requires a vote account
+    Ok((fee_payer, vote_account.unwrap(), command))
 }
 
 const DEFAULT_MAINNET_RPC_URL : &str = "https://api.mainnet-beta.solana.com";
@@ -287,259 +601,349 @@ const DEFAULT_TESTNET_RPC_URL : &str = "https://api.testnet.solana.com";
 const DEFAULT_DEVNET_RPC_URL : &str = "https://api.devnet.solana.com";
 const DEFAULT_LOCALHOST_RPC_URL : &str = "http://localhost:8899";
 
+// The command line, split into flag arguments and other tokens, before it is checked against the spec of the command
 #[derive(Default)]
 struct Args
 {
-    fee_payer : Option<String>,
+    // Each flag argument supplied, by name, in the order supplied; switches have an empty value
+    flags : Vec<(&'static str, String)>,
 
-    url : Option<String>,
+    help : bool,
 
-    commitment : Option<String>,
+    // The command name followed by any positional arguments
+    tokens : Vec<String>
+}
 
-    vote_account : Option<String>,
+// Returns the spec of the argument with the given flag, which is the same for every command that accepts it
+fn find_flag(flag : &str) -> Option<&'static ArgSpec>
+{
+    GLOBAL_ARGS.iter().chain(COMMANDS.iter().flat_map(|spec| spec.args.iter())).find(|arg| {
+        (arg.position != Position::Positional) &&
+            ((flag.strip_prefix("--") == Some(arg.name)) ||
+                arg.short.map(|short| flag == format!("-{}", short)).unwrap_or(false))
+    })
+}
 
-    withdraw_authority : Option<String>,
+fn parse_args(input_args : Vec<String>) -> Result<Args, String>
+{
+    let mut args = Args::default();
 
-    administrator : Option<String>,
+    let mut input_args = input_args.into_iter();
 
-    operational_authority : Option<String>,
+    while let Some(arg) = input_args.next() {
+        if (arg == "-h") || (arg == "--help") || (arg == "help") {
+            args.help = true;
+            continue;
+        }
 
-    rewards_authority : Option<String>,
+        let spec = match find_flag(&arg) {
+            Some(spec) => spec,
+            None if arg.starts_with("--") => return Err(format!("Unknown argument: {}", arg)),
+            None => {
+                args.tokens.push(arg);
+                continue;
+            }
+        };
 
-    vote_authority : Option<String>,
+        let value = match spec.value_type {
+            ValueType::Switch => String::new(),
+            value_type => {
+                let mut value = input_args.next().ok_or_else(|| format!("{} requires an argument", arg))?;
+                // Allow "--amount ALL-BUT <SOL>" as well as "--amount 'ALL-BUT <SOL>'"
+                if (value_type == ValueType::Amount) && value.eq_ignore_ascii_case("ALL-BUT") {
+                    value = format!(
+                        "{} {}",
+                        value,
+                        input_args.next().ok_or_else(|| format!("{} requires an argument", arg))?
+                    );
+                }
+                value
+            }
+        };
 
-    max_commission : Option<String>,
+        args.flags.push((spec.name, value));
+    }
 
-    max_commission_increase_per_epoch : Option<String>,
+    Ok(args)
+}
 
-    leave_epoch : Option<String>,
+// The values of the arguments of a command, checked against its spec
+struct Values
+{
+    spec : &'static CommandSpec,
 
-    validator_identity : Option<String>,
+    values : Vec<(&'static str, String)>
+}
 
-    recipient : Option<String>,
+// Checks the arguments of a command line against the spec of its command, assigning positional arguments to the
+// arguments of the spec that were not supplied as flags, in order, and then taking arguments that were not supplied
+// at all from the environment
+fn resolve(
+    spec : &'static CommandSpec,
+    args : Args,
+    environment : &Environment
+) -> Result<Values, String>
+{
+    let mut values = args.flags;
 
-    amount : Option<String>,
+    for (name, _) in &values {
+        let arg = spec.arg(name).ok_or_else(|| format!("--{} is not accepted by the {} command", name, spec.name))?;
+        if (arg.occurs != Occurs::Repeated) && (values.iter().filter(|(other, _)| other == name).count() > 1) {
+            return Err(format!("Duplicate --{}", name));
+        }
+    }
 
-    commission : Option<String>,
+    let supplied_as_flag = values.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
 
-    json : Option<bool>,
+    let mut tokens = args.tokens.into_iter().skip(1);
 
-    help : bool,
+    for arg in spec.args.iter().filter(|arg| arg.position != Position::Flag) {
+        if arg.occurs == Occurs::Repeated {
+            values.extend(tokens.by_ref().map(|token| (arg.name, token)));
+            continue;
+        }
 
-    tokens : Vec<String>
+        // A positional argument is not expected if its value, or the value of an argument excluding it, was
+        // supplied as a flag
+        let excluded = spec
+            .exclusive_group(arg.name)
+            .map(|(names, _)| names.to_vec())
+            .unwrap_or_else(|| vec![arg.name])
+            .iter()
+            .any(|name| supplied_as_flag.contains(name));
+
+        if !excluded {
+            if let Some(token) = tokens.next() {
+                values.push((arg.name, token));
+            }
+        }
+    }
+
+    if let Some(token) = tokens.next() {
+        return Err(format!("Unexpected argument: {}", token));
+    }
+
+    if spec.accepted_args().any(|arg| arg.env) {
+        let profile = values.iter().find(|(name, _)| *name == "profile").map(|(_, profile)| profile.clone());
+
+        let defaults = environment.load(profile, |name| {
+            GLOBAL_ARGS
+                .iter()
+                .chain(COMMANDS.iter().flat_map(|spec| spec.args.iter()))
+                .any(|arg| arg.env && (arg.name == name))
+        })?;
+
+        let supplied = values.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+
+        // An argument is not defaulted if it, or an argument in a group with it, was supplied
+        let defaulted = spec
+            .accepted_args()
+            .filter(|arg| arg.env && !spec.related_args(arg.name).iter().any(|name| supplied.contains(name)))
+            .filter_map(|arg| defaults.value(arg).map(|value| (arg.name, value)))
+            .collect::<Vec<(&'static str, String)>>();
+
+        values.extend(defaulted);
+    }
+
+    let is_set = |name : &str| values.iter().any(|(other, _)| *other == name);
+
+    // A command run with --interactive prompts for the required arguments that were not supplied
+    let interactive = spec.arg("interactive").is_some() && is_set("interactive");
+
+    if let Some(arg) =
+        spec.args.iter().find(|arg| !interactive && (arg.occurs == Occurs::Required) && !is_set(arg.name))
+    {
+        return Err(format!("{} command requires exactly one {}", spec.name, arg.description()));
+    }
+
+    for group in spec.groups {
+        let (names, min, max) = match group {
+            Group::Choice(names) => (names, 1, 1),
+            Group::Exclusive(names) => (names, 0, 1),
+            Group::AnyOf(names) => (names, 1, names.len())
+        };
+        let count = names.iter().filter(|name| is_set(name)).count();
+        if count < min {
+            return Err(format!("{} command requires one of {}", spec.name, join_flags(names, "or")));
+        }
+        if count > max {
+            return Err(format!("Only one of {} may be supplied", join_flags(names, "and")));
+        }
+    }
+
+    for (name, value) in &values {
+        check_value(spec.arg(name).unwrap(), value)?;
+    }
+
+    Ok(Values { spec, values })
 }
 
-fn get_arg(
-    index : usize,
-    args : &Vec<String>
+// Joins flags as "--a", "--a or --b", "--a, --b or --c"
+fn join_flags(
+    names : &[&str],
+    conjunction : &str
 ) -> String
 {
-    if index == args.len() {
-        error_exit(&format!("{} requires an argument", args[index - 1]));
+    let flags = names.iter().map(|name| format!("--{}", name)).collect::<Vec<String>>();
+
+    match flags.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} {} {}", rest.join(", "), conjunction, last),
+        _ => flags.join("")
     }
+}
+
+fn check_value(
+    arg : &ArgSpec,
+    value : &str
+) -> Result<(), String>
+{
+    let invalid = |e : String| format!("Invalid value for {}: {}", arg.description(), e);
 
-    args[index].clone()
+    match arg.value_type {
+        ValueType::Integer => value.parse::<u64>().map(|_| ()).map_err(|e| invalid(e.to_string())),
+        ValueType::Percent => match value.parse::<u8>() {
+            Ok(percent) if percent <= 100 => Ok(()),
+            Ok(_) => Err(invalid("must be at most 100".to_string())),
+            Err(e) => Err(invalid(e.to_string()))
+        },
+        ValueType::Amount => value.parse::<Amount>().map(|_| ()).map_err(invalid),
+        ValueType::Sol => value.parse::<Sol>().map(|_| ()).map_err(invalid),
+        ValueType::Keypair |
+        ValueType::Address |
+        ValueType::File |
+        ValueType::Profile |
+        ValueType::Text |
+        ValueType::Switch => Ok(())
+    }
 }
 
-fn parse_args() -> Args
+impl Values
 {
-    let mut args = Args::default();
+    fn lookup<'a>(
+        &'a self,
+        name : &'a str
+    ) -> impl Iterator<Item = &'a String>
+    {
+        // Reading an argument that the spec does not declare is a bug in the parser of the command
+        assert!(self.spec.arg(name).is_some(), "--{} is not in the spec of the {} command", name, self.spec.name);
 
-    let input_args = std::env::args().skip(1).collect::<Vec<String>>();
+        self.values.iter().filter(move |(other, _)| *other == name).map(|(_, value)| value)
+    }
 
-    let mut i = 0;
+    // The value of a required argument
+    fn get<T>(
+        &self,
+        name : &str
+    ) -> Result<T, String>
+    where
+        T : std::str::FromStr,
+        T::Err : std::fmt::Display
+    {
+        self.get_option(name)?.ok_or_else(|| format!("{} command requires exactly one {}", self.spec.name, name))
+    }
 
-    while i < input_args.len() {
-        let arg = &input_args[i];
-        match arg.as_str() {
-            "-f" | "--fee-payer" => {
-                if args.fee_payer.is_none() {
-                    i += 1;
-                    args.fee_payer = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate fee payer");
-                }
-            },
-            "-u" | "--url" => {
-                if args.url.is_none() {
-                    i += 1;
-                    args.url = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate url");
-                }
-            },
-            "-c" | "--commitment" => {
-                if args.commitment.is_none() {
-                    i += 1;
-                    args.commitment = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate commitment");
-                }
-            },
-            "--vote-account" => {
-                if args.vote_account.is_none() {
-                    i += 1;
-                    args.vote_account = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --vote-account");
-                }
-            },
-            "--withdraw-authority" => {
-                if args.withdraw_authority.is_none() {
-                    i += 1;
-                    args.withdraw_authority = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --withdraw-authority");
-                }
-            },
-            "--administrator" => {
-                if args.administrator.is_none() {
-                    i += 1;
-                    args.administrator = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --administrator");
-                }
-            },
-            "--operational-authority" => {
-                if args.operational_authority.is_none() {
-                    i += 1;
-                    args.operational_authority = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --operational-authority");
-                }
-            },
-            "--rewards-authority" => {
-                if args.rewards_authority.is_none() {
-                    i += 1;
-                    args.rewards_authority = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --rewards-authority");
-                }
-            },
-            "--vote-authority" => {
-                if args.vote_authority.is_none() {
-                    i += 1;
-                    args.vote_authority = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --vote-authority");
-                }
-            },
-            "--max-commission" => {
-                if args.max_commission.is_none() {
-                    i += 1;
-                    args.max_commission = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --max-commission");
-                }
-            },
-            "--max-commission-increase-per-epoch" => {
-                if args.max_commission_increase_per_epoch.is_none() {
-                    i += 1;
-                    args.max_commission_increase_per_epoch = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --max-commission-increase-per-epoch");
-                }
-            },
-            "--leave-epoch" => {
-                if args.leave_epoch.is_none() {
-                    i += 1;
-                    args.leave_epoch = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --leave-epoch");
-                }
-            },
-            "--validator-identity" => {
-                if args.validator_identity.is_none() {
-                    i += 1;
-                    args.validator_identity = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --validator-identity");
-                }
-            },
-            "--recipient" => {
-                if args.recipient.is_none() {
-                    i += 1;
-                    args.recipient = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --recipient");
-                }
-            },
-            "--amount" => {
-                if args.amount.is_none() {
-                    i += 1;
-                    args.amount = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --amount");
-                }
-            },
-            "--commission" => {
-                if args.amount.is_none() {
-                    i += 1;
-                    args.commission = Some(get_arg(i, &input_args));
-                }
-                else {
-                    error_exit("Duplicate --commission");
-                }
-            },
-            "--json" => {
-                if args.json.is_none() {
-                    args.json = Some(true)
-                }
-                else {
-                    error_exit("Duplicate --json");
-                }
-            },
-            "-h" | "--help" | "help" => args.help = true,
-            _ => args.tokens.push(arg.clone())
+    fn get_option<T>(
+        &self,
+        name : &str
+    ) -> Result<Option<T>, String>
+    where
+        T : std::str::FromStr,
+        T::Err : std::fmt::Display
+    {
+        self.lookup(name)
+            .next()
+            .map(|value| value.parse::<T>().map_err(|e| format!("Invalid value for {}: {}", name, e)))
+            .transpose()
+    }
+
+    fn get_all(
+        &self,
+        name : &str
+    ) -> Vec<String>
+    {
+        self.lookup(name).cloned().collect()
+    }
+
+    fn is_set(
+        &self,
+        name : &str
+    ) -> bool
+    {
+        self.lookup(name).next().is_some()
+    }
+}
+
+// An amount may be given by --amount (or positionally) or by --lamports, but not both.  As for --amount, --lamports
+// may not be 0, which the program would take to mean everything.
+fn get_amount(values : &Values) -> Result<Option<Amount>, String>
+{
+    match values.get_option("lamports")? {
+        Some(0) => {
+            Err("Invalid value for --lamports: must be more than 0; use --amount ALL to withdraw everything"
+                .to_string())
+        },
+        Some(lamports) => Ok(Some(Amount::Lamports(lamports))),
+        None => values.get_option("amount")
+    }
+}
+
+// Parses RECIPIENT:PERCENT entries into (recipient, basis points) pairs, requiring the percentages to total 100
+fn parse_split<'a>(entries : impl Iterator<Item = &'a str>) -> Result<Vec<(String, u32)>, String>
+{
+    let mut recipients : Vec<(String, u32)> = vec![];
+
+    for entry in entries {
+        // Split at the last colon, since a keypair file path could itself contain a colon
+        let (recipient, percentage) = entry
+            .trim()
+            .rsplit_once(':')
+            .ok_or_else(|| format!("Invalid split entry {}, expected RECIPIENT:PERCENT", entry))?;
+        let basis_points = parse_percentage(percentage.trim())
+            .filter(|basis_points| *basis_points > 0)
+            .ok_or_else(|| format!("Invalid percentage in split entry {}", entry))?;
+        if recipients.iter().any(|(existing, _)| existing == recipient) {
+            return Err(format!("Duplicate split recipient {}", recipient));
         }
-        i += 1;
+        recipients.push((recipient.to_string(), basis_points));
+    }
+
+    if recipients.iter().map(|(_, basis_points)| basis_points).sum::<u32>() != 10000 {
+        return Err("Split percentages must total 100".to_string());
     }
 
-    args
+    Ok(recipients)
 }
 
-fn get_arg_value<T>(
-    token : Option<String>,
-    arg : Option<String>,
-    command : &str,
-    name : &str
-) -> T
-where
-    T : std::str::FromStr
+// Parses a percentage with up to two fractional digits into basis points
+fn parse_percentage(s : &str) -> Option<u32>
 {
-    str::parse::<T>(
-        &token.xor(arg).unwrap_or_else(|| error_exit(&format!("{} command requires exactly one {}", command, name)))
-    )
-    .map_err(|_| error_exit(&format!("Invalid value for {}", name)))
-    .unwrap()
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+
+    if whole.is_empty() || (fraction.len() > 2) || !(whole.to_string() + fraction).bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let basis_points =
+        whole.parse::<u32>().ok()?.checked_mul(100)? + format!("{:0<2}", fraction).parse::<u32>().ok()?;
+
+    if basis_points > 10000 {
+        None
+    }
+    else {
+        Some(basis_points)
+    }
 }
 
-fn get_option_arg_value<T>(
-    token : Option<String>,
-    arg : Option<String>,
-    name : &str
-) -> Option<T>
-where
-    T : std::str::FromStr
+// Reads vote accounts from a file, one per line; blank lines and lines beginning with # are ignored
+fn read_vote_accounts_file(path : &str) -> Result<Vec<String>, String>
 {
-    token
-        .xor(arg)
-        .map(|token| str::parse::<T>(&token).map_err(|_| error_exit(&format!("Invalid value for {}", name))).unwrap())
+    Ok(std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read vote accounts file {}: {}", path, e))?
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect())
 }
 
 fn get_url(url : Option<String>) -> String
@@ -558,27 +962,620 @@ fn get_url(url : Option<String>) -> String
 
 fn do_help(tokens : Vec<String>) -> !
 {
-    let msg = if tokens.len() == 0 {
-        usage::USAGE_MESSAGE
+    println!("{}", help_message(&tokens));
+
+    std::process::exit(0);
+}
+
+// The help on the command or argument named by the first token, or the usage message
+fn help_message(tokens : &[String]) -> String
+{
+    match tokens.first().map(|token| token.as_str()) {
+        Some("record-rpc" | "replay-rpc") => usage::RPC_FIXTURE_HELP.to_string(),
+        topic => match topic.and_then(find_command) {
+            Some(spec) => spec.help_message(),
+            None => usage_message()
+        }
     }
-    else {
-        match tokens[0].as_str() {
-            "enter" => usage::ENTER_USAGE_MESSAGE,
-            "set-leave-epoch" => usage::SET_LEAVE_EPOCH_USAGE_MESSAGE,
-            "leave" => usage::LEAVE_USAGE_MESSAGE,
-            "set-administrator" => usage::SET_ADMINISTRATOR_USAGE_MESSAGE,
-            "set-operational-authority" => usage::SET_OPERATIONAL_AUTHORITY_USAGE_MESSAGE,
-            "set-rewards-authority" => usage::SET_REWARDS_AUTHORITY_USAGE_MESSAGE,
-            "set-vote-authority" => usage::SET_VOTE_AUTHORITY_USAGE_MESSAGE,
-            "set-validator-identity" => usage::SET_VALIDATOR_IDENTITY_USAGE_MESSAGE,
-            "withdraw" => usage::WITHDRAW_USAGE_MESSAGE,
-            "set-commission" => usage::SET_COMMISSION_USAGE_MESSAGE,
-            "show" => usage::SHOW_USAGE_MESSAGE,
-            _ => usage::USAGE_MESSAGE
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    const PUBKEY : &str = "11111111111111111111111111111111";
+
+    // A file which is valid as any file argument: as a split file, a vote accounts file, or a state file.  It is
+    // removed when dropped.
+    struct SampleFile
+    {
+        path : String
+    }
+
+    impl SampleFile
+    {
+        fn new() -> Self
+        {
+            let path = std::env::temp_dir().join(format!("solana-vamp-args-test-{}", Pubkey::new_unique()));
+            std::fs::write(&path, format!("{}:100\n", PUBKEY)).unwrap();
+            SampleFile { path : path.to_str().unwrap().to_string() }
         }
-    };
+    }
+
+    impl Drop for SampleFile
+    {
+        fn drop(&mut self)
+        {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
 
-    println!("{}", msg);
+    fn sample_value(
+        arg : &ArgSpec,
+        file : &SampleFile
+    ) -> Option<String>
+    {
+        match arg.value_type {
+            ValueType::Keypair => Some("keypair.json".to_string()),
+            ValueType::Address => Some(PUBKEY.to_string()),
+            ValueType::File => Some(file.path.clone()),
+            ValueType::Integer => Some("100".to_string()),
+            ValueType::Percent => Some("5".to_string()),
+            ValueType::Amount | ValueType::Sol => Some("1.5".to_string()),
+            ValueType::Profile => Some("default".to_string()),
+            ValueType::Text => {
+                Some(arg.suggestions.first().map(|s| s.to_string()).unwrap_or(format!("{}:100", PUBKEY)))
+            },
+            ValueType::Switch => None
+        }
+    }
 
-    std::process::exit(0);
+    fn flag_args(
+        spec : &CommandSpec,
+        name : &str,
+        file : &SampleFile
+    ) -> Vec<String>
+    {
+        let arg = spec.arg(name).unwrap_or_else(|| panic!("usage of {} shows unknown --{}", spec.name, name));
+        std::iter::once(arg.flag()).chain(sample_value(arg, file)).collect()
+    }
+
+    fn parse(args : &[String]) -> Result<String, String>
+    {
+        parse_command_line(args.to_vec(), &Environment::default()).map(|parsed| format!("{:?}", parsed))
+    }
+
+    // The flags shown on a line of usage, which may be a single flag or alternatives separated by |
+    fn usage_flags(line : &str) -> Vec<&str>
+    {
+        line.split(|c : char| c.is_whitespace() || "[]()|".contains(c))
+            .filter_map(|word| word.strip_prefix("--"))
+            .collect::<Vec<&str>>()
+    }
+
+    // Splits the usage of a command into the flags which it shows as required, and each set of alternatives which it
+    // shows in parentheses (one required) or brackets (optional)
+    fn read_usage(spec : &CommandSpec) -> (Vec<&str>, Vec<Vec<&str>>, Vec<Vec<&str>>)
+    {
+        let usage = Box::leak(spec.usage().replace(" |\n", " | ").into_boxed_str());
+
+        let (mut required, mut one_of, mut optional) = (vec![], vec![], vec![]);
+
+        for line in usage.lines().skip(1).map(|line| line.trim()) {
+            let mut flags = usage_flags(line);
+            flags.dedup();
+            if line.starts_with('[') {
+                optional.push(flags);
+            }
+            else if line.starts_with('(') {
+                one_of.push(flags);
+            }
+            else {
+                required.push(flags[0]);
+            }
+        }
+
+        (required, one_of, optional)
+    }
+
+    // The minimal command line shown by the usage of a command: its positional-only arguments, its required flags,
+    // and the first of each set of required alternatives.  The usage does not show groups of which at least one
+    // argument is required, so the first of each such group is added if no other is.
+    fn minimal_command_line(
+        spec : &CommandSpec,
+        file : &SampleFile
+    ) -> Vec<String>
+    {
+        let (required, one_of, _) = read_usage(spec);
+
+        let mut args = vec![spec.name.to_string()];
+
+        for arg in spec.args.iter().filter(|arg| arg.position == Position::Positional) {
+            args.extend(sample_value(arg, file));
+        }
+
+        for name in required.iter().chain(one_of.iter().map(|alternatives| &alternatives[0])) {
+            args.extend(flag_args(spec, name, file));
+        }
+
+        args.extend(any_of_args(spec, &args, file));
+
+        args
+    }
+
+    // The first argument of each group of which at least one argument is required, if args has none of the group
+    fn any_of_args(
+        spec : &CommandSpec,
+        args : &[String],
+        file : &SampleFile
+    ) -> Vec<String>
+    {
+        spec.groups
+            .iter()
+            .filter_map(|group| match group {
+                Group::AnyOf(names) if !names.iter().any(|name| args.contains(&format!("--{}", name))) => {
+                    Some(flag_args(spec, names[0], file))
+                },
+                _ => None
+            })
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn every_command_accepts_its_usage()
+    {
+        let file = SampleFile::new();
+
+        for spec in COMMANDS {
+            let minimal = minimal_command_line(spec, &file);
+
+            assert!(parse(&minimal).is_ok(), "{:?}: {:?}", minimal, parse(&minimal));
+
+            let (required, one_of, optional) = read_usage(spec);
+
+            // Each optional argument is accepted
+            for name in optional.iter().flatten().filter(|name| !minimal.contains(&format!("--{}", name))) {
+                let args = [minimal.clone(), flag_args(spec, name, &file)].concat();
+                assert!(parse(&args).is_ok(), "{:?}: {:?}", args, parse(&args));
+            }
+
+            // Each alternative of a required choice is accepted in place of the first
+            for alternatives in &one_of {
+                for name in &alternatives[1..] {
+                    let mut args = vec![spec.name.to_string()];
+                    for other in required.iter().chain(one_of.iter().map(|other| {
+                        if other == alternatives {
+                            name
+                        }
+                        else {
+                            &other[0]
+                        }
+                    })) {
+                        args.extend(flag_args(spec, other, &file));
+                    }
+                    assert!(parse(&args).is_ok(), "{:?}: {:?}", args, parse(&args));
+                }
+            }
+
+            // Each required argument is required
+            for name in &required {
+                let flag = format!("--{}", name);
+                let mut args = minimal.clone();
+                let i = args.iter().position(|arg| *arg == flag).unwrap();
+                args.drain(i..(i + 2));
+                assert!(parse(&args).is_err(), "{:?} should be rejected", args);
+            }
+
+            // Alternatives are exclusive
+            for alternatives in one_of.iter().chain(optional.iter()).filter(|alternatives| alternatives.len() > 1) {
+                let args = [minimal.clone(), flag_args(spec, alternatives[1], &file)].concat();
+                let args = [args, flag_args(spec, alternatives[0], &file)].concat();
+                assert!(parse(&args).is_err(), "{:?} should be rejected", args);
+            }
+        }
+    }
+
+    #[test]
+    fn every_command_rejects_flags_not_in_its_usage()
+    {
+        let all_flags =
+            COMMANDS.iter().flat_map(|spec| spec.args.iter()).filter(|arg| arg.position != Position::Positional);
+
+        let file = SampleFile::new();
+
+        for spec in COMMANDS {
+            let usage = spec.usage();
+
+            for arg in all_flags.clone() {
+                if usage_flags(&usage).contains(&arg.name) {
+                    continue;
+                }
+                let args = [
+                    minimal_command_line(spec, &file),
+                    std::iter::once(arg.flag()).chain(sample_value(arg, &file)).collect()
+                ];
+                let result = parse(&args.concat());
+                assert_eq!(result, Err(format!("--{} is not accepted by the {} command", arg.name, spec.name)));
+            }
+        }
+    }
+
+    #[test]
+    fn positional_arguments_are_taken_in_spec_order()
+    {
+        let file = SampleFile::new();
+
+        for spec in COMMANDS {
+            let positional = spec.args.iter().filter(|arg| arg.position != Position::Flag);
+
+            let mut as_flags = vec![spec.name.to_string()];
+            let mut as_positional = vec![spec.name.to_string()];
+
+            for arg in positional {
+                let value = sample_value(arg, &file).unwrap();
+                if arg.position == Position::Either {
+                    as_flags.extend([arg.flag(), value.clone()]);
+                }
+                else {
+                    as_flags.push(value.clone());
+                }
+                as_positional.push(value);
+            }
+
+            let required_flags = any_of_args(spec, &as_flags, &file);
+            as_flags.extend(required_flags.clone());
+            as_positional.extend(required_flags);
+
+            assert!(parse(&as_flags).is_ok(), "{:?}: {:?}", as_flags, parse(&as_flags));
+            assert_eq!(parse(&as_positional), parse(&as_flags));
+        }
+
+        // A positional argument fills the first argument not supplied as a flag
+        let parsed = parse(&["set-commission", "--vote-account", PUBKEY, "rewards.json", "10"].map(String::from));
+        assert_eq!(
+            parsed,
+            parse(&["set-commission", "rewards.json", PUBKEY, "10"].map(String::from)),
+            "flags and positional arguments should combine"
+        );
+    }
+
+    #[test]
+    fn interactive_enter_does_not_require_arguments()
+    {
+        assert_eq!(
+            parse(&["enter"].map(String::from)).unwrap_err(),
+            "enter command requires exactly one withdraw authority"
+        );
+
+        let parsed = parse(&["enter", "--interactive", "--max-commission", "10"].map(String::from)).unwrap();
+        assert!(parsed.contains(
+            "EnterInteractive { withdraw_authority: None, administrator: None, max_commission: Some(10), \
+             max_commission_increase_per_epoch: None, fee_payer: None }"
+        ));
+
+        // Values are still checked
+        assert!(parse(&["enter", "--interactive", "--max-commission", "101"].map(String::from)).is_err());
+    }
+
+    #[test]
+    fn batch_scripts_are_parsed_line_by_line()
+    {
+        let script = format!(
+            "# Set up a validator\n\nenter withdraw.json {0} admin.json\n  set-commission --rewards-authority \
+             rewards.json {0} 5\n",
+            PUBKEY
+        );
+
+        let steps = parse_script(&script, "setup.vamp", None, &Environment::default()).unwrap();
+
+        assert_eq!(
+            format!("{:?}", steps),
+            format!(
+                "[(Some(\"withdraw.json\"), \"{0}\", Enter {{ withdraw_authority: \"withdraw.json\", administrator: \
+                 \"admin.json\", max_commission: None, max_commission_increase_per_epoch: None }}), \
+                 (Some(\"rewards.json\"), \"{0}\", SetCommission {{ rewards_authority: \"rewards.json\", commission: \
+                 5 }})]",
+                PUBKEY
+            )
+        );
+
+        let error = |script : &str| parse_script(script, "setup.vamp", None, &Environment::default()).unwrap_err();
+
+        assert_eq!(error("# Nothing\n"), "Batch script setup.vamp contains no commands");
+        assert_eq!(error(&format!("\nshow {}\n", PUBKEY)), "setup.vamp line 2: show cannot be used in a batch");
+        assert_eq!(
+            error(&format!("leave withdraw.json {} --url localhost", PUBKEY)),
+            "setup.vamp line 1: --url applies to the whole batch and must be given to the batch command"
+        );
+        assert_eq!(
+            error(&format!("leave withdraw.json {} -f payer.json", PUBKEY)),
+            "setup.vamp line 1: --fee-payer applies to the whole batch and must be given to the batch command"
+        );
+        assert_eq!(
+            error(&format!("enter --interactive --vote-account {}", PUBKEY)),
+            "setup.vamp line 1: enter --interactive cannot be used in a batch"
+        );
+        assert_eq!(error("leave withdraw.json"), "setup.vamp line 1: leave command requires exactly one vote account");
+    }
+
+    #[test]
+    fn flags_are_not_duplicated()
+    {
+        assert_eq!(
+            parse(&["set-commission", "a", "b", "--commission", "5", "--commission", "6"].map(String::from)),
+            Err("Duplicate --commission".to_string())
+        );
+        assert_eq!(
+            parse(&["withdraw", "a", "b", "c", "--amount", "1", "--amount", "2"].map(String::from)),
+            Err("Duplicate --amount".to_string())
+        );
+        assert!(parse(&["show", "--vote-account", PUBKEY, "--vote-account", PUBKEY].map(String::from)).is_ok());
+    }
+
+    #[test]
+    fn help_documents_every_argument()
+    {
+        for spec in COMMANDS {
+            let documented = spec
+                .help
+                .lines()
+                .filter_map(|line| line.strip_prefix("  --").and_then(|line| line.split_once(':')))
+                .map(|(name, _)| name)
+                .collect::<Vec<&str>>();
+
+            for arg in spec.args.iter().chain(spec.arg("url")) {
+                if arg.position != Position::Positional {
+                    assert!(documented.contains(&arg.name), "help of {} does not document --{}", spec.name, arg.name);
+                }
+            }
+
+            // Every flag mentioned by the help, including in its examples, is accepted by the command
+            for name in usage_flags(spec.help) {
+                let name = name.trim_end_matches(|c : char| !c.is_ascii_alphanumeric());
+                assert!(
+                    spec.arg(name).is_some(),
+                    "help of {} mentions --{}, which it does not accept",
+                    spec.name,
+                    name
+                );
+            }
+        }
+    }
+
+    // A home directory with a solana CLI config and a profile named "validator"
+    fn sample_home(name : &str) -> std::path::PathBuf
+    {
+        let home = std::env::temp_dir().join(format!("solana-vamp-args-home-{}-{}", name, std::process::id()));
+
+        std::fs::create_dir_all(home.join(".config/solana/cli")).unwrap();
+        std::fs::write(
+            home.join(".config/solana/cli/config.yml"),
+            "---\njson_rpc_url: \"http://config:8899\"\nkeypair_path: /keys/id.json\ncommitment: confirmed\n"
+        )
+        .unwrap();
+
+        std::fs::create_dir_all(home.join(crate::environment::PROFILES_DIRECTORY)).unwrap();
+        std::fs::write(
+            home.join(crate::environment::PROFILES_DIRECTORY).join("validator"),
+            "# Validator keys\nurl = http://profile:8899\nrewards-authority = profile_rewards.json\n"
+        )
+        .unwrap();
+
+        home
+    }
+
+    fn parse_with(
+        args : &[&str],
+        vars : &[(&str, &str)],
+        home : &std::path::Path
+    ) -> Result<ParsedCommand, String>
+    {
+        parse_command_line(args.iter().map(|arg| arg.to_string()).collect(), &Environment::new(vars, Some(home)))
+    }
+
+    #[test]
+    fn arguments_not_supplied_are_taken_from_the_environment_in_order_of_precedence()
+    {
+        let home = sample_home("precedence");
+
+        let url = |args : &[&str], vars : &[(&str, &str)]| parse_with(args, vars, &home).map(|parsed| parsed.1);
+
+        let command = ["set-commission", "rewards.json", PUBKEY, "10"];
+
+        // Default, when there is no configuration at all
+        assert_eq!(
+            parse_command_line(command.map(String::from).to_vec(), &Environment::default()).unwrap().1,
+            DEFAULT_MAINNET_RPC_URL
+        );
+
+        // solana CLI config
+        assert_eq!(url(&command, &[]), Ok("http://config:8899".to_string()));
+
+        // Profile, by environment variable or by flag
+        assert_eq!(url(&command, &[("SOLANA_VAMP_PROFILE", "validator")]), Ok("http://profile:8899".to_string()));
+        assert_eq!(
+            url(&[&command[..], &["--profile", "validator"]].concat(), &[]),
+            Ok("http://profile:8899".to_string())
+        );
+
+        // Environment variable
+        let vars = [("SOLANA_VAMP_PROFILE", "validator"), ("SOLANA_VAMP_URL", "devnet")];
+        assert_eq!(url(&command, &vars), Ok(DEFAULT_DEVNET_RPC_URL.to_string()));
+
+        // Flag
+        assert_eq!(url(&[&command[..], &["-u", "testnet"]].concat(), &vars), Ok(DEFAULT_TESTNET_RPC_URL.to_string()));
+
+        // Empty variables are unset
+        assert_eq!(url(&command, &[("SOLANA_VAMP_URL", "")]), Ok("http://config:8899".to_string()));
+
+        let commitment = parse_with(&command, &[("SOLANA_VAMP_COMMITMENT", "processed")], &home).unwrap().2;
+        assert_eq!(commitment, Some("processed".to_string()));
+        assert_eq!(parse_with(&command, &[], &home).unwrap().2, Some("confirmed".to_string()));
+
+        std::fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn authority_keypairs_are_taken_from_the_environment()
+    {
+        let home = sample_home("keypairs");
+
+        let vars = [
+            ("SOLANA_VAMP_REWARDS_AUTHORITY", "env_rewards.json"),
+            ("SOLANA_VAMP_VOTE_ACCOUNT", PUBKEY),
+            ("SOLANA_VAMP_FEE_PAYER", "env_fee_payer.json")
+        ];
+
+        let (fee_payer, _, _, _, vote_account, command) =
+            parse_with(&["set-commission", "--commission", "10"], &vars, &home).unwrap();
+        assert_eq!(fee_payer, Some("env_fee_payer.json".to_string()));
+        assert_eq!(vote_account, Some(PUBKEY.to_string()));
+        assert_eq!(
+            format!("{:?}", command),
+            "SetCommission { rewards_authority: \"env_rewards.json\", commission: 10 }"
+        );
+
+        // The profile supplies the rewards authority when the environment does not; the fee payer defaults to the
+        // rewards authority as usual
+        let (fee_payer, ..) = parse_with(
+            &["set-commission", "--profile", "validator", "--vote-account", PUBKEY, "--commission", "10"],
+            &[],
+            &home
+        )
+        .unwrap();
+        assert_eq!(fee_payer, Some("profile_rewards.json".to_string()));
+
+        // Positional arguments are command line arguments, and so take precedence over the environment
+        let (_, _, _, _, vote_account, _) =
+            parse_with(&["set-commission", "rewards.json", "other_vote.json", "10"], &vars, &home).unwrap();
+        assert_eq!(vote_account, Some("other_vote.json".to_string()));
+
+        // SOLANA_VAMP_REWARDS_AUTHORITY is the keypair that signs, not the new rewards authority to be set
+        let result = parse_with(&["set-rewards-authority", "--administrator", "admin.json"], &vars, &home);
+        assert_eq!(
+            result.err(),
+            Some("set-rewards-authority command requires exactly one rewards authority".to_string())
+        );
+
+        // The vote accounts of show are not defaulted when a file of them is supplied
+        let vote_accounts_file = SampleFile::new();
+        let (.., command) =
+            parse_with(&["show", "--vote-accounts-file", &vote_accounts_file.path], &vars, &home).unwrap();
+        assert!(!format!("{:?}", command).contains(&format!("\"{}\"", PUBKEY)));
+
+        std::fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn profiles_are_checked()
+    {
+        let home = sample_home("profiles");
+
+        let profiles = home.join(crate::environment::PROFILES_DIRECTORY);
+
+        std::fs::write(profiles.join("bad"), "amount = 5\n").unwrap();
+
+        let result = parse_with(&["leave", "--profile", "bad"], &[], &home).err().unwrap();
+        assert_eq!(result, "Unknown setting amount in profile bad");
+
+        let result = parse_with(&["leave"], &[("SOLANA_VAMP_PROFILE", "missing")], &home).err().unwrap();
+        assert!(result.starts_with("Failed to read profile missing"), "{}", result);
+
+        std::fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn zero_amounts_are_rejected()
+    {
+        let file = SampleFile::new();
+
+        for name in ["withdraw", "withdraw-and-stake"] {
+            let minimal = minimal_command_line(COMMANDS.iter().find(|spec| spec.name == name).unwrap(), &file);
+            let without_amount = minimal.iter().filter(|arg| *arg != "1.5").cloned().collect::<Vec<String>>();
+
+            let args = [without_amount.clone(), vec!["0".to_string()]].concat();
+            assert_eq!(
+                parse(&args),
+                Err("Invalid value for amount: must be more than 0 SOL; use ALL to withdraw everything".to_string())
+            );
+
+            let args = [without_amount.clone(), vec!["--lamports".to_string(), "0".to_string()]].concat();
+            assert_eq!(
+                parse(&args),
+                Err("Invalid value for --lamports: must be more than 0; use --amount ALL to withdraw everything"
+                    .to_string())
+            );
+
+            let args = [without_amount, vec!["--lamports".to_string(), "1".to_string()]].concat();
+            assert!(parse(&args).unwrap().contains("amount: Some(Lamports(1))"), "{:?}", parse(&args));
+        }
+    }
+
+    #[test]
+    fn splits_and_files_are_checked()
+    {
+        let withdraw = |split : &[&str]| {
+            parse(
+                &[&["withdraw", "rewards.json", PUBKEY, "1"], split]
+                    .concat()
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect::<Vec<String>>()
+            )
+        };
+
+        let other = "Vote111111111111111111111111111111111111111";
+
+        assert!(withdraw(&["--split", &format!("{}:60,{}:40", PUBKEY, other)]).is_ok());
+        assert_eq!(
+            withdraw(&["--split", &format!("{}:60,{}:30", PUBKEY, other)]),
+            Err("Split percentages must total 100".to_string())
+        );
+        assert_eq!(
+            withdraw(&["--split", &format!("{}:50,{}:50", PUBKEY, PUBKEY)]),
+            Err(format!("Duplicate split recipient {}", PUBKEY))
+        );
+        assert_eq!(
+            withdraw(&["--split", PUBKEY]),
+            Err(format!("Invalid split entry {}, expected RECIPIENT:PERCENT", PUBKEY))
+        );
+
+        let missing = std::env::temp_dir().join(format!("solana-vamp-args-test-{}", Pubkey::new_unique()));
+        let missing = missing.to_str().unwrap();
+
+        let result = withdraw(&["--split-file", missing]).unwrap_err();
+        assert!(result.starts_with(&format!("Failed to read split file {}", missing)), "{}", result);
+
+        let result = parse(&["show", "--vote-accounts-file", missing].map(String::from)).unwrap_err();
+        assert!(result.starts_with(&format!("Failed to read vote accounts file {}", missing)), "{}", result);
+    }
+
+    #[test]
+    fn rpc_fixture_arguments_have_their_own_help()
+    {
+        for topic in ["record-rpc", "replay-rpc"] {
+            assert_eq!(help_message(&[topic.to_string()]), usage::RPC_FIXTURE_HELP);
+        }
+
+        assert!(!usage_message().contains("--record-rpc"));
+        assert_eq!(help_message(&["enter".to_string()]), find_command("enter").unwrap().help_message());
+    }
+
+    #[test]
+    fn readme_shows_usage()
+    {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/README.md");
+
+        let readme = format!("```{}```\n", usage_message());
+
+        // Run with SOLANA_VAMP_UPDATE_README set to regenerate README.md from the command specs
+        if std::env::var("SOLANA_VAMP_UPDATE_README").is_ok() {
+            std::fs::write(path, &readme).unwrap();
+        }
+
+        assert_eq!(std::fs::read_to_string(path).unwrap(), readme, "README.md is out of date");
+    }
 }
@@ -0,0 +1,356 @@
+// Submission of the instructions of several commands in as few transactions as possible, for 'solana-vamp batch'.
+// Instructions are packed in order into each transaction until the next would not fit, so that a sequence of commands
+// such as enter, set-operational-authority and set-rewards-authority takes effect atomically when it fits in one
+// transaction, and otherwise in order across several.
+
+use crate::args::{BatchStep, Command};
+use crate::rpc::Rpc;
+use crate::{commission_caps, error_exit, get_latest_blockhash, load_keypair_helper, make_pubkey_helper};
+use crate::{submit_transaction, PACKET_DATA_SIZE};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use solana_vamp::instruction::{self, get_vote_account_manager_state_account};
+use solana_vamp::transaction_data::VampInstruction;
+use std::ops::Range;
+
+// The instruction of one command of a batch, with the keypairs other than the fee payer that must sign it
+struct Step
+{
+    instruction : Instruction,
+
+    signers : Vec<Keypair>
+}
+
+pub fn run(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    fee_payer : &Keypair,
+    batch : Vec<BatchStep>
+)
+{
+    let steps = batch
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, vote_account, command))| {
+            make_pubkey_helper(&vote_account)
+                .map_err(|e| format!("Failed to create vote account pubkey: {}", e))
+                .and_then(|vote_account| step(program_id, vote_account, fee_payer.pubkey(), command))
+                .unwrap_or_else(|e| error_exit(&format!("Command {} of the batch: {}", i + 1, e)))
+        })
+        .collect::<Vec<Step>>();
+
+    let groups = pack(&steps, &fee_payer.pubkey()).unwrap_or_else(|e| error_exit(&e));
+
+    for (i, group) in groups.iter().enumerate() {
+        if groups.len() > 1 {
+            println!("Transaction {} of {}: commands {} to {}", i + 1, groups.len(), group.start + 1, group.end);
+        }
+
+        let steps = &steps[group.clone()];
+
+        let tx = Transaction::new_signed_with_payer(
+            &steps.iter().map(|step| step.instruction.clone()).collect::<Vec<Instruction>>(),
+            Some(&fee_payer.pubkey()),
+            &signers(steps, fee_payer),
+            get_latest_blockhash(rpc_client)
+        );
+
+        // submit_transaction exits on failure, so no transaction is submitted after one that fails
+        submit_transaction(rpc_client, &tx)
+    }
+}
+
+fn step(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    fee_payer : Pubkey,
+    command : Command
+) -> Result<Step, String>
+{
+    let vote_account_manager_state_account = get_vote_account_manager_state_account(&program_id, &vote_account);
+
+    let keypair = |path : &str, desc : &str| {
+        load_keypair_helper(path).map_err(|e| format!("Failed to load {} keypair: {}", desc, e))
+    };
+
+    let pubkey =
+        |s : &str, desc : &str| make_pubkey_helper(s).map_err(|e| format!("Failed to create {} pubkey: {}", desc, e));
+
+    let set_authority = |authority : Keypair, data : VampInstruction| Step {
+        instruction : instruction::set_authority(
+            program_id,
+            vote_account,
+            vote_account_manager_state_account,
+            authority.pubkey(),
+            data
+        ),
+        signers : vec![authority]
+    };
+
+    Ok(match command {
+        Command::Enter { withdraw_authority, administrator, max_commission, max_commission_increase_per_epoch } => {
+            let withdraw_authority = keypair(&withdraw_authority, "withdraw authority")?;
+            Step {
+                instruction : instruction::enter(
+                    program_id,
+                    vote_account,
+                    vote_account_manager_state_account,
+                    fee_payer,
+                    withdraw_authority.pubkey(),
+                    pubkey(&administrator, "administrator")?,
+                    commission_caps(max_commission, max_commission_increase_per_epoch)
+                ),
+                signers : vec![withdraw_authority]
+            }
+        },
+        Command::SetLeaveEpoch { withdraw_authority, leave_epoch } => {
+            let withdraw_authority = keypair(&withdraw_authority, "withdraw authority")?;
+            Step {
+                instruction : instruction::set_leave_epoch(
+                    program_id,
+                    vote_account,
+                    vote_account_manager_state_account,
+                    withdraw_authority.pubkey(),
+                    leave_epoch
+                ),
+                signers : vec![withdraw_authority]
+            }
+        },
+        Command::Leave { withdraw_authority } => {
+            let withdraw_authority = keypair(&withdraw_authority, "withdraw authority")?;
+            // Lamports recipient -- assume fee payer
+            Step {
+                instruction : instruction::leave(
+                    program_id,
+                    vote_account,
+                    vote_account_manager_state_account,
+                    withdraw_authority.pubkey(),
+                    fee_payer
+                ),
+                signers : vec![withdraw_authority]
+            }
+        },
+        Command::SetAdministrator { withdraw_authority, administrator } => {
+            set_authority(keypair(&withdraw_authority, "withdraw authority")?, VampInstruction::SetAdministrator {
+                new_administrator : pubkey(&administrator, "administrator")?.to_bytes()
+            })
+        },
+        Command::SetOperationalAuthority { administrator, authority } => {
+            set_authority(keypair(&administrator, "administrator")?, VampInstruction::SetOperationalAuthority {
+                new_authority : pubkey(&authority, "operational authority")?.to_bytes()
+            })
+        },
+        Command::SetRewardsAuthority { administrator, authority } => {
+            set_authority(keypair(&administrator, "administrator")?, VampInstruction::SetRewardsAuthority {
+                new_authority : pubkey(&authority, "rewards authority")?.to_bytes()
+            })
+        },
+        Command::SetVoteAuthority { operational_authority, authority } => {
+            let operational_authority = keypair(&operational_authority, "operational authority")?;
+            Step {
+                instruction : instruction::set_vote_authority(
+                    program_id,
+                    vote_account,
+                    vote_account_manager_state_account,
+                    operational_authority.pubkey(),
+                    pubkey(&authority, "vote authority")?
+                ),
+                signers : vec![operational_authority]
+            }
+        },
+        Command::SetValidatorIdentity { operational_authority, validator_identity } => {
+            let operational_authority = keypair(&operational_authority, "operational authority")?;
+            let new_identity = keypair(&validator_identity, "validator identity")?;
+            Step {
+                instruction : instruction::set_validator_identity(
+                    program_id,
+                    vote_account,
+                    vote_account_manager_state_account,
+                    operational_authority.pubkey(),
+                    new_identity.pubkey()
+                ),
+                signers : vec![operational_authority, new_identity]
+            }
+        },
+        Command::SetCommission { rewards_authority, commission } => {
+            let rewards_authority = keypair(&rewards_authority, "rewards authority")?;
+            Step {
+                instruction : instruction::set_commission(
+                    program_id,
+                    vote_account,
+                    vote_account_manager_state_account,
+                    rewards_authority.pubkey(),
+                    commission
+                ),
+                signers : vec![rewards_authority]
+            }
+        },
+
+        // The batch script parser accepts only the commands above
+        command => unreachable!("{:?} cannot be batched", command)
+    })
+}
+
+// Divides steps, in order, into ranges which each fit in a single transaction paid for by fee_payer
+fn pack(
+    steps : &[Step],
+    fee_payer : &Pubkey
+) -> Result<Vec<Range<usize>>, String>
+{
+    let mut groups : Vec<Range<usize>> = vec![];
+
+    for i in 0..steps.len() {
+        match groups.last_mut() {
+            Some(group) if fits(&steps[group.start..=i], fee_payer) => group.end = i + 1,
+            _ if fits(&steps[i..=i], fee_payer) => groups.push(i..(i + 1)),
+            _ => return Err(format!("Command {} of the batch is too large for a transaction", i + 1))
+        }
+    }
+
+    Ok(groups)
+}
+
+fn fits(
+    steps : &[Step],
+    fee_payer : &Pubkey
+) -> bool
+{
+    let message =
+        Message::new(&steps.iter().map(|step| step.instruction.clone()).collect::<Vec<Instruction>>(), Some(fee_payer));
+
+    // A serialized transaction is a compact array of signatures followed by the serialized message
+    (1 + ((message.header.num_required_signatures as usize) * 64) + message.serialize().len()) <= PACKET_DATA_SIZE
+}
+
+// The fee payer followed by every other keypair that must sign the steps, each only once
+fn signers<'a>(
+    steps : &'a [Step],
+    fee_payer : &'a Keypair
+) -> Vec<&'a Keypair>
+{
+    let mut signers = vec![fee_payer];
+
+    for signer in steps.iter().flat_map(|step| step.signers.iter()) {
+        if !signers.iter().any(|existing| existing.pubkey() == signer.pubkey()) {
+            signers.push(signer);
+        }
+    }
+
+    signers
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::mock::{KeypairFile, MockRpc};
+    use crate::VAMP_PROGRAM_PUBKEY;
+    use std::str::FromStr;
+
+    fn vamp_instructions(tx : &Transaction) -> Vec<VampInstruction>
+    {
+        let program_id = Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap();
+
+        tx.message
+            .instructions
+            .iter()
+            .filter(|instruction| tx.message.account_keys[instruction.program_id_index as usize] == program_id)
+            .map(|instruction| VampInstruction::decode(&instruction.data).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn setup_commands_are_submitted_in_one_transaction()
+    {
+        let rpc = MockRpc::new();
+        let vote_account = Pubkey::new_unique().to_string();
+        let withdraw_authority = KeypairFile::new();
+        let administrator = KeypairFile::new();
+        let operational_authority = Pubkey::new_unique();
+        let rewards_authority = Pubkey::new_unique();
+
+        let batch = vec![
+            (None, vote_account.clone(), Command::Enter {
+                withdraw_authority : withdraw_authority.path.clone(),
+                administrator : administrator.path.clone(),
+                max_commission : Some(10),
+                max_commission_increase_per_epoch : Some(2)
+            }),
+            (None, vote_account.clone(), Command::SetOperationalAuthority {
+                administrator : administrator.path.clone(),
+                authority : operational_authority.to_string()
+            }),
+            (None, vote_account, Command::SetRewardsAuthority {
+                administrator : administrator.path.clone(),
+                authority : rewards_authority.to_string()
+            }),
+        ];
+
+        run(&rpc, Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap(), &withdraw_authority.keypair, batch);
+
+        let sent = rpc.sent.borrow();
+
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].verify().is_ok());
+
+        // The withdraw authority pays and signs enter; the administrator signs twice but is a signer once
+        assert_eq!(sent[0].signatures.len(), 2);
+        assert_eq!(sent[0].message.account_keys[..2], [withdraw_authority.pubkey(), administrator.pubkey()]);
+
+        assert_eq!(vamp_instructions(&sent[0]), vec![
+            VampInstruction::Enter {
+                administrator : administrator.pubkey().to_bytes(),
+                use_commission_caps : true,
+                max_commission : 10,
+                max_commission_increase_per_epoch : 2
+            },
+            VampInstruction::SetOperationalAuthority { new_authority : operational_authority.to_bytes() },
+            VampInstruction::SetRewardsAuthority { new_authority : rewards_authority.to_bytes() }
+        ]);
+    }
+
+    #[test]
+    fn commands_which_do_not_fit_are_split_into_ordered_transactions()
+    {
+        let rpc = MockRpc::new();
+        let fee_payer = Keypair::new();
+
+        // Each command has a different vote account and signer, so few fit in a transaction
+        let rewards_authorities = (0..20).map(|_| KeypairFile::new()).collect::<Vec<KeypairFile>>();
+        let batch = rewards_authorities
+            .iter()
+            .zip(0..)
+            .map(|(rewards_authority, commission)| {
+                (None, Pubkey::new_unique().to_string(), Command::SetCommission {
+                    rewards_authority : rewards_authority.path.clone(),
+                    commission
+                })
+            })
+            .collect::<Vec<BatchStep>>();
+
+        run(&rpc, Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap(), &fee_payer, batch);
+
+        let sent = rpc.sent.borrow();
+
+        assert!(sent.len() > 1);
+
+        let commissions = sent
+            .iter()
+            .inspect(|tx| {
+                assert!(tx.verify().is_ok());
+                assert!(bincode::serialize(tx).unwrap().len() <= PACKET_DATA_SIZE);
+            })
+            .flat_map(vamp_instructions)
+            .map(|instruction| match instruction {
+                VampInstruction::SetCommission { new_commission } => new_commission,
+                instruction => panic!("unexpected {:?}", instruction)
+            })
+            .collect::<Vec<u8>>();
+
+        assert_eq!(commissions, (0..20).collect::<Vec<u8>>());
+    }
+}
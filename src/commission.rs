@@ -0,0 +1,238 @@
+// Commission planning according to the commission caps enforced by the vamp program.  The vamp program does not allow
+// commission to exceed max_commission, and does not allow commission to be increased by more than
+// max_commission_increase_per_epoch above the commission that was in effect when the current epoch began.  Decreases
+// are always allowed.
+
+use solana_vamp::model::MAX_COMMISSION;
+use std::cmp::{max, min};
+
+// Returns the schedule of (epoch, commission) values that must be set, starting in current_epoch, to move from
+// current_commission to target as quickly as possible.  The last entry gives the earliest epoch at which target can
+// be reached; an empty schedule means that the commission is already target.  Returns None if target can never be
+// reached.
+pub fn plan(
+    current_commission : u8,
+    current_epoch : u64,
+    commission_caps : Option<(u8, u8)>,
+    target : u8
+) -> Option<Vec<(u64, u8)>>
+{
+    if target > MAX_COMMISSION {
+        return None;
+    }
+
+    if target == current_commission {
+        return Some(vec![]);
+    }
+
+    let (max_commission, max_commission_increase_per_epoch) = match commission_caps {
+        Some(commission_caps) => commission_caps,
+        None => return Some(vec![(current_epoch, target)])
+    };
+
+    if target < current_commission {
+        return Some(vec![(current_epoch, target)]);
+    }
+
+    if (target > max_commission) || (max_commission_increase_per_epoch == 0) {
+        return None;
+    }
+
+    let mut schedule = vec![];
+
+    let mut commission = current_commission;
+
+    let mut epoch = current_epoch;
+
+    while commission < target {
+        commission = min(target, commission.saturating_add(max_commission_increase_per_epoch));
+        schedule.push((epoch, commission));
+        epoch += 1;
+    }
+
+    Some(schedule)
+}
+
+// Returns the highest commission that could be in effect at epoch, given that the commission is current_commission in
+// current_epoch.  epoch must not be less than current_epoch.
+pub fn highest_at_epoch(
+    current_commission : u8,
+    current_epoch : u64,
+    commission_caps : Option<(u8, u8)>,
+    epoch : u64
+) -> u8
+{
+    match commission_caps {
+        Some((max_commission, max_commission_increase_per_epoch)) => {
+            // One increase is possible in each epoch from current_epoch through epoch, inclusive
+            let increases = (epoch - current_epoch).saturating_add(1);
+            let reachable = (current_commission as u64)
+                .saturating_add(increases.saturating_mul(max_commission_increase_per_epoch as u64));
+            // The commission can always stay where it is, even if that is above max_commission
+            max(current_commission, min(reachable, min(max_commission, MAX_COMMISSION) as u64) as u8)
+        },
+        None => MAX_COMMISSION
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use proptest::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_vamp::instruction;
+    use solana_vamp::model::{Model, VoteAccountModel};
+
+    // Returns a model of a managed vote account with the given commission and caps
+    fn managed(
+        commission : u8,
+        epoch : u64,
+        commission_caps : Option<(u8, u8)>
+    ) -> Model
+    {
+        let authority = Pubkey::new_unique();
+
+        let mut model = Model::new(Pubkey::new_unique(), Pubkey::new_unique(), epoch, VoteAccountModel {
+            node_pubkey : authority,
+            authorized_voter : authority,
+            authorized_withdrawer : authority,
+            commission,
+            lamports : 0,
+            rent_exempt_minimum : 0
+        });
+
+        let enter = instruction::enter(
+            model.program_id,
+            model.vote_account,
+            model.manager_state_account(),
+            authority,
+            authority,
+            authority,
+            commission_caps
+        );
+
+        model.process(&enter).unwrap();
+
+        model
+    }
+
+    fn set_commission(
+        model : &mut Model,
+        commission : u8
+    ) -> bool
+    {
+        let rewards_authority = model.manager.as_ref().unwrap().rewards_authority;
+
+        let instruction = instruction::set_commission(
+            model.program_id,
+            model.vote_account,
+            model.manager_state_account(),
+            rewards_authority,
+            commission
+        );
+
+        model.process(&instruction).is_ok()
+    }
+
+    #[test]
+    fn plan_steps_up_by_the_maximum_increase_per_epoch()
+    {
+        assert_eq!(plan(5, 100, Some((10, 2)), 10), Some(vec![(100, 7), (101, 9), (102, 10)]));
+
+        // Without commission caps, or when decreasing, the target is set at once
+        assert_eq!(plan(5, 100, None, 10), Some(vec![(100, 10)]));
+        assert_eq!(plan(10, 100, Some((10, 2)), 0), Some(vec![(100, 0)]));
+
+        assert_eq!(plan(5, 100, Some((10, 2)), 5), Some(vec![]));
+    }
+
+    #[test]
+    fn plan_rejects_unreachable_targets()
+    {
+        assert_eq!(plan(5, 100, Some((10, 2)), 11), None);
+        assert_eq!(plan(5, 100, Some((10, 0)), 6), None);
+        assert_eq!(plan(5, 100, None, MAX_COMMISSION + 1), None);
+    }
+
+    #[test]
+    fn highest_at_epoch_is_capped()
+    {
+        assert_eq!(highest_at_epoch(5, 100, Some((10, 2)), 100), 7);
+        assert_eq!(highest_at_epoch(5, 100, Some((10, 2)), 101), 9);
+        assert_eq!(highest_at_epoch(5, 100, Some((10, 2)), 200), 10);
+
+        // A commission already above max_commission may stay where it is
+        assert_eq!(highest_at_epoch(20, 100, Some((10, 2)), 100), 20);
+
+        assert_eq!(highest_at_epoch(5, 100, None, 100), MAX_COMMISSION);
+    }
+
+    proptest! {
+        #[test]
+        fn plan_is_accepted_by_program(
+            current in 0_u8..=100,
+            epoch in 0_u64..1000,
+            commission_caps in proptest::option::of((0_u8..=100, 0_u8..=100)),
+            target in 0_u8..=100
+        ) {
+            let mut model = managed(current, epoch, commission_caps);
+
+            match plan(current, epoch, commission_caps, target) {
+                Some(schedule) => {
+                    for (step_epoch, commission) in schedule {
+                        model.advance_to_epoch(step_epoch);
+                        prop_assert!(set_commission(&mut model, commission));
+                    }
+                    prop_assert_eq!(model.vote.commission, target);
+                },
+                None => {
+                    // If there is no plan, the program never accepts the target in the current epoch or later ones
+                    for _ in 0..=100 {
+                        prop_assert!(!set_commission(&mut model.clone(), target));
+                        let highest = model.max_allowed_commission().unwrap();
+                        set_commission(&mut model, highest);
+                        model.advance_to_epoch(model.epoch + 1);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn plan_is_as_fast_as_possible(
+            current in 0_u8..=100,
+            epoch in 0_u64..1000,
+            commission_caps in proptest::option::of((0_u8..=100, 0_u8..=100)),
+            target in 0_u8..=100
+        ) {
+            if let Some(schedule) = plan(current, epoch, commission_caps, target) {
+                if let Some((last_epoch, _)) = schedule.last() {
+                    if *last_epoch > epoch {
+                        prop_assert!(highest_at_epoch(current, epoch, commission_caps, last_epoch - 1) < target);
+                    }
+                    prop_assert!(highest_at_epoch(current, epoch, commission_caps, *last_epoch) >= target);
+                }
+            }
+        }
+
+        #[test]
+        fn highest_at_epoch_is_reachable(
+            current in 0_u8..=100,
+            epoch in 0_u64..1000,
+            commission_caps in proptest::option::of((0_u8..=100, 0_u8..=100)),
+            epochs in 0_u64..10
+        ) {
+            let mut model = managed(current, epoch, commission_caps);
+
+            for _ in 0..epochs {
+                let highest = model.max_allowed_commission().unwrap();
+                set_commission(&mut model, highest);
+                model.advance_to_epoch(model.epoch + 1);
+            }
+
+            let highest = highest_at_epoch(current, epoch, commission_caps, epoch + epochs);
+
+            prop_assert_eq!(model.max_allowed_commission().unwrap(), highest);
+        }
+    }
+}
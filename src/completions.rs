@@ -0,0 +1,431 @@
+// Shell completion scripts, generated from the command specs
+
+use crate::environment::PROFILES_DIRECTORY;
+use crate::spec::{ArgSpec, CommandSpec, Occurs, Position, ValueType, COMMANDS, GLOBAL_ARGS};
+
+pub fn completions(shell : &str) -> String
+{
+    match shell {
+        "bash" => bash(),
+        "zsh" => zsh(),
+        "fish" => fish(),
+        _ => unreachable!("unsupported shell {}", shell)
+    }
+}
+
+// Whether the value of an argument is completed as a file path; pubkeys may also be given as keypair files
+fn completes_files(arg : &ArgSpec) -> bool
+{
+    matches!(arg.value_type, ValueType::Keypair | ValueType::Address | ValueType::File)
+}
+
+fn flag_args(spec : &CommandSpec) -> impl Iterator<Item = &'static ArgSpec>
+{
+    spec.accepted_args().filter(|arg| arg.position != Position::Positional)
+}
+
+fn positional_args(spec : &CommandSpec) -> impl Iterator<Item = &'static ArgSpec>
+{
+    spec.args.iter().filter(|arg| arg.position != Position::Flag)
+}
+
+// The flags of every argument of every command, each with its spec as used by the first command which accepts it;
+// flags are completed the same way by every command
+fn all_flag_args() -> Vec<&'static ArgSpec>
+{
+    let mut args : Vec<&'static ArgSpec> = vec![];
+
+    for arg in GLOBAL_ARGS.iter().chain(COMMANDS.iter().flat_map(flag_args)) {
+        if !args.iter().any(|other| other.name == arg.name) {
+            args.push(arg);
+        }
+    }
+
+    args
+}
+
+fn flag_names(arg : &ArgSpec) -> Vec<String>
+{
+    arg.short.map(|short| format!("-{}", short)).into_iter().chain(std::iter::once(arg.flag())).collect()
+}
+
+fn command_names() -> String
+{
+    COMMANDS.iter().map(|spec| spec.name).chain(std::iter::once("help")).collect::<Vec<&str>>().join(" ")
+}
+
+fn bash() -> String
+{
+    let args = all_flag_args();
+
+    let value_args = args.iter().copied().filter(|arg| arg.value_type != ValueType::Switch);
+
+    let file_flags = value_args.clone().filter(|arg| completes_files(arg)).flat_map(flag_names);
+
+    let profile_flags = value_args.clone().filter(|arg| arg.value_type == ValueType::Profile).flat_map(flag_names);
+
+    let other_flags = value_args
+        .clone()
+        .filter(|arg| !completes_files(arg) && arg.suggestions.is_empty() && (arg.value_type != ValueType::Profile));
+
+    let mut script = format!(
+        r#"# bash completion for solana-vamp, generated by 'solana-vamp completions bash'
+
+_solana_vamp()
+{{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}" command="" word
+
+    case "$prev" in
+        {})
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return
+            ;;
+"#,
+        file_flags.collect::<Vec<String>>().join("|")
+    );
+
+    for arg in value_args.filter(|arg| !arg.suggestions.is_empty()) {
+        script.push_str(&format!(
+            r#"        {})
+            COMPREPLY=($(compgen -W "{}" -- "$cur"))
+            return
+            ;;
+"#,
+            flag_names(arg).join("|"),
+            arg.suggestions.join(" ")
+        ));
+    }
+
+    script.push_str(&format!(
+        r#"        {})
+            COMPREPLY=($(compgen -W "$(ls "$HOME/{}" 2>/dev/null)" -- "$cur"))
+            return
+            ;;
+"#,
+        profile_flags.collect::<Vec<String>>().join("|"),
+        PROFILES_DIRECTORY
+    ));
+
+    script.push_str(&format!(
+        r#"        {})
+            return
+            ;;
+    esac
+
+    for word in "${{COMP_WORDS[@]:1:COMP_CWORD-1}}"; do
+        case "$word" in
+            {})
+                command="$word"
+                break
+                ;;
+        esac
+    done
+
+    case "$command" in
+        "" | help)
+            COMPREPLY=($(compgen -W "{} {}" -- "$cur"))
+            ;;
+"#,
+        other_flags.flat_map(flag_names).collect::<Vec<String>>().join("|"),
+        command_names().replace(' ', "|"),
+        command_names(),
+        GLOBAL_ARGS.iter().flat_map(flag_names).collect::<Vec<String>>().join(" ")
+    ));
+
+    for spec in COMMANDS {
+        let flags = flag_args(spec).flat_map(flag_names).collect::<Vec<String>>().join(" ");
+        let positional = match positional_args(spec).find(|arg| !arg.suggestions.is_empty()) {
+            Some(arg) => format!("compgen -W \"{}\" -- \"$cur\"", arg.suggestions.join(" ")),
+            None if positional_args(spec).any(completes_files) => "compgen -f -- \"$cur\"".to_string(),
+            None => "compgen -W \"\" -- \"$cur\"".to_string()
+        };
+        script.push_str(&format!(
+            r#"        {})
+            if [[ "$cur" == -* ]]; then
+                COMPREPLY=($(compgen -W "{} --help" -- "$cur"))
+            else
+                COMPREPLY=($({}))
+            fi
+            ;;
+"#,
+            spec.name, flags, positional
+        ));
+    }
+
+    script.push_str("    esac\n}\n\ncomplete -o filenames -F _solana_vamp solana-vamp\n");
+
+    script
+}
+
+// Quotes text for use within single quotes in zsh and fish
+fn single_quoted(s : &str) -> String
+{
+    s.replace('\'', "'\\''")
+}
+
+fn zsh() -> String
+{
+    let mut script = String::from(
+        "#compdef solana-vamp\n# zsh completion for solana-vamp, generated by 'solana-vamp completions zsh'\n\n\
+         _solana_vamp()\n{\n    local -a commands\n    commands=(\n"
+    );
+
+    for spec in COMMANDS {
+        script.push_str(&format!("        '{}:{}'\n", spec.name, single_quoted(spec.summary)));
+    }
+
+    script.push_str(&format!(
+        r#"        'help:To print this help message'
+    )
+
+    local command word
+    for word in ${{words[2,CURRENT-1]}}; do
+        case $word in
+            ({})
+                command=$word
+                break
+                ;;
+        esac
+    done
+
+    case $command in
+        ('' | help)
+            _describe -t commands 'solana-vamp command' commands
+            ;;
+"#,
+        command_names().replace(' ', "|")
+    ));
+
+    for spec in COMMANDS {
+        script.push_str(&format!("        ({})\n            _arguments \\\n", spec.name));
+        for arg in flag_args(spec) {
+            script.push_str(&format!("                {} \\\n", zsh_flag(arg)));
+        }
+        let positional = match positional_args(spec).find(|arg| !arg.suggestions.is_empty()) {
+            Some(arg) => format!("'*:{}:({})'", arg.description(), arg.suggestions.join(" ")),
+            None if positional_args(spec).any(completes_files) => "'*:argument:_files'".to_string(),
+            None => "'*: :'".to_string()
+        };
+        script.push_str(&format!("                {}\n            ;;\n", positional));
+    }
+
+    script.push_str("    esac\n}\n\n_solana_vamp \"$@\"\n");
+
+    script
+}
+
+fn zsh_flag(arg : &ArgSpec) -> String
+{
+    let names = match arg.short {
+        Some(short) => format!("'(-{} {})'{{-{},{}}}'", short, arg.flag(), short, arg.flag()),
+        None if arg.occurs == Occurs::Repeated => format!("'*{}", arg.flag()),
+        None => format!("'{}", arg.flag())
+    };
+
+    let action = if arg.value_type == ValueType::Switch {
+        String::new()
+    }
+    else if !arg.suggestions.is_empty() {
+        format!(":{}:({})", arg.value, arg.suggestions.join(" "))
+    }
+    else if completes_files(arg) {
+        format!(":{}:_files", arg.value)
+    }
+    else if arg.value_type == ValueType::Profile {
+        format!(":{}:_files -W $HOME/{}", arg.value, PROFILES_DIRECTORY)
+    }
+    else {
+        format!(":{}: ", arg.value)
+    };
+
+    format!("{}[{}]{}'", names, arg.description(), single_quoted(&action))
+}
+
+fn fish() -> String
+{
+    let mut script = format!(
+        "# fish completion for solana-vamp, generated by 'solana-vamp completions fish'\n\nset -l commands {}\n\n\
+         complete -c solana-vamp -f\n",
+        command_names()
+    );
+
+    let no_command = "not __fish_seen_subcommand_from $commands";
+
+    for (name, summary) in COMMANDS
+        .iter()
+        .map(|spec| (spec.name, spec.summary))
+        .chain(std::iter::once(("help", "To print this help message")))
+    {
+        script.push_str(&format!(
+            "complete -c solana-vamp -n \"{}\" -a {} -d '{}'\n",
+            no_command,
+            name,
+            single_quoted(summary)
+        ));
+    }
+
+    script.push_str("complete -c solana-vamp -n \"__fish_seen_subcommand_from help\" -a \"$commands\"\n");
+
+    for spec in COMMANDS {
+        let condition = format!("-n \"__fish_seen_subcommand_from {}\"", spec.name);
+        script.push('\n');
+        for arg in flag_args(spec) {
+            let short = arg.short.map(|short| format!(" -s {}", short)).unwrap_or_default();
+            let value = if arg.value_type == ValueType::Switch {
+                String::new()
+            }
+            else if !arg.suggestions.is_empty() {
+                format!(" -x -a '{}'", arg.suggestions.join(" "))
+            }
+            else if completes_files(arg) {
+                " -r -F".to_string()
+            }
+            else if arg.value_type == ValueType::Profile {
+                format!(" -x -a '(ls ~/{} 2>/dev/null)'", PROFILES_DIRECTORY)
+            }
+            else {
+                " -x".to_string()
+            };
+            script.push_str(&format!(
+                "complete -c solana-vamp {}{} -l {}{} -d '{}'\n",
+                condition,
+                short,
+                arg.name,
+                value,
+                arg.description()
+            ));
+        }
+        match positional_args(spec).find(|arg| !arg.suggestions.is_empty()) {
+            Some(arg) => {
+                script.push_str(&format!("complete -c solana-vamp {} -a '{}'\n", condition, arg.suggestions.join(" ")))
+            },
+            None if positional_args(spec).any(completes_files) => {
+                script.push_str(&format!("complete -c solana-vamp {} -F\n", condition))
+            },
+            None => ()
+        }
+    }
+
+    script
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::spec::SHELLS;
+
+    // Checks the syntax of a script with the shell, if the shell is installed
+    fn check_syntax(
+        shell : &str,
+        script : &str
+    )
+    {
+        let path = std::env::temp_dir().join(format!("solana-vamp-completions-{}.{}", std::process::id(), shell));
+
+        std::fs::write(&path, script).unwrap();
+
+        if let Ok(output) = std::process::Command::new(shell).arg("-n").arg(&path).output() {
+            assert!(output.status.success(), "{}: {}", shell, String::from_utf8_lossy(&output.stderr));
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn every_command_and_flag_is_completed()
+    {
+        for shell in SHELLS {
+            let script = completions(shell);
+
+            check_syntax(shell, &script);
+
+            for spec in COMMANDS {
+                assert!(script.contains(spec.name), "{} completion lacks {}", shell, spec.name);
+                for arg in flag_args(spec) {
+                    let flag = if *shell == "fish" { format!("-l {}", arg.name) } else { arg.flag() };
+                    assert!(script.contains(&flag), "{} completion lacks {} for {}", shell, flag, spec.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keypair_arguments_complete_file_paths()
+    {
+        let script = bash();
+
+        let file_flags = script.lines().skip_while(|line| !line.contains("case \"$prev\"")).nth(1).unwrap();
+
+        for flag in ["--withdraw-authority", "--rewards-authority", "--fee-payer", "-f", "--split-file"] {
+            assert!(file_flags.trim_end_matches(')').split('|').any(|name| name.trim() == flag), "{}", flag);
+        }
+
+        assert!(!file_flags.contains("--leave-epoch"));
+
+        assert!(zsh().contains("'--withdraw-authority[withdraw authority]:WITHDRAW_AUTHORITY_FILE:_files'"));
+
+        assert!(fish().contains(
+            "complete -c solana-vamp -n \"__fish_seen_subcommand_from enter\" -l withdraw-authority -r -F -d \
+             'withdraw authority'"
+        ));
+    }
+
+    // Runs the bash completion function for a command line, returning the completions offered for its last word
+    fn complete_with_bash(words : &[&str]) -> Option<Vec<String>>
+    {
+        complete_with_bash_at_home(&std::env::temp_dir(), words)
+    }
+
+    fn complete_with_bash_at_home(
+        home : &std::path::Path,
+        words : &[&str]
+    ) -> Option<Vec<String>>
+    {
+        let script = format!(
+            "{}\nCOMP_WORDS=({})\nCOMP_CWORD={}\n_solana_vamp\nprintf '%s\\n' \"${{COMPREPLY[@]}}\"\n",
+            bash(),
+            words.iter().map(|word| format!("'{}'", word)).collect::<Vec<String>>().join(" "),
+            words.len() - 1
+        );
+
+        let output = std::process::Command::new("bash").arg("-c").arg(script).env("HOME", home).output().ok()?;
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        Some(String::from_utf8(output.stdout).unwrap().lines().map(String::from).collect())
+    }
+
+    #[test]
+    fn bash_completes_commands_flags_and_values()
+    {
+        // bash is not available everywhere the tests run
+        let Some(commands) = complete_with_bash(&["solana-vamp", "set-"])
+        else {
+            return;
+        };
+
+        assert!(commands.contains(&"set-leave-epoch".to_string()));
+        assert!(!commands.contains(&"enter".to_string()));
+
+        let flags = complete_with_bash(&["solana-vamp", "enter", "--max"]).unwrap();
+        assert_eq!(flags, vec!["--max-commission", "--max-commission-increase-per-epoch"]);
+
+        let flags = complete_with_bash(&["solana-vamp", "show", "--"]).unwrap();
+        assert!(flags.contains(&"--json".to_string()));
+        assert!(!flags.contains(&"--amount".to_string()));
+
+        let urls = complete_with_bash(&["solana-vamp", "show", "--url", "d"]).unwrap();
+        assert_eq!(urls, vec!["devnet"]);
+
+        let profiles = std::env::temp_dir().join(format!("solana-vamp-completions-home-{}", std::process::id()));
+        std::fs::create_dir_all(profiles.join(PROFILES_DIRECTORY)).unwrap();
+        std::fs::write(profiles.join(PROFILES_DIRECTORY).join("validator"), "url = devnet\n").unwrap();
+        let completed = complete_with_bash_at_home(&profiles, &["solana-vamp", "show", "--profile", "v"]).unwrap();
+        std::fs::remove_dir_all(profiles).unwrap();
+        assert_eq!(completed, vec!["validator"]);
+
+        let shells = complete_with_bash(&["solana-vamp", "completions", ""]).unwrap();
+        assert_eq!(shells, vec!["bash", "zsh", "fish"]);
+    }
+}
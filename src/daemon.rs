@@ -0,0 +1,296 @@
+// Long running mode that withdraws rewards from a vote account as they accumulate
+
+use crate::amount::format_sol;
+use crate::rpc::Rpc;
+use crate::{format_utc, simulate_transaction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use solana_vamp::instruction;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Longest time to wait between retries after RPC failures
+const MAX_BACKOFF : Duration = Duration::from_secs(600);
+
+pub struct DaemonConfig
+{
+    pub recipient : Pubkey,
+
+    // Lamports; no withdraw is made until at least this many lamports can be withdrawn
+    pub threshold : u64,
+
+    // Lamports to leave in the vote account above its rent exempt minimum
+    pub buffer : u64,
+
+    // If true, the vote account is only checked once per epoch
+    pub every_epoch : bool,
+
+    pub poll_interval : Duration
+}
+
+pub fn run(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    fee_payer : &Keypair,
+    rewards_authority : &Keypair,
+    config : &DaemonConfig
+) -> !
+{
+    log(&format!(
+        "Starting daemon for vote account {}: recipient {}, threshold {} SOL, buffer {} SOL, {}",
+        vote_account,
+        config.recipient,
+        format_sol(config.threshold),
+        format_sol(config.buffer),
+        if config.every_epoch { "checking once per epoch" } else { "checking at every poll" }
+    ));
+
+    let mut last_epoch = None;
+
+    let mut backoff = config.poll_interval;
+
+    loop {
+        match check(
+            rpc_client,
+            program_id,
+            vote_account,
+            vote_account_manager_state_account,
+            fee_payer,
+            rewards_authority,
+            config,
+            &mut last_epoch
+        ) {
+            Ok(()) => {
+                backoff = config.poll_interval;
+                std::thread::sleep(config.poll_interval);
+            },
+            Err(e) => {
+                log(&format!("Error: {}; retrying in {} seconds", e, backoff.as_secs()));
+                std::thread::sleep(backoff);
+                backoff = std::cmp::min(backoff * 2, std::cmp::max(MAX_BACKOFF, config.poll_interval));
+            }
+        }
+    }
+}
+
+// Checks the vote account and withdraws from it if called for.  last_epoch is the epoch of the last successful
+// check, and is updated on success.
+#[allow(clippy::too_many_arguments)]
+fn check(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    fee_payer : &Keypair,
+    rewards_authority : &Keypair,
+    config : &DaemonConfig,
+    last_epoch : &mut Option<u64>
+) -> Result<(), String>
+{
+    let epoch = rpc_client.get_epoch_info().map_err(|e| format!("Failed to fetch epoch info: {}", e))?.epoch;
+
+    let new_epoch = *last_epoch != Some(epoch);
+
+    if config.every_epoch && !new_epoch {
+        return Ok(());
+    }
+
+    let account = rpc_client.get_account(&vote_account).map_err(|e| format!("Failed to fetch vote account: {}", e))?;
+
+    let rent_exempt_minimum = rpc_client
+        .get_minimum_balance_for_rent_exemption(account.data.len())
+        .map_err(|e| format!("Failed to fetch rent exempt minimum: {}", e))?;
+
+    let withdrawable = account.lamports.saturating_sub(rent_exempt_minimum).saturating_sub(config.buffer);
+
+    if new_epoch {
+        log(&format!(
+            "Epoch {}: vote account balance {} SOL, withdrawable {} SOL",
+            epoch,
+            format_sol(account.lamports),
+            format_sol(withdrawable)
+        ));
+    }
+
+    if (withdrawable > 0) && (withdrawable >= config.threshold) {
+        let instruction = instruction::withdraw(
+            program_id,
+            vote_account,
+            vote_account_manager_state_account,
+            rewards_authority.pubkey(),
+            config.recipient,
+            withdrawable
+        );
+
+        let blockhash =
+            rpc_client.get_latest_blockhash().map_err(|e| format!("Failed to fetch latest blockhash: {}", e))?;
+
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&fee_payer.pubkey()),
+            &[fee_payer, rewards_authority],
+            blockhash
+        );
+
+        log(&format!("Withdrawing {} SOL to {}", format_sol(withdrawable), config.recipient));
+
+        // As for every other command, a withdraw that would fail is not submitted
+        simulate_transaction(rpc_client, &tx)?;
+
+        let signature =
+            rpc_client.send_and_confirm_transaction(&tx).map_err(|e| format!("Failed to submit withdraw: {}", e))?;
+
+        log(&format!("Withdraw confirmed with signature: {}", signature));
+    }
+
+    *last_epoch = Some(epoch);
+
+    Ok(())
+}
+
+fn log(msg : &str)
+{
+    println!(
+        "{} {}",
+        format_utc(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64),
+        msg
+    );
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::amount::LAMPORTS_PER_SOL;
+    use crate::rpc::mock::MockRpc;
+    use solana_sdk::transaction::TransactionError;
+    use solana_sdk::vote::state::VoteState;
+    use solana_vamp::instruction::get_vote_account_manager_state_account;
+
+    fn config(every_epoch : bool) -> DaemonConfig
+    {
+        DaemonConfig {
+            recipient : Pubkey::new_unique(),
+            threshold : LAMPORTS_PER_SOL,
+            buffer : LAMPORTS_PER_SOL / 2,
+            every_epoch,
+            poll_interval : Duration::from_secs(1)
+        }
+    }
+
+    fn check_with(
+        rpc : &MockRpc,
+        vote_account : Pubkey,
+        config : &DaemonConfig,
+        last_epoch : &mut Option<u64>
+    ) -> Result<(), String>
+    {
+        let program_id = Pubkey::new_unique();
+
+        let keypair = Keypair::new();
+
+        check(
+            rpc,
+            program_id,
+            vote_account,
+            get_vote_account_manager_state_account(&program_id, &vote_account),
+            &keypair,
+            &keypair,
+            config,
+            last_epoch
+        )
+    }
+
+    #[test]
+    fn withdraws_above_threshold_leaving_buffer()
+    {
+        let rpc = MockRpc::new();
+
+        let vote_account = Pubkey::new_unique();
+
+        let rent_exempt_minimum = rpc.get_minimum_balance_for_rent_exemption(VoteState::size_of()).unwrap();
+
+        rpc.set_epoch(10);
+
+        // Below the threshold once the buffer is left
+        rpc.set_vote_account(vote_account, Pubkey::new_unique(), 5, rent_exempt_minimum + LAMPORTS_PER_SOL);
+
+        let config = config(false);
+
+        let mut last_epoch = None;
+
+        check_with(&rpc, vote_account, &config, &mut last_epoch).unwrap();
+
+        assert!(rpc.sent.borrow().is_empty());
+        assert_eq!(last_epoch, Some(10));
+
+        rpc.set_vote_account(vote_account, Pubkey::new_unique(), 5, rent_exempt_minimum + 3 * LAMPORTS_PER_SOL);
+
+        check_with(&rpc, vote_account, &config, &mut last_epoch).unwrap();
+
+        let sent = rpc.sent.borrow();
+
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].message.account_keys.contains(&config.recipient));
+        assert_eq!(
+            solana_vamp::transaction_data::VampInstruction::decode(&sent[0].message.instructions[0].data),
+            Ok(solana_vamp::transaction_data::VampInstruction::Withdraw { lamports : 5 * LAMPORTS_PER_SOL / 2 })
+        );
+    }
+
+    #[test]
+    fn failed_simulation_is_not_submitted()
+    {
+        let mut rpc = MockRpc::new();
+
+        rpc.simulation_err = Some(TransactionError::AccountNotFound);
+        rpc.simulation_logs = vec!["Program log: failed".to_string()];
+
+        let vote_account = Pubkey::new_unique();
+
+        let rent_exempt_minimum = rpc.get_minimum_balance_for_rent_exemption(VoteState::size_of()).unwrap();
+
+        rpc.set_epoch(10);
+        rpc.set_vote_account(vote_account, Pubkey::new_unique(), 5, rent_exempt_minimum + 3 * LAMPORTS_PER_SOL);
+
+        let mut last_epoch = None;
+
+        let result = check_with(&rpc, vote_account, &config(false), &mut last_epoch);
+
+        assert_eq!(
+            result,
+            Err(format!(
+                "Transaction simulation failed: {}\nProgram logs:\n  Program log: failed\n",
+                TransactionError::AccountNotFound
+            ))
+        );
+        assert!(rpc.sent.borrow().is_empty());
+        // The withdraw is tried again at the next check
+        assert_eq!(last_epoch, None);
+    }
+
+    #[test]
+    fn every_epoch_checks_once_per_epoch()
+    {
+        let rpc = MockRpc::new();
+
+        let vote_account = Pubkey::new_unique();
+
+        let config = config(true);
+
+        let mut last_epoch = Some(10);
+
+        // The vote account does not exist, so any check beyond the epoch fetch fails
+        rpc.set_epoch(10);
+
+        assert!(check_with(&rpc, vote_account, &config, &mut last_epoch).is_ok());
+
+        rpc.set_epoch(11);
+
+        assert!(check_with(&rpc, vote_account, &config, &mut last_epoch).is_err());
+        assert_eq!(last_epoch, Some(10));
+    }
+}
@@ -0,0 +1,432 @@
+// Pretty printing of the vamp program instructions within a transaction, so that signers can review what they are
+// approving
+
+use crate::describe::{account_roles, describe_instruction};
+use crate::history::fetch_transaction;
+use crate::rpc::Rpc;
+use crate::{error_exit, format_utc};
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_vamp::instruction::get_vote_account_manager_state_account;
+use std::str::FromStr;
+
+// Size of the metadata which precedes the addresses stored in an address lookup table account
+const LOOKUP_TABLE_META_SIZE : usize = 56;
+
+pub fn decode(
+    rpc_client : &dyn Rpc,
+    program_id : &Pubkey,
+    input : &str
+)
+{
+    let input = input.trim();
+
+    println!();
+
+    if let Ok(signature) = Signature::from_str(input) {
+        let fetched = fetch_transaction(rpc_client, &signature);
+        println!("Signature: {}", signature);
+        println!("Slot: {}", fetched.slot);
+        if let Some(block_time) = fetched.block_time {
+            println!("Time: {}", format_utc(block_time));
+        }
+        match &fetched.error {
+            Some(error) => println!("Status: FAILED ({})", error),
+            None => println!("Status: ok")
+        }
+        print_signatures(&fetched.transaction);
+        print!("{}", format_message(program_id, &fetched.transaction.message, &fetched.account_keys));
+    }
+    else {
+        match parse(input) {
+            Some(Ok(transaction)) => {
+                let account_keys = resolve_account_keys(rpc_client, &transaction.message);
+                print_signatures(&transaction);
+                print!("{}", format_message(program_id, &transaction.message, &account_keys));
+            },
+            Some(Err(message)) => {
+                let account_keys = resolve_account_keys(rpc_client, &message);
+                print!("{}", format_message(program_id, &message, &account_keys));
+            },
+            None => error_exit(
+                "Input is not a transaction signature, or a base64 or base58 encoded serialized transaction or message"
+            )
+        }
+    }
+
+    println!();
+}
+
+// Parses base64 or base58 encoded input as a serialized transaction, or failing that a serialized message
+fn parse(input : &str) -> Option<Result<VersionedTransaction, VersionedMessage>>
+{
+    [base64::decode(input).ok(), bs58::decode(input).into_vec().ok()]
+        .into_iter()
+        .flatten()
+        .find_map(|bytes| parse_transaction(&bytes).map(Ok).or_else(|| parse_message(&bytes).map(Err)))
+}
+
+// Only accepts bytes which are exactly one well formed transaction
+fn parse_transaction(bytes : &[u8]) -> Option<VersionedTransaction>
+{
+    let transaction = bincode::deserialize::<VersionedTransaction>(bytes).ok()?;
+
+    (transaction.sanitize(false).is_ok() && (bincode::serialized_size(&transaction).ok()? == bytes.len() as u64))
+        .then_some(transaction)
+}
+
+// Only accepts bytes which are exactly one well formed message
+fn parse_message(bytes : &[u8]) -> Option<VersionedMessage>
+{
+    let message = bincode::deserialize::<VersionedMessage>(bytes).ok()?;
+
+    (message.sanitize(false).is_ok() && (message.serialize().len() == bytes.len())).then_some(message)
+}
+
+// Returns the static account keys of the message followed by any keys loaded from address lookup tables, which are
+// fetched from the ledger
+fn resolve_account_keys(
+    rpc_client : &dyn Rpc,
+    message : &VersionedMessage
+) -> Vec<Pubkey>
+{
+    let mut account_keys = message.static_account_keys().to_vec();
+
+    let lookups = message.address_table_lookups().unwrap_or_default();
+
+    let tables = lookups
+        .iter()
+        .map(|lookup| {
+            let account = rpc_client.get_account(&lookup.account_key).unwrap_or_else(|e| {
+                error_exit(&format!("Failed to fetch address lookup table {}: {}", lookup.account_key, e))
+            });
+            account
+                .data
+                .get(LOOKUP_TABLE_META_SIZE..)
+                .unwrap_or_default()
+                .chunks_exact(32)
+                .map(|chunk| Pubkey::new_from_array(chunk.try_into().unwrap()))
+                .collect::<Vec<Pubkey>>()
+        })
+        .collect::<Vec<Vec<Pubkey>>>();
+
+    let lookup = |table : &Vec<Pubkey>, table_key : &Pubkey, index : &u8| {
+        *table.get(*index as usize).unwrap_or_else(|| {
+            error_exit(&format!("Address lookup table {} has no address at index {}", table_key, index))
+        })
+    };
+
+    // All writable loaded addresses come before all readonly loaded addresses
+    for (table, lookup_entry) in tables.iter().zip(lookups) {
+        account_keys
+            .extend(lookup_entry.writable_indexes.iter().map(|index| lookup(table, &lookup_entry.account_key, index)));
+    }
+
+    for (table, lookup_entry) in tables.iter().zip(lookups) {
+        account_keys
+            .extend(lookup_entry.readonly_indexes.iter().map(|index| lookup(table, &lookup_entry.account_key, index)));
+    }
+
+    account_keys
+}
+
+fn print_signatures(transaction : &VersionedTransaction)
+{
+    let results = transaction.verify_with_results();
+
+    println!("Signatures:");
+
+    for ((signature, signer), valid) in
+        transaction.signatures.iter().zip(transaction.message.static_account_keys()).zip(results)
+    {
+        if *signature == Signature::default() {
+            println!("  {}: missing", signer);
+        }
+        else if valid {
+            println!("  {}: {}", signer, signature);
+        }
+        else {
+            println!("  {}: {} (INVALID)", signer, signature);
+        }
+    }
+}
+
+// Describes the instructions of a message.  Accounts which are loaded from address lookup tables that could not be
+// read are reported by their index rather than failing.
+fn format_message(
+    program_id : &Pubkey,
+    message : &VersionedMessage,
+    account_keys : &[Pubkey]
+) -> String
+{
+    let mut output = format!("Fee Payer: {}\n", message.static_account_keys()[0]);
+
+    output.push_str(&format!("Recent Blockhash: {}\n", message.recent_blockhash()));
+
+    let account_name = |index : &u8| {
+        account_keys
+            .get(*index as usize)
+            .map(|pubkey| pubkey.to_string())
+            .unwrap_or(format!("unresolved account index {}", index))
+    };
+
+    let instructions = message.instructions();
+
+    for (i, instruction) in instructions.iter().enumerate() {
+        output.push('\n');
+
+        if account_keys.get(instruction.program_id_index as usize) != Some(program_id) {
+            output.push_str(&format!(
+                "Instruction {} of {}: program {} (not a Vote Account Manager instruction)\n",
+                i + 1,
+                instructions.len(),
+                account_name(&instruction.program_id_index)
+            ));
+            continue;
+        }
+
+        let resolved = instruction.accounts.iter().map(|index| account_keys.get(*index as usize).cloned());
+
+        // Only the accounts up to the first unresolved one, so that each keeps its position
+        let accounts = resolved.clone().map_while(|pubkey| pubkey).collect::<Vec<Pubkey>>();
+
+        let unresolved = resolved.clone().any(|pubkey| pubkey.is_none());
+
+        let description = describe_instruction(&instruction.data, &accounts);
+
+        match &description {
+            Some(description) => {
+                output.push_str(&format!(
+                    "Instruction {} of {}: Vote Account Manager {}\n",
+                    i + 1,
+                    instructions.len(),
+                    description.action
+                ));
+                for (name, value) in &description.parameters {
+                    output.push_str(&format!("  {}: {}\n", name, value));
+                }
+            },
+            None => {
+                output.push_str(&format!(
+                    "Instruction {} of {}: Vote Account Manager (unrecognized)\n",
+                    i + 1,
+                    instructions.len()
+                ));
+                output.push_str(&format!(
+                    "  WARNING: instruction data is not a valid Vote Account Manager instruction: {}\n",
+                    instruction.data.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+                ));
+            }
+        }
+
+        let roles = if description.is_some() { account_roles(instruction.data[0]) } else { &[] };
+
+        output.push_str("  Accounts:\n");
+
+        for (j, index) in instruction.accounts.iter().enumerate() {
+            let mut flags = vec![];
+            if message.is_signer(*index as usize) {
+                flags.push("signer");
+            }
+            if message.is_maybe_writable(*index as usize) {
+                flags.push("writable");
+            }
+            output.push_str(&format!(
+                "    {}: {}{}\n",
+                roles.get(j).unwrap_or(&"Extra Account"),
+                account_name(index),
+                if flags.is_empty() { "".to_string() } else { format!(" ({})", flags.join(", ")) }
+            ));
+        }
+
+        if unresolved {
+            output.push_str("  WARNING: some accounts are loaded from address lookup tables which could not be read\n");
+        }
+        else if description.is_some() {
+            if accounts.len() < roles.len() {
+                output.push_str(&format!(
+                    "  WARNING: instruction has {} accounts; {} are required\n",
+                    accounts.len(),
+                    roles.len()
+                ));
+            }
+            else if accounts[0] != get_vote_account_manager_state_account(program_id, &accounts[1]) {
+                output.push_str("  WARNING: the manager state account does not belong to the vote account\n");
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::mock::MockRpc;
+    use solana_sdk::account::Account;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::CompiledInstruction;
+    use solana_sdk::message::v0::{self, MessageAddressTableLookup};
+    use solana_sdk::message::{Message, MessageHeader};
+    use solana_sdk::signer::keypair::Keypair;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use solana_vamp::instruction;
+    use solana_vamp::transaction_data::VampInstruction;
+
+    fn set_commission_transaction(
+        program_id : &Pubkey,
+        rewards_authority : &Keypair,
+        vote_account : &Pubkey
+    ) -> VersionedTransaction
+    {
+        let manager = get_vote_account_manager_state_account(program_id, vote_account);
+
+        Transaction::new_signed_with_payer(
+            &[instruction::set_commission(*program_id, *vote_account, manager, rewards_authority.pubkey(), 7)],
+            Some(&rewards_authority.pubkey()),
+            &[rewards_authority],
+            Hash::new_unique()
+        )
+        .into()
+    }
+
+    // A v0 message whose only instruction is a vamp instruction using two accounts loaded from a lookup table
+    fn lookup_table_message(
+        payer : Pubkey,
+        program_id : Pubkey,
+        table : Pubkey
+    ) -> VersionedMessage
+    {
+        VersionedMessage::V0(v0::Message {
+            header : MessageHeader {
+                num_required_signatures : 1,
+                num_readonly_signed_accounts : 0,
+                num_readonly_unsigned_accounts : 1
+            },
+            account_keys : vec![payer, program_id],
+            recent_blockhash : Hash::new_unique(),
+            instructions : vec![CompiledInstruction::new_from_raw_parts(
+                1,
+                VampInstruction::SetCommission { new_commission : 7 }.encode(),
+                vec![2, 3, 0]
+            )],
+            address_table_lookups : vec![MessageAddressTableLookup {
+                account_key : table,
+                writable_indexes : vec![2],
+                readonly_indexes : vec![0]
+            }]
+        })
+    }
+
+    #[test]
+    fn parses_base64_and_base58_transactions()
+    {
+        let transaction = set_commission_transaction(&Pubkey::new_unique(), &Keypair::new(), &Pubkey::new_unique());
+
+        let bytes = bincode::serialize(&transaction).unwrap();
+
+        for input in [base64::encode(&bytes), bs58::encode(&bytes).into_string()] {
+            match parse(&input) {
+                Some(Ok(parsed)) => assert_eq!(parsed, transaction),
+                _ => panic!("{} did not parse as a transaction", input)
+            }
+        }
+
+        assert!(parse("not a transaction").is_none());
+        assert!(parse(&base64::encode(&bytes[..bytes.len() - 1])).is_none());
+    }
+
+    #[test]
+    fn parses_a_message_without_signatures()
+    {
+        let payer = Pubkey::new_unique();
+
+        let message = VersionedMessage::Legacy(Message::new(&[], Some(&payer)));
+
+        for input in [base64::encode(message.serialize()), bs58::encode(message.serialize()).into_string()] {
+            match parse(&input) {
+                Some(Err(parsed)) => assert_eq!(parsed, message),
+                _ => panic!("{} did not parse as a message", input)
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_addresses_from_lookup_tables()
+    {
+        let rpc = MockRpc::new();
+
+        let (payer, program_id, table) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let addresses = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let mut data = vec![0_u8; LOOKUP_TABLE_META_SIZE];
+
+        addresses.iter().for_each(|address| data.extend_from_slice(address.as_ref()));
+
+        rpc.set_account(table, Account { lamports : 1, data, ..Account::default() });
+
+        let message = lookup_table_message(payer, program_id, table);
+
+        // Static keys, then writable loaded addresses, then readonly loaded addresses
+        assert_eq!(resolve_account_keys(&rpc, &message), vec![payer, program_id, addresses[2], addresses[0]]);
+    }
+
+    #[test]
+    fn names_accounts_by_their_roles()
+    {
+        let program_id = Pubkey::new_unique();
+
+        let rewards_authority = Keypair::new();
+
+        let vote_account = Pubkey::new_unique();
+
+        let transaction = set_commission_transaction(&program_id, &rewards_authority, &vote_account);
+
+        let output = format_message(&program_id, &transaction.message, transaction.message.static_account_keys());
+
+        assert!(output.contains("Instruction 1 of 1: Vote Account Manager set-commission\n  commission: 7\n"));
+        assert!(output.contains(&format!("    Vote Account: {} (writable)\n", vote_account)));
+        // The rewards authority is also the fee payer
+        let rewards_authority = rewards_authority.pubkey();
+        assert!(output.contains(&format!("    Rewards Authority: {} (signer, writable)\n", rewards_authority)));
+        assert!(!output.contains("WARNING"));
+
+        // The same instruction issued to some other program is not described
+        let output =
+            format_message(&Pubkey::new_unique(), &transaction.message, transaction.message.static_account_keys());
+
+        assert!(output.contains("(not a Vote Account Manager instruction)"));
+    }
+
+    #[test]
+    fn reports_unresolved_account_indexes()
+    {
+        let (payer, program_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let message = lookup_table_message(payer, program_id, Pubkey::new_unique());
+
+        // The lookup table could not be read, so only the static keys are known
+        let output = format_message(&program_id, &message, &[payer, program_id]);
+
+        assert!(output.contains("Vote Account Manager set-commission"));
+        assert!(output.contains("    Vote Account Manager State Account: unresolved account index 2 (writable)\n"));
+        assert!(output.contains("    Vote Account: unresolved account index 3\n"));
+        assert!(output.contains("WARNING: some accounts are loaded from address lookup tables which could not"));
+
+        // Nor is a program loaded from a lookup table
+        let mut message = message;
+
+        if let VersionedMessage::V0(message) = &mut message {
+            message.instructions[0].program_id_index = 3;
+        }
+
+        let output = format_message(&program_id, &message, &[payer, program_id]);
+
+        assert!(output.contains("program unresolved account index 3 (not a Vote Account Manager instruction)"));
+    }
+}
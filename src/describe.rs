@@ -0,0 +1,172 @@
+// Human readable descriptions of vamp program instructions, decoded from their instruction data and accounts
+
+use crate::amount::format_sol;
+use solana_sdk::pubkey::Pubkey;
+use solana_vamp::transaction_data::VampInstruction;
+
+pub struct InstructionDescription
+{
+    // The solana-vamp command which issues the instruction
+    pub action : &'static str,
+
+    // (name, value) pairs giving the parameters of the instruction
+    pub parameters : Vec<(&'static str, String)>
+}
+
+// Describes a vamp program instruction given its data and the pubkeys of its accounts, in instruction order.
+// Returns None if the instruction data is not a valid vamp program instruction.
+pub fn describe_instruction(
+    data : &[u8],
+    accounts : &[Pubkey]
+) -> Option<InstructionDescription>
+{
+    let account = |index : usize| accounts.get(index).map(|pubkey| pubkey.to_string()).unwrap_or("?".to_string());
+
+    let (action, parameters) = match VampInstruction::decode(data).ok()? {
+        VampInstruction::Enter {
+            administrator,
+            use_commission_caps,
+            max_commission,
+            max_commission_increase_per_epoch
+        } => {
+            let mut parameters = vec![("administrator", Pubkey::new_from_array(administrator).to_string())];
+            if use_commission_caps {
+                parameters.push(("max_commission", max_commission.to_string()));
+                parameters.push(("max_commission_increase_per_epoch", max_commission_increase_per_epoch.to_string()));
+            }
+            ("enter", parameters)
+        },
+        VampInstruction::SetLeaveEpoch { leave_epoch } => {
+            ("set-leave-epoch", vec![("leave_epoch", leave_epoch.to_string())])
+        },
+        VampInstruction::Leave => ("leave", vec![("recipient", account(3))]),
+        VampInstruction::SetAdministrator { new_administrator } => {
+            ("set-administrator", vec![("administrator", Pubkey::new_from_array(new_administrator).to_string())])
+        },
+        VampInstruction::SetOperationalAuthority { new_authority } => ("set-operational-authority", vec![(
+            "operational_authority",
+            Pubkey::new_from_array(new_authority).to_string()
+        )]),
+        VampInstruction::SetRewardsAuthority { new_authority } => {
+            ("set-rewards-authority", vec![("rewards_authority", Pubkey::new_from_array(new_authority).to_string())])
+        },
+        VampInstruction::SetVoteAuthority { new_authority } => {
+            ("set-vote-authority", vec![("vote_authority", Pubkey::new_from_array(new_authority).to_string())])
+        },
+        VampInstruction::SetValidatorIdentity => ("set-validator-identity", vec![("validator_identity", account(3))]),
+        VampInstruction::Withdraw { lamports } => ("withdraw", vec![
+            ("recipient", account(3)),
+            ("amount", if lamports == 0 { "ALL".to_string() } else { format_sol(lamports) }),
+        ]),
+        VampInstruction::SetCommission { new_commission } => {
+            ("set-commission", vec![("commission", new_commission.to_string())])
+        },
+    };
+
+    Some(InstructionDescription { action, parameters })
+}
+
+// Returns the roles of the accounts of a vamp program instruction, in instruction order
+pub fn account_roles(instruction_code : u8) -> &'static [&'static str]
+{
+    match instruction_code {
+        0 => &[
+            "Vote Account Manager State Account",
+            "Vote Account",
+            "Funding Account",
+            "Withdraw Authority",
+            "System Program",
+            "Vote Program",
+            "Clock Sysvar"
+        ],
+        1 => &["Vote Account Manager State Account", "Vote Account", "Withdraw Authority"],
+        2 => &[
+            "Vote Account Manager State Account",
+            "Vote Account",
+            "Withdraw Authority",
+            "Lamports Recipient",
+            "Vote Program",
+            "Clock Sysvar"
+        ],
+        3 => &["Vote Account Manager State Account", "Vote Account", "Withdraw Authority"],
+        4 | 5 => &["Vote Account Manager State Account", "Vote Account", "Administrator"],
+        6 => &[
+            "Vote Account Manager State Account",
+            "Vote Account",
+            "Operational Authority",
+            "Vote Program",
+            "Clock Sysvar"
+        ],
+        7 => &[
+            "Vote Account Manager State Account",
+            "Vote Account",
+            "Operational Authority",
+            "New Validator Identity",
+            "Vote Program"
+        ],
+        8 => &["Vote Account Manager State Account", "Vote Account", "Rewards Authority", "Recipient", "Vote Program"],
+        9 => &["Vote Account Manager State Account", "Vote Account", "Rewards Authority", "Vote Program"],
+        _ => &[]
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use solana_vamp::instruction;
+
+    #[test]
+    fn account_roles_name_every_account_of_each_instruction()
+    {
+        let id = Pubkey::new_unique;
+
+        let data = |authority| VampInstruction::SetAdministrator { new_administrator : authority };
+
+        let instructions = [
+            instruction::enter(id(), id(), id(), id(), id(), id(), None),
+            instruction::set_leave_epoch(id(), id(), id(), id(), 10),
+            instruction::leave(id(), id(), id(), id(), id()),
+            instruction::set_authority(id(), id(), id(), id(), data([1; 32])),
+            instruction::set_authority(id(), id(), id(), id(), VampInstruction::SetOperationalAuthority {
+                new_authority : [1; 32]
+            }),
+            instruction::set_authority(id(), id(), id(), id(), VampInstruction::SetRewardsAuthority {
+                new_authority : [1; 32]
+            }),
+            instruction::set_vote_authority(id(), id(), id(), id(), id()),
+            instruction::set_validator_identity(id(), id(), id(), id(), id()),
+            instruction::withdraw(id(), id(), id(), id(), id(), 0),
+            instruction::set_commission(id(), id(), id(), id(), 5)
+        ];
+
+        for (code, instruction) in instructions.iter().enumerate() {
+            assert_eq!(instruction.data[0], code as u8);
+            assert_eq!(account_roles(instruction.data[0]).len(), instruction.accounts.len(), "code {}", code);
+        }
+
+        assert!(account_roles(10).is_empty());
+    }
+
+    #[test]
+    fn describes_parameters_from_data_and_accounts()
+    {
+        let recipient = Pubkey::new_unique();
+
+        let accounts = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), recipient];
+
+        let description =
+            describe_instruction(&VampInstruction::Withdraw { lamports : 0 }.encode(), &accounts).unwrap();
+
+        assert_eq!(description.action, "withdraw");
+        assert_eq!(description.parameters, vec![("recipient", recipient.to_string()), ("amount", "ALL".to_string())]);
+
+        // A missing account is shown rather than failing
+        let description = describe_instruction(&VampInstruction::Leave.encode(), &accounts[..2]).unwrap();
+
+        assert_eq!(description.parameters, vec![("recipient", "?".to_string())]);
+
+        assert!(describe_instruction(&[200], &accounts).is_none());
+        assert!(describe_instruction(&[], &accounts).is_none());
+    }
+}
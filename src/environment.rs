@@ -0,0 +1,150 @@
+// Values of arguments which are not supplied on the command line.  In order of precedence, these come from:
+//
+//   - SOLANA_VAMP_<ARGUMENT> environment variables, for example SOLANA_VAMP_REWARDS_AUTHORITY
+//   - The selected profile: the file ~/.config/solana-vamp/profiles/<PROFILE>, with lines of the form
+//     <argument> = <value>, for example "rewards-authority = /keys/rewards.json"
+//   - The solana CLI config file ~/.config/solana/cli/config.yml, for --url and --commitment only
+//
+// Only arguments declared in the command specs as taking a default from the environment are looked up.
+
+use crate::spec::ArgSpec;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// Relative to the home directory
+pub const PROFILES_DIRECTORY : &str = ".config/solana-vamp/profiles";
+
+const SOLANA_CONFIG_FILE : &str = ".config/solana/cli/config.yml";
+
+pub const PROFILE_VARIABLE : &str = "SOLANA_VAMP_PROFILE";
+
+#[derive(Default)]
+pub struct Environment
+{
+    vars : HashMap<String, String>,
+
+    home : Option<PathBuf>
+}
+
+// The values loaded from each source, by argument name
+#[derive(Default)]
+pub struct Defaults
+{
+    vars : HashMap<String, String>,
+
+    profile : HashMap<String, String>,
+
+    solana_config : HashMap<String, String>
+}
+
+impl Environment
+{
+    pub fn from_process() -> Self
+    {
+        Environment { vars : std::env::vars().collect(), home : std::env::var_os("HOME").map(PathBuf::from) }
+    }
+
+    #[cfg(test)]
+    pub fn new(
+        vars : &[(&str, &str)],
+        home : Option<&std::path::Path>
+    ) -> Self
+    {
+        Environment {
+            vars : vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+            home : home.map(std::path::Path::to_path_buf)
+        }
+    }
+
+    // Loads the defaults from every source.  profile is the profile selected by --profile, if any, which takes
+    // precedence over SOLANA_VAMP_PROFILE.  accepts returns whether an argument name may be given a default.
+    pub fn load(
+        &self,
+        profile : Option<String>,
+        accepts : impl Fn(&str) -> bool
+    ) -> Result<Defaults, String>
+    {
+        let profile = match profile.or_else(|| self.vars.get(PROFILE_VARIABLE).cloned()) {
+            Some(profile) => self.read_profile(&profile, &accepts)?,
+            None => HashMap::new()
+        };
+
+        Ok(Defaults { vars : self.vars.clone(), profile, solana_config : self.read_solana_config() })
+    }
+
+    fn home_file(
+        &self,
+        relative : &str
+    ) -> Option<PathBuf>
+    {
+        self.home.as_ref().map(|home| home.join(relative))
+    }
+
+    fn read_profile(
+        &self,
+        name : &str,
+        accepts : &impl Fn(&str) -> bool
+    ) -> Result<HashMap<String, String>, String>
+    {
+        let path = self
+            .home_file(PROFILES_DIRECTORY)
+            .map(|directory| directory.join(name))
+            .ok_or_else(|| format!("Cannot locate profile {} without a home directory", name))?;
+
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read profile {} ({}): {}", name, path.display(), e))?;
+
+        let mut values = HashMap::new();
+
+        for line in contents.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| format!("Invalid line in profile {}: {}", name, line))?;
+            if !accepts(key) {
+                return Err(format!("Unknown setting {} in profile {}", key, name));
+            }
+            values.insert(key.to_string(), value.to_string());
+        }
+
+        Ok(values)
+    }
+
+    // The solana CLI config is YAML; only its top level "key: value" lines are needed
+    fn read_solana_config(&self) -> HashMap<String, String>
+    {
+        let contents =
+            self.home_file(SOLANA_CONFIG_FILE).and_then(|path| std::fs::read_to_string(path).ok()).unwrap_or_default();
+
+        contents
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .filter_map(|(key, value)| {
+                let name = match key {
+                    "json_rpc_url" => "url",
+                    "commitment" => "commitment",
+                    _ => return None
+                };
+                let value = value.trim().trim_matches(|c| (c == '"') || (c == '\''));
+                (!value.is_empty()).then(|| (name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl Defaults
+{
+    pub fn value(
+        &self,
+        arg : &ArgSpec
+    ) -> Option<String>
+    {
+        // An empty environment variable is taken to be unset
+        self.vars
+            .get(&arg.env_var())
+            .filter(|value| !value.is_empty())
+            .or_else(|| self.profile.get(arg.name))
+            .or_else(|| self.solana_config.get(arg.name))
+            .cloned()
+    }
+}
@@ -0,0 +1,155 @@
+// Recording and replaying of JSON-RPC traffic.  A fixture file is a JSON array of the requests that a command made, in
+// order, each with the response that the RPC node gave:
+//
+//   [ { "method" : "getMultipleAccounts", "params" : [...], "result" : {...} },
+//     { "method" : "getAccountInfo", "params" : [...], "error" : "..." } ]
+//
+// Fixtures recorded against a local test validator are replayed by regression tests so that command output can be
+// checked without any validator.
+
+use crate::error_exit;
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
+use solana_client::rpc_client::{RpcClient, RpcClientConfig};
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::Mutex;
+
+// Returns an RpcClient which makes requests of the RPC node at url, recording them to the fixture file at path
+pub fn record(
+    path : &str,
+    url : String,
+    commitment : CommitmentConfig
+) -> RpcClient
+{
+    let sender = RecordingSender {
+        inner : NonblockingRpcClient::new(url.clone()),
+        url,
+        path : path.to_string(),
+        entries : Mutex::new(vec![])
+    };
+
+    RpcClient::new_sender(sender, RpcClientConfig::with_commitment(commitment))
+}
+
+// Returns an RpcClient which answers requests from the fixture file at path instead of from an RPC node
+pub fn replay(
+    path : &str,
+    commitment : CommitmentConfig
+) -> RpcClient
+{
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| error_exit(&format!("Failed to read RPC fixture {}: {}", path, e)));
+
+    let entries = serde_json::from_str::<Vec<serde_json::Value>>(&contents)
+        .unwrap_or_else(|e| error_exit(&format!("Invalid RPC fixture {}: {}", path, e)))
+        .into_iter()
+        .map(Some)
+        .collect();
+
+    RpcClient::new_sender(
+        ReplaySender { path : path.to_string(), entries : Mutex::new(entries) },
+        RpcClientConfig::with_commitment(commitment)
+    )
+}
+
+struct RecordingSender
+{
+    inner : NonblockingRpcClient,
+
+    url : String,
+
+    path : String,
+
+    entries : Mutex<Vec<serde_json::Value>>
+}
+
+#[async_trait::async_trait]
+impl RpcSender for RecordingSender
+{
+    async fn send(
+        &self,
+        request : RpcRequest,
+        params : serde_json::Value
+    ) -> ClientResult<serde_json::Value>
+    {
+        let response = self.inner.send::<serde_json::Value>(request, params.clone()).await;
+
+        let mut entry = serde_json::json!({ "method" : request.to_string(), "params" : params });
+
+        match &response {
+            Ok(result) => entry["result"] = result.clone(),
+            Err(e) => entry["error"] = serde_json::Value::String(e.to_string())
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.push(entry);
+
+        // The whole fixture is rewritten after every request, since a command may exit at any point
+        std::fs::write(&self.path, serde_json::to_string_pretty(&*entries).unwrap() + "\n")
+            .unwrap_or_else(|e| error_exit(&format!("Failed to write RPC fixture {}: {}", self.path, e)));
+
+        response
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats
+    {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String
+    {
+        self.url.clone()
+    }
+}
+
+struct ReplaySender
+{
+    path : String,
+
+    // Entries which have been replayed are taken, so that a request made more than once gets each recorded response
+    // in turn
+    entries : Mutex<Vec<Option<serde_json::Value>>>
+}
+
+#[async_trait::async_trait]
+impl RpcSender for ReplaySender
+{
+    async fn send(
+        &self,
+        request : RpcRequest,
+        params : serde_json::Value
+    ) -> ClientResult<serde_json::Value>
+    {
+        let method = request.to_string();
+
+        let mut entries = self.entries.lock().unwrap();
+
+        let entry = entries
+            .iter_mut()
+            .find(|entry| {
+                entry.as_ref().map(|entry| (entry["method"] == *method) && (entry["params"] == params)).unwrap_or(false)
+            })
+            .and_then(|entry| entry.take())
+            .ok_or_else(|| {
+                ClientErrorKind::Custom(format!("No response for {} {} recorded in {}", method, params, self.path))
+            })?;
+
+        match entry.get("result") {
+            Some(result) => Ok(result.clone()),
+            None => Err(ClientErrorKind::Custom(entry["error"].as_str().unwrap_or_default().to_string()).into())
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats
+    {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String
+    {
+        format!("replay:{}", self.path)
+    }
+}
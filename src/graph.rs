@@ -0,0 +1,236 @@
+// The permission graph of managed vote accounts, for 'solana-vamp show --graph'.  Each key is a node, and an edge runs
+// from each authority to whatever it controls: the withdraw authority sets the administrator, the administrator sets
+// the operational and rewards authorities, the operational authority sets the vote authority and validator identity,
+// and the rewards authority sets the commission and withdraws.  A key that holds several roles, whether in one vote
+// account or across a fleet, is a single node, highlighted so that key reuse stands out.
+
+use crate::ManagedAccount;
+use solana_sdk::pubkey::Pubkey;
+
+struct Node
+{
+    id : String,
+
+    // A key node is titled by its pubkey, and a capability node by what is controlled
+    title : String,
+
+    // The roles held by a key
+    roles : Vec<String>,
+
+    key : bool
+}
+
+struct Graph
+{
+    // In order of first appearance, so that output is stable
+    nodes : Vec<Node>,
+
+    // (from, to, label)
+    edges : Vec<(String, String, &'static str)>
+}
+
+impl Graph
+{
+    fn key(
+        &mut self,
+        pubkey : &Pubkey,
+        role : String
+    ) -> String
+    {
+        let id = format!("k{}", pubkey);
+        match self.nodes.iter_mut().find(|node| node.id == id) {
+            Some(node) => node.roles.push(role),
+            None => {
+                self.nodes.push(Node { id : id.clone(), title : pubkey.to_string(), roles : vec![role], key : true })
+            },
+        }
+        id
+    }
+
+    fn capability(
+        &mut self,
+        id : String,
+        title : String
+    ) -> String
+    {
+        self.nodes.push(Node { id : id.clone(), title, roles : vec![], key : false });
+        id
+    }
+
+    fn edge(
+        &mut self,
+        from : &str,
+        to : &str,
+        label : &'static str
+    )
+    {
+        let edge = (from.to_string(), to.to_string(), label);
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+}
+
+fn build(accounts : &[ManagedAccount]) -> Graph
+{
+    let mut graph = Graph { nodes : vec![], edges : vec![] };
+
+    for account in accounts {
+        // Roles are qualified by vote account only when there is more than one
+        let of = |role : &str| {
+            if accounts.len() == 1 {
+                role.to_string()
+            }
+            else {
+                format!("{} of {}", role, account.vote_account)
+            }
+        };
+
+        let manager_state = &account.manager_state;
+
+        let withdraw_authority = graph.key(&manager_state.withdraw_authority, of("withdraw authority"));
+        let administrator = graph.key(&manager_state.administrator, of("administrator"));
+        let operational_authority = graph.key(&manager_state.operational_authority, of("operational authority"));
+        let rewards_authority = graph.key(&manager_state.rewards_authority, of("rewards authority"));
+        let vote_authority = graph.key(&account.vote_authority, of("vote authority"));
+        let validator_identity = graph.key(&account.validator_identity, of("validator identity"));
+        let commission = graph.capability(format!("c{}", account.vote_account), of("commission"));
+        let withdraw = graph.capability(format!("w{}", account.vote_account), of("withdraw"));
+
+        graph.edge(&withdraw_authority, &administrator, "sets administrator");
+        graph.edge(&administrator, &operational_authority, "sets operational authority");
+        graph.edge(&administrator, &rewards_authority, "sets rewards authority");
+        graph.edge(&operational_authority, &vote_authority, "sets vote authority");
+        graph.edge(&operational_authority, &validator_identity, "sets validator identity");
+        graph.edge(&rewards_authority, &commission, "sets commission");
+        graph.edge(&rewards_authority, &withdraw, "withdraws");
+    }
+
+    graph
+}
+
+// Renders the permission graph of the accounts in Graphviz dot or Mermaid format, noting the vote accounts which
+// are not managed and so have no place in it
+pub fn render(
+    format : &str,
+    accounts : &[ManagedAccount],
+    unmanaged : &[Pubkey]
+) -> String
+{
+    let graph = build(accounts);
+
+    let (header, comment, line_break) = match format {
+        "dot" => ("digraph vamp {\n    rankdir=TB;\n    node [shape=box];\n", "//", "\\n"),
+        _ => ("flowchart TD\n", "%%", "<br/>")
+    };
+
+    let mut output = String::from(header);
+
+    output.push_str(&format!("    {} Keys which hold more than one role are drawn in red\n", comment));
+
+    for vote_account in unmanaged {
+        output.push_str(&format!(
+            "    {} {} is not managed by the Vote Account Manager program\n",
+            comment, vote_account
+        ));
+    }
+
+    for node in &graph.nodes {
+        let label = std::iter::once(&node.title).chain(&node.roles).cloned().collect::<Vec<String>>().join(line_break);
+        let reused = node.roles.len() > 1;
+        output.push_str(&match (format, node.key) {
+            ("dot", false) => format!("    \"{}\" [label=\"{}\", shape=ellipse];\n", node.id, label),
+            ("dot", true) if reused => {
+                format!("    \"{}\" [label=\"{}\", color=red, fontcolor=red];\n", node.id, label)
+            },
+            ("dot", true) => format!("    \"{}\" [label=\"{}\"];\n", node.id, label),
+            (_, false) => format!("    {}([\"{}\"])\n", node.id, label),
+            (_, true) if reused => format!("    {}[\"{}\"]:::reused\n", node.id, label),
+            (_, true) => format!("    {}[\"{}\"]\n", node.id, label)
+        });
+    }
+
+    for (from, to, label) in &graph.edges {
+        output.push_str(&match format {
+            "dot" => format!("    \"{}\" -> \"{}\" [label=\"{}\"];\n", from, to, label),
+            _ => format!("    {} -->|{}| {}\n", from, label, to)
+        });
+    }
+
+    output.push_str(match format {
+        "dot" => "}\n",
+        _ => "    classDef reused stroke:#d00,stroke-width:3px,color:#d00\n"
+    });
+
+    output
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use solana_vamp::state::ManagerState;
+
+    fn account(roles : [Pubkey; 6]) -> ManagedAccount
+    {
+        ManagedAccount {
+            vote_account : Pubkey::new_unique(),
+            manager_state : ManagerState {
+                withdraw_authority : roles[0],
+                administrator : roles[1],
+                operational_authority : roles[2],
+                rewards_authority : roles[3],
+                commission_caps : None,
+                leave_epoch : 0
+            },
+            vote_authority : roles[4],
+            validator_identity : roles[5]
+        }
+    }
+
+    fn keys() -> [Pubkey; 6]
+    {
+        [(); 6].map(|_| Pubkey::new_unique())
+    }
+
+    #[test]
+    fn single_account_is_annotated_with_its_keys()
+    {
+        let roles = keys();
+        let account = account(roles);
+
+        let dot = render("dot", &[account], &[]);
+
+        assert!(dot.starts_with("digraph vamp {\n"));
+        assert!(dot.contains(&format!("    \"k{}\" [label=\"{}\\nwithdraw authority\"];\n", roles[0], roles[0])));
+        assert!(dot.contains(&format!("    \"k{}\" -> \"k{}\" [label=\"sets administrator\"];\n", roles[0], roles[1])));
+        assert!(
+            dot.contains(&format!("    \"k{}\" -> \"k{}\" [label=\"sets validator identity\"];\n", roles[2], roles[5]))
+        );
+        assert!(!dot.contains("color=red"));
+        assert_eq!(dot.matches(" -> ").count(), 7);
+    }
+
+    #[test]
+    fn keys_shared_across_a_fleet_are_merged_and_highlighted()
+    {
+        let shared = Pubkey::new_unique();
+        let mut first = keys();
+        let mut second = keys();
+        first[0] = shared;
+        second[0] = shared;
+        // The administrator of the second vote account is also its rewards authority
+        second[3] = second[1];
+        let (first, second) = (account(first), account(second));
+        let unmanaged = Pubkey::new_unique();
+
+        let mermaid = render("mermaid", &[first, second], &[unmanaged]);
+
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains(&format!("%% {} is not managed", unmanaged)));
+        assert_eq!(mermaid.matches(&format!("    k{}[", shared)).count(), 1);
+        assert!(mermaid.contains(&format!("    k{}[\"{}<br/>withdraw authority of ", shared, shared)));
+        assert_eq!(mermaid.matches(":::reused").count(), 2);
+        assert_eq!(mermaid.matches(" -->|").count(), 14);
+    }
+}
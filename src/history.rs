@@ -0,0 +1,337 @@
+// Timeline of the vamp program instructions that have been issued for a vote account
+
+use crate::describe::{describe_instruction, InstructionDescription};
+use crate::rpc::Rpc;
+use crate::{error_exit, format_utc};
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionEncoding;
+use std::str::FromStr;
+
+// Maximum number of signatures returned by a single getSignaturesForAddress call
+const MAX_SIGNATURES_PER_REQUEST : usize = 1000;
+
+struct HistoryEntry
+{
+    signature : Signature,
+
+    slot : u64,
+
+    block_time : Option<i64>,
+
+    // None if the transaction succeeded
+    error : Option<String>,
+
+    signers : Vec<Pubkey>,
+
+    // None if the instruction data could not be decoded
+    description : Option<InstructionDescription>
+}
+
+pub fn history(
+    rpc_client : &dyn Rpc,
+    program_id : &Pubkey,
+    vote_account_manager_state_account : &Pubkey,
+    limit : usize,
+    json : bool
+)
+{
+    let mut entries = vec![];
+
+    // Signatures are returned newest first; the timeline is displayed oldest first
+    for signature in get_signatures(rpc_client, vote_account_manager_state_account, limit).iter().rev() {
+        entries.extend(get_entries(rpc_client, program_id, signature));
+    }
+
+    if json {
+        println!("[{}]", entries.iter().map(entry_json).collect::<Vec<String>>().join(","));
+    }
+    else {
+        println!();
+        for entry in &entries {
+            println!(
+                "{:<20}  {:>10}  {:<6}  {:<25}  {}",
+                entry.block_time.map(format_utc).unwrap_or("-".to_string()),
+                entry.slot,
+                if entry.error.is_none() { "ok" } else { "FAILED" },
+                entry.description.as_ref().map(|description| description.action).unwrap_or("(unrecognized)"),
+                entry
+                    .description
+                    .as_ref()
+                    .map(|description| {
+                        description
+                            .parameters
+                            .iter()
+                            .map(|(name, value)| format!("{}={}", name, value))
+                            .collect::<Vec<String>>()
+                            .join(" ")
+                    })
+                    .unwrap_or_default()
+            );
+            println!(
+                "{:<20}  {:>10}  Signed by: {}",
+                "",
+                "",
+                entry.signers.iter().map(|signer| signer.to_string()).collect::<Vec<String>>().join(", ")
+            );
+            println!("{:<20}  {:>10}  Signature: {}", "", "", entry.signature);
+            if let Some(error) = &entry.error {
+                println!("{:<20}  {:>10}  Error: {}", "", "", error);
+            }
+        }
+        println!();
+    }
+}
+
+// Returns the most recent signatures of transactions referencing address, newest first, up to limit signatures
+fn get_signatures(
+    rpc_client : &dyn Rpc,
+    address : &Pubkey,
+    limit : usize
+) -> Vec<Signature>
+{
+    let mut signatures = vec![];
+
+    while signatures.len() < limit {
+        let count = std::cmp::min(limit - signatures.len(), MAX_SIGNATURES_PER_REQUEST);
+
+        let results = rpc_client
+            .get_signatures_for_address_with_config(address, GetConfirmedSignaturesForAddress2Config {
+                before : signatures.last().cloned(),
+                until : None,
+                limit : Some(count),
+                commitment : Some(rpc_client.commitment())
+            })
+            .unwrap_or_else(|e| error_exit(&format!("Failed to fetch signatures for {}: {}", address, e)));
+
+        let done = results.len() < count;
+
+        signatures.extend(results.iter().map(|result| Signature::from_str(&result.signature).unwrap()));
+
+        if done {
+            break;
+        }
+    }
+
+    signatures
+}
+
+// A transaction fetched from the ledger
+pub struct FetchedTransaction
+{
+    pub slot : u64,
+
+    pub block_time : Option<i64>,
+
+    // None if the transaction succeeded
+    pub error : Option<String>,
+
+    pub transaction : VersionedTransaction,
+
+    // The static account keys of the transaction followed by any keys loaded from address lookup tables
+    pub account_keys : Vec<Pubkey>
+}
+
+pub fn fetch_transaction(
+    rpc_client : &dyn Rpc,
+    signature : &Signature
+) -> FetchedTransaction
+{
+    let confirmed = rpc_client
+        .get_transaction_with_config(signature, RpcTransactionConfig {
+            encoding : Some(UiTransactionEncoding::Base64),
+            commitment : Some(rpc_client.commitment()),
+            max_supported_transaction_version : Some(0)
+        })
+        .unwrap_or_else(|e| error_exit(&format!("Failed to fetch transaction {}: {}", signature, e)));
+
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .unwrap_or_else(|| error_exit(&format!("Failed to decode transaction {}", signature)));
+
+    let meta = confirmed.transaction.meta;
+
+    let error = meta.as_ref().and_then(|meta| meta.err.as_ref()).map(|err| err.to_string());
+
+    let mut account_keys = transaction.message.static_account_keys().to_vec();
+
+    if let Some(OptionSerializer::Some(loaded_addresses)) = meta.map(|meta| meta.loaded_addresses) {
+        account_keys.extend(
+            loaded_addresses
+                .writable
+                .iter()
+                .chain(loaded_addresses.readonly.iter())
+                .filter_map(|address| Pubkey::from_str(address).ok())
+        );
+    }
+
+    FetchedTransaction { slot : confirmed.slot, block_time : confirmed.block_time, error, transaction, account_keys }
+}
+
+// Returns one entry per vamp program instruction in the transaction with the given signature
+fn get_entries(
+    rpc_client : &dyn Rpc,
+    program_id : &Pubkey,
+    signature : &Signature
+) -> Vec<HistoryEntry>
+{
+    let fetched = fetch_transaction(rpc_client, signature);
+
+    let message = &fetched.transaction.message;
+
+    let account_keys = &fetched.account_keys;
+
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| account_keys.get(instruction.program_id_index as usize) == Some(program_id))
+        .map(|instruction| {
+            // Only the accounts up to the first unresolved one, so that each keeps its position
+            let accounts = instruction
+                .accounts
+                .iter()
+                .map_while(|index| account_keys.get(*index as usize).cloned())
+                .collect::<Vec<Pubkey>>();
+            HistoryEntry {
+                signature : *signature,
+                slot : fetched.slot,
+                block_time : fetched.block_time,
+                error : fetched.error.clone(),
+                signers : instruction
+                    .accounts
+                    .iter()
+                    .filter(|index| message.is_signer(**index as usize))
+                    .filter_map(|index| account_keys.get(*index as usize).cloned())
+                    .collect(),
+                description : describe_instruction(&instruction.data, &accounts)
+            }
+        })
+        .collect()
+}
+
+fn entry_json(entry : &HistoryEntry) -> String
+{
+    let mut json = format!("{{\"signature\":\"{}\",\"slot\":{}", entry.signature, entry.slot);
+
+    if let Some(block_time) = entry.block_time {
+        json.push_str(&format!(",\"block_time\":{},\"time\":\"{}\"", block_time, format_utc(block_time)));
+    }
+
+    json.push_str(&format!(",\"success\":{}", entry.error.is_none()));
+
+    if let Some(error) = &entry.error {
+        json.push_str(&format!(",\"error\":\"{}\"", error.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+
+    json.push_str(&format!(
+        ",\"signers\":[{}]",
+        entry.signers.iter().map(|signer| format!("\"{}\"", signer)).collect::<Vec<String>>().join(",")
+    ));
+
+    match &entry.description {
+        Some(description) => json.push_str(&format!(
+            ",\"action\":\"{}\",\"parameters\":{{{}}}",
+            description.action,
+            description
+                .parameters
+                .iter()
+                .map(|(name, value)| format!("\"{}\":\"{}\"", name, value))
+                .collect::<Vec<String>>()
+                .join(",")
+        )),
+        None => json.push_str(",\"action\":null")
+    }
+
+    json.push('}');
+
+    json
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::mock::MockRpc;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::signer::keypair::Keypair;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::Transaction;
+    use solana_vamp::instruction::{self, get_vote_account_manager_state_account};
+
+    #[test]
+    fn timeline_of_vamp_instructions()
+    {
+        let rpc = MockRpc::new();
+
+        let program_id = Pubkey::new_unique();
+
+        let vote_account = Pubkey::new_unique();
+
+        let manager = get_vote_account_manager_state_account(&program_id, &vote_account);
+
+        let authority = Keypair::new();
+
+        for (slot, leave_epoch) in [(10, 500), (20, 600), (30, 700)] {
+            let tx = Transaction::new_signed_with_payer(
+                &[instruction::set_leave_epoch(program_id, vote_account, manager, authority.pubkey(), leave_epoch)],
+                Some(&authority.pubkey()),
+                &[&authority],
+                Hash::new_unique()
+            );
+            rpc.add_transaction(slot, tx.into());
+        }
+
+        // Newest first, limited
+        let signatures = get_signatures(&rpc, &manager, 2);
+
+        assert_eq!(signatures.len(), 2);
+
+        let entries = signatures.iter().rev().flat_map(|signature| get_entries(&rpc, &program_id, signature));
+
+        let entries = entries.collect::<Vec<HistoryEntry>>();
+
+        assert_eq!(entries.iter().map(|entry| entry.slot).collect::<Vec<u64>>(), vec![20, 30]);
+        assert_eq!(entries[0].signers, vec![authority.pubkey()]);
+        assert_eq!(entries[1].description.as_ref().unwrap().parameters, vec![("leave_epoch", "700".to_string())]);
+        assert!(entry_json(&entries[0]).contains("\"slot\":20"));
+    }
+
+    #[test]
+    fn entry_json_describes_the_instruction_and_its_outcome()
+    {
+        let signer = Pubkey::new_unique();
+
+        let entry = HistoryEntry {
+            signature : Signature::default(),
+            slot : 20,
+            block_time : Some(0),
+            error : Some("custom \"error\"".to_string()),
+            signers : vec![signer],
+            description : Some(InstructionDescription {
+                action : "set-leave-epoch",
+                parameters : vec![("leave_epoch", "700".to_string())]
+            })
+        };
+
+        assert_eq!(
+            entry_json(&entry),
+            format!(
+                "{{\"signature\":\"{}\",\"slot\":20,\"block_time\":0,\"time\":\"1970-01-01T00:00:00Z\",\
+                 \"success\":false,\"error\":\"custom \\\"error\\\"\",\"signers\":[\"{}\"],\
+                 \"action\":\"set-leave-epoch\",\"parameters\":{{\"leave_epoch\":\"700\"}}}}",
+                Signature::default(),
+                signer
+            )
+        );
+
+        let entry = HistoryEntry { block_time : None, error : None, signers : vec![], description : None, ..entry };
+
+        assert!(entry_json(&entry).ends_with(",\"slot\":20,\"success\":true,\"signers\":[],\"action\":null}"));
+    }
+}
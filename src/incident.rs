@@ -0,0 +1,756 @@
+// Response to a compromised key, for 'solana-vamp incident'.  Every managed vote account in which the key holds a role
+// is found with getProgramAccounts filters, what the key allows an attacker to do is explained, and the fewest
+// rotations that take the key's roles away are worked out.  The roles are those of the manager state, and the vote
+// authority and validator identity of the vote account, which the operational authority controls.  Each role is
+// revoked by the authority above it, and since nothing is above the withdraw authority, a compromised withdraw
+// authority can only be escaped by leaving the program.
+
+use crate::args::BatchStep;
+use crate::make_pubkey_helper;
+use crate::rotate::{self, Rotation};
+use crate::rpc::Rpc;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::vote;
+use solana_sdk::vote::state::VoteState;
+use solana_vamp::instruction::get_vote_account_manager_state_account;
+use solana_vamp::state::{ManagerState, MANAGER_STATE_SIZE};
+use std::collections::BTreeMap;
+
+// The offset of the authorized withdrawer in vote account data: after the 4 byte version and the 32 byte node pubkey
+const VOTE_ACCOUNT_WITHDRAWER_OFFSET : usize = 36;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role
+{
+    WithdrawAuthority,
+
+    Administrator,
+
+    OperationalAuthority,
+
+    RewardsAuthority,
+
+    VoteAuthority,
+
+    ValidatorIdentity
+}
+
+// The roles held in the manager state
+const MANAGER_ROLES : [Role; 4] =
+    [Role::WithdrawAuthority, Role::Administrator, Role::OperationalAuthority, Role::RewardsAuthority];
+
+// The roles held in the vote account, which the operational authority sets
+const VOTE_ROLES : [Role; 2] = [Role::VoteAuthority, Role::ValidatorIdentity];
+
+impl Role
+{
+    fn name(self) -> &'static str
+    {
+        match self {
+            Role::WithdrawAuthority => "withdraw authority",
+            Role::Administrator => "administrator",
+            Role::OperationalAuthority => "operational authority",
+            Role::RewardsAuthority => "rewards authority",
+            Role::VoteAuthority => "vote authority",
+            Role::ValidatorIdentity => "validator identity"
+        }
+    }
+
+    // The offset of the role's pubkey in manager state account data, for the roles held there
+    fn offset(self) -> usize
+    {
+        match self {
+            Role::WithdrawAuthority => 0,
+            Role::Administrator => 32,
+            Role::OperationalAuthority => 64,
+            Role::RewardsAuthority => 96,
+            Role::VoteAuthority | Role::ValidatorIdentity => {
+                unreachable!("{} is not held in the manager state", self.name())
+            }
+        }
+    }
+
+    fn holder(
+        self,
+        exposure : &Exposure
+    ) -> Pubkey
+    {
+        match self {
+            Role::WithdrawAuthority => exposure.manager_state.withdraw_authority,
+            Role::Administrator => exposure.manager_state.administrator,
+            Role::OperationalAuthority => exposure.manager_state.operational_authority,
+            Role::RewardsAuthority => exposure.manager_state.rewards_authority,
+            Role::VoteAuthority => exposure.vote_authority,
+            Role::ValidatorIdentity => exposure.validator_identity
+        }
+    }
+
+    // What an attacker holding the role can do
+    fn consequences(self) -> &'static str
+    {
+        match self {
+            Role::WithdrawAuthority => {
+                "can make anyone the administrator and so take every other role, and can take the vote account out of \
+                 the program whenever leaving is allowed and then withdraw all of its lamports"
+            },
+            Role::Administrator => {
+                "can replace the operational and rewards authorities, and so take control of voting, the validator \
+                 identity, the commission and the rewards"
+            },
+            Role::OperationalAuthority => {
+                "can replace the vote authority and the validator identity, and so stop the validator from voting or \
+                 vote with it in ways that cost it rewards"
+            },
+            Role::RewardsAuthority => {
+                "can withdraw the rewards of the vote account to any account, and change its commission within any \
+                 commission caps"
+            },
+            Role::VoteAuthority => "can vote with the vote account in ways that cost it rewards",
+            Role::ValidatorIdentity => {
+                "can spend the identity account, which pays for voting, and run a validator as the vote account's \
+                 node"
+            },
+        }
+    }
+}
+
+// The rotations that revoke the compromised key, each with a description, and the vote accounts whose rotations could
+// not be signed, each with the reason
+pub type Plan = (Vec<(String, BatchStep)>, Vec<(Pubkey, String)>);
+
+// A managed vote account in which the compromised key holds at least one role
+pub struct Exposure
+{
+    pub vote_account : Pubkey,
+
+    pub manager_state : ManagerState,
+
+    pub vote_authority : Pubkey,
+
+    pub validator_identity : Pubkey,
+
+    // The roles held by the compromised key, in MANAGER_ROLES and then VOTE_ROLES order
+    pub roles : Vec<Role>
+}
+
+impl Exposure
+{
+    fn holds(
+        &self,
+        role : Role
+    ) -> bool
+    {
+        self.roles.contains(&role)
+    }
+}
+
+// Finds every managed vote account in which the compromised key holds a role, ordered by vote account
+pub fn find(
+    rpc_client : &dyn Rpc,
+    program_id : &Pubkey,
+    compromised : &Pubkey
+) -> Result<Vec<Exposure>, String>
+{
+    // Manager state account -> (manager state, roles held)
+    let mut found : BTreeMap<Pubkey, (ManagerState, Vec<Role>)> = BTreeMap::new();
+
+    for role in MANAGER_ROLES {
+        let accounts = rpc_client
+            .get_program_accounts_with_filters(program_id, vec![
+                RpcFilterType::DataSize(MANAGER_STATE_SIZE as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(role.offset(), compromised.as_ref())),
+            ])
+            .map_err(|e| format!("Failed to fetch manager state accounts: {}", e))?;
+
+        for (manager_state_account, account) in accounts {
+            if let Some(manager_state) = ManagerState::decode(&account.data) {
+                found.entry(manager_state_account).or_insert_with(|| (manager_state, vec![])).1.push(role);
+            }
+        }
+    }
+
+    // Vote account -> exposure
+    let mut exposures : BTreeMap<Pubkey, Exposure> = BTreeMap::new();
+
+    for (manager_state_account, (manager_state, roles)) in found {
+        // The vote account is the one whose withdrawer is its own manager state account
+        let vote_accounts = rpc_client
+            .get_program_accounts_with_filters(&vote::program::id(), vec![RpcFilterType::Memcmp(
+                Memcmp::new_base58_encoded(VOTE_ACCOUNT_WITHDRAWER_OFFSET, manager_state_account.as_ref())
+            )])
+            .map_err(|e| format!("Failed to fetch vote accounts: {}", e))?;
+
+        // A manager state account without such a vote account is left over from a vote account that has left the
+        // program, and grants no authority
+        if let Some((vote_account, vote_state)) = vote_accounts.into_iter().find_map(|(vote_account, account)| {
+            (get_vote_account_manager_state_account(program_id, &vote_account) == manager_state_account)
+                .then(|| VoteState::deserialize(&account.data).ok().map(|vote_state| (vote_account, vote_state)))
+                .flatten()
+        }) {
+            exposures.insert(vote_account, exposure(vote_account, manager_state, &vote_state, roles));
+        }
+    }
+
+    // The vote authority follows the variable length list of votes in vote account data, so it cannot be matched by
+    // a filter; every vote account is checked instead
+    let vote_accounts = rpc_client
+        .get_program_accounts_with_filters(&vote::program::id(), vec![])
+        .map_err(|e| format!("Failed to fetch vote accounts: {}", e))?;
+
+    for (vote_account, account) in vote_accounts {
+        let vote_state = match VoteState::deserialize(&account.data) {
+            Ok(vote_state) => vote_state,
+            Err(_) => continue
+        };
+
+        let manager_state_account = get_vote_account_manager_state_account(program_id, &vote_account);
+
+        let roles = VOTE_ROLES
+            .into_iter()
+            .zip(vote_role_holders(&vote_state))
+            .filter_map(|(role, holder)| (holder == *compromised).then_some(role))
+            .collect::<Vec<Role>>();
+
+        if roles.is_empty() || (vote_state.authorized_withdrawer != manager_state_account) {
+            continue;
+        }
+
+        match exposures.get_mut(&vote_account) {
+            Some(exposure) => exposure.roles.extend(roles),
+            None => {
+                let manager_state = rpc_client
+                    .get_account(&manager_state_account)
+                    .ok()
+                    .and_then(|account| ManagerState::decode(&account.data))
+                    .ok_or_else(|| format!("Failed to fetch manager state account {}", manager_state_account))?;
+                exposures.insert(vote_account, exposure(vote_account, manager_state, &vote_state, roles));
+            }
+        }
+    }
+
+    Ok(exposures.into_values().collect())
+}
+
+// The holders of the VOTE_ROLES of a vote account, in order
+fn vote_role_holders(vote_state : &VoteState) -> [Pubkey; 2]
+{
+    [
+        vote_state.authorized_voters().last().map(|(_, authorized_voter)| *authorized_voter).unwrap_or_default(),
+        vote_state.node_pubkey
+    ]
+}
+
+fn exposure(
+    vote_account : Pubkey,
+    manager_state : ManagerState,
+    vote_state : &VoteState,
+    roles : Vec<Role>
+) -> Exposure
+{
+    let [vote_authority, validator_identity] = vote_role_holders(vote_state);
+
+    Exposure { vote_account, manager_state, vote_authority, validator_identity, roles }
+}
+
+// Describes the exposure of each vote account, what an attacker can do with it, and how to revoke it
+pub fn report(
+    compromised : &Pubkey,
+    exposures : &[Exposure],
+    current_epoch : u64
+) -> String
+{
+    let mut report = match exposures.len() {
+        0 => return format!("{} holds no role in any vote account managed by the program\n", compromised),
+        1 => format!("{} holds roles in 1 managed vote account\n", compromised),
+        n => format!("{} holds roles in {} managed vote accounts\n", compromised, n)
+    };
+
+    for exposure in exposures {
+        let manager_state = &exposure.manager_state;
+
+        report.push_str(&format!("\nVote account {}\n", exposure.vote_account));
+
+        for role in MANAGER_ROLES.into_iter().chain(VOTE_ROLES) {
+            let mut name = role.name().to_string();
+            name[..1].make_ascii_uppercase();
+            let compromised = if exposure.holds(role) { " (compromised)" } else { "" };
+            report.push_str(&format!("  {:<23}{}{}\n", format!("{}:", name), role.holder(exposure), compromised));
+        }
+
+        report.push_str(&format!("  {:<23}{}\n", "Commission caps:", match manager_state.commission_caps {
+            Some((max, increase)) => format!("{}% maximum, {}% maximum increase per epoch", max, increase),
+            None => "none".to_string()
+        }));
+
+        report.push_str(&format!("  {:<23}{}\n", "Leave epoch:", match manager_state.leave_epoch {
+            0 => "none".to_string(),
+            leave_epoch => leave_epoch.to_string()
+        }));
+
+        report.push_str("\n  With this key, an attacker:\n");
+
+        for role in &exposure.roles {
+            report.push_str(&format!("    As {}, {}\n", role.name(), role.consequences()));
+        }
+
+        report.push_str("\n  To revoke the key:\n");
+
+        for revocation in revocations(exposure, current_epoch) {
+            report.push_str(&format!("    {}\n", revocation));
+        }
+    }
+
+    report
+}
+
+// The steps that take the compromised key's roles away from a vote account, in the order they must be taken
+fn revocations(
+    exposure : &Exposure,
+    current_epoch : u64
+) -> Vec<String>
+{
+    if exposure.holds(Role::WithdrawAuthority) {
+        let manager_state = &exposure.manager_state;
+
+        // Any role could be taken back by the attacker through the withdraw authority, so replacing them is futile
+        let mut revocations =
+            vec!["The withdraw authority cannot be replaced while the vote account is in the program, \
+                                    and it can take back any other role, so none are worth replacing"
+                .to_string()];
+
+        let leave = "Leave the program with 'solana-vamp leave' and then authorize a new withdrawer with 'solana \
+                     vote-authorize-withdrawer', before the attacker does the same";
+
+        if manager_state.commission_caps.is_none() ||
+            ((manager_state.leave_epoch != 0) && (current_epoch >= manager_state.leave_epoch))
+        {
+            revocations.push(format!("{}; leaving is allowed now", leave));
+        }
+        else if manager_state.leave_epoch == 0 {
+            revocations.push(format!(
+                "Set the leave epoch to {} with 'solana-vamp set-leave-epoch', the earliest allowed, as the attacker \
+                 also can",
+                current_epoch + 2
+            ));
+            revocations.push(format!("{} once that epoch is reached", leave));
+        }
+        else {
+            revocations.push(format!("{} once epoch {} is reached", leave, manager_state.leave_epoch));
+        }
+
+        return revocations;
+    }
+
+    let mut revocations = vec![];
+
+    if exposure.holds(Role::Administrator) {
+        revocations
+            .push("Replace the administrator with 'set-administrator', signed by the withdraw authority".to_string());
+    }
+
+    let administrator = if exposure.holds(Role::Administrator) { "new administrator" } else { "administrator" };
+
+    for (role, command) in
+        [(Role::RewardsAuthority, "set-rewards-authority"), (Role::OperationalAuthority, "set-operational-authority")]
+    {
+        if exposure.holds(role) {
+            revocations.push(format!(
+                "Replace the {} with '{}', signed by the {}",
+                role.name(),
+                command,
+                administrator
+            ));
+        }
+    }
+
+    let operational_authority =
+        if exposure.holds(Role::OperationalAuthority) { "new operational authority" } else { "operational authority" };
+
+    if exposure.holds(Role::VoteAuthority) {
+        revocations.push(format!(
+            "Replace the vote authority with 'set-vote-authority', signed by the {}",
+            operational_authority
+        ));
+    }
+
+    if exposure.holds(Role::ValidatorIdentity) {
+        revocations.push(format!(
+            "Replace the validator identity with 'set-validator-identity', signed by the {} and the new validator \
+             identity, and restart the validator with the new identity",
+            operational_authority
+        ));
+    }
+
+    // The attacker may already have used the administrator or operational authority role
+    if exposure.holds(Role::Administrator) {
+        revocations.push(
+            "Check that the operational and rewards authorities above are as expected, since the attacker may already \
+             have replaced them"
+                .to_string()
+        );
+    }
+
+    if exposure.holds(Role::Administrator) || exposure.holds(Role::OperationalAuthority) {
+        revocations.push(
+            "Check that the vote authority and validator identity above are as expected, since the attacker may \
+             already have replaced them"
+                .to_string()
+        );
+    }
+
+    revocations
+}
+
+// The arguments that give the replacements needed to revoke the key from every vote account that it can be revoked from
+pub fn replacement_flags(exposures : &[Exposure]) -> Vec<&'static str>
+{
+    [
+        (Role::Administrator, "--new-administrator"),
+        (Role::RewardsAuthority, "--new-rewards-authority"),
+        (Role::OperationalAuthority, "--new-operational-authority"),
+        (Role::VoteAuthority, "--new-vote-authority"),
+        (Role::ValidatorIdentity, "--new-validator-identity")
+    ]
+    .into_iter()
+    .filter(|(role, _)| {
+        exposures.iter().any(|exposure| exposure.holds(*role) && !exposure.holds(Role::WithdrawAuthority))
+    })
+    .map(|(_, flag)| flag)
+    .collect()
+}
+
+// Works out the rotations that revoke the compromised key from every vote account that it can be revoked from, using
+// the new authorities and current authority keypairs of replacements, each with a description.  Each vote account is
+// planned by itself: the vote accounts of a fleet may have different authorities, and one whose rotations cannot be
+// signed with the supplied keypairs is returned with the reason rather than holding up the rest.
+pub fn plan(
+    compromised : &Pubkey,
+    exposures : &[Exposure],
+    replacements : &Rotation
+) -> Result<Plan, String>
+{
+    let mut steps = vec![];
+
+    let mut unsigned = vec![];
+
+    for exposure in exposures.iter().filter(|exposure| !exposure.holds(Role::WithdrawAuthority)) {
+        let replacement = |role : Role, value : &Option<String>, flag : &str| -> Result<Option<String>, String> {
+            if !exposure.holds(role) {
+                return Ok(None);
+            }
+
+            let value = value.as_ref().ok_or_else(|| {
+                format!("--{} must be supplied to replace the {} of {}", flag, role.name(), exposure.vote_account)
+            })?;
+
+            if make_pubkey_helper(value).ok() == Some(*compromised) {
+                return Err(format!("The new {} cannot be the compromised key", role.name()));
+            }
+
+            Ok(Some(value.clone()))
+        };
+
+        let rotation = Rotation {
+            withdraw_authority : replacements.withdraw_authority.clone(),
+            administrator : replacements.administrator.clone(),
+            operational_authority : replacements.operational_authority.clone(),
+            new_administrator : replacement(Role::Administrator, &replacements.new_administrator, "new-administrator")?,
+            new_rewards_authority : replacement(
+                Role::RewardsAuthority,
+                &replacements.new_rewards_authority,
+                "new-rewards-authority"
+            )?,
+            new_operational_authority : replacement(
+                Role::OperationalAuthority,
+                &replacements.new_operational_authority,
+                "new-operational-authority"
+            )?,
+            new_vote_authority : replacement(
+                Role::VoteAuthority,
+                &replacements.new_vote_authority,
+                "new-vote-authority"
+            )?,
+            new_validator_identity : replacement(
+                Role::ValidatorIdentity,
+                &replacements.new_validator_identity,
+                "new-validator-identity"
+            )?
+        };
+
+        match rotate::plan(&rotation, &exposure.vote_account, &exposure.manager_state) {
+            Ok(rotations) => steps.extend(rotations),
+            Err(e) => unsigned.push((exposure.vote_account, e))
+        }
+    }
+
+    Ok((steps, unsigned))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::args::Command;
+    use crate::rpc::mock::{KeypairFile, MockRpc};
+
+    // Puts a vote account under program control with the given roles
+    fn manage(
+        rpc : &MockRpc,
+        program_id : &Pubkey,
+        roles : [Pubkey; 4],
+        commission_caps : Option<(u8, u8)>
+    ) -> Pubkey
+    {
+        let vote_account = Pubkey::new_unique();
+        let manager_state_account = get_vote_account_manager_state_account(program_id, &vote_account);
+        rpc.set_vote_account(vote_account, manager_state_account, 5, 1_000_000_000);
+        rpc.set_manager_state_roles(*program_id, manager_state_account, roles, commission_caps, 0);
+        vote_account
+    }
+
+    #[test]
+    fn finds_every_role_held_by_the_compromised_key()
+    {
+        let rpc = MockRpc::default();
+        let program_id = Pubkey::new_unique();
+        let compromised = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        let administered = manage(&rpc, &program_id, [other, compromised, other, compromised], None);
+        let withdrawn = manage(&rpc, &program_id, [compromised, other, other, other], Some((10, 1)));
+        manage(&rpc, &program_id, [other; 4], None);
+
+        // Vote accounts that have left the program no longer grant any authority
+        let left = Pubkey::new_unique();
+        rpc.set_vote_account(left, other, 5, 1_000_000_000);
+        rpc.set_manager_state(
+            program_id,
+            get_vote_account_manager_state_account(&program_id, &left),
+            compromised,
+            None,
+            0
+        );
+
+        let exposures = find(&rpc, &program_id, &compromised).unwrap();
+
+        let found : Vec<(Pubkey, Vec<Role>)> =
+            exposures.iter().map(|exposure| (exposure.vote_account, exposure.roles.clone())).collect();
+
+        let mut expected = vec![
+            (administered, vec![Role::Administrator, Role::RewardsAuthority]),
+            (withdrawn, vec![Role::WithdrawAuthority]),
+        ];
+        expected.sort_by_key(|(vote_account, _)| *vote_account);
+
+        assert_eq!(found, expected);
+
+        let report = report(&compromised, &exposures, 100);
+        assert!(report.starts_with(&format!("{} holds roles in 2 managed vote accounts\n", compromised)));
+        assert!(report.contains("Replace the administrator with 'set-administrator', signed by the withdraw"));
+        assert!(report
+            .contains("Replace the rewards authority with 'set-rewards-authority', signed by the new administrator"));
+        assert!(report.contains("Set the leave epoch to 102 with 'solana-vamp set-leave-epoch'"));
+
+        assert_eq!(replacement_flags(&exposures), vec!["--new-administrator", "--new-rewards-authority"]);
+    }
+
+    #[test]
+    fn plans_the_fewest_rotations_that_revoke_the_key()
+    {
+        let rpc = MockRpc::default();
+        let program_id = Pubkey::new_unique();
+        let compromised_file = KeypairFile::new();
+        let withdraw_authority_file = KeypairFile::new();
+        let administrator_file = KeypairFile::new();
+        let new_administrator_file = KeypairFile::new();
+        let (compromised, withdraw_authority, administrator) =
+            (compromised_file.pubkey(), withdraw_authority_file.pubkey(), administrator_file.pubkey());
+        let other = Pubkey::new_unique();
+
+        let administered = manage(&rpc, &program_id, [withdraw_authority, compromised, other, other], None);
+        let operated = manage(&rpc, &program_id, [other, administrator, compromised, other], None);
+        // Nothing can be revoked from a vote account whose withdraw authority is compromised
+        manage(&rpc, &program_id, [compromised; 4], None);
+
+        let exposures = find(&rpc, &program_id, &compromised).unwrap();
+
+        let mut replacements = Rotation {
+            withdraw_authority : Some(withdraw_authority_file.path.clone()),
+            administrator : Some(administrator_file.path.clone()),
+            new_administrator : Some(new_administrator_file.path.clone()),
+            ..Rotation::default()
+        };
+
+        assert_eq!(
+            plan(&compromised, &exposures, &replacements).err(),
+            Some(format!(
+                "--new-operational-authority must be supplied to replace the operational authority of {}",
+                operated
+            ))
+        );
+
+        replacements.new_operational_authority = Some(compromised_file.path.clone());
+
+        assert_eq!(
+            plan(&compromised, &exposures, &replacements).err(),
+            Some("The new operational authority cannot be the compromised key".to_string())
+        );
+
+        replacements.new_operational_authority = Some(other.to_string());
+
+        let (steps, unsigned) = plan(&compromised, &exposures, &replacements).unwrap();
+        assert!(unsigned.is_empty());
+
+        let mut steps : Vec<(String, Command)> =
+            steps.into_iter().map(|(_, (_, vote_account, command))| (vote_account, command)).collect();
+        steps.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut expected = vec![
+            (administered.to_string(), Command::SetAdministrator {
+                withdraw_authority : withdraw_authority_file.path.clone(),
+                administrator : new_administrator_file.path.clone()
+            }),
+            (operated.to_string(), Command::SetOperationalAuthority {
+                administrator : administrator_file.path.clone(),
+                authority : other.to_string()
+            }),
+        ];
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(format!("{:?}", steps), format!("{:?}", expected));
+    }
+
+    #[test]
+    fn finds_and_revokes_the_vote_authority_and_validator_identity()
+    {
+        let rpc = MockRpc::default();
+        let program_id = Pubkey::new_unique();
+        let compromised = Pubkey::new_unique();
+        let administrator = KeypairFile::new();
+        let operational_authority = KeypairFile::new();
+        let new_identity = KeypairFile::new();
+        let other = Pubkey::new_unique();
+
+        // Puts a vote account with the given identity and vote authority under program control
+        let manage_voting = |[node_pubkey, authorized_voter] : [Pubkey; 2], rewards_authority : Pubkey| {
+            let vote_account = Pubkey::new_unique();
+            let manager_state_account = get_vote_account_manager_state_account(&program_id, &vote_account);
+            let roles = [node_pubkey, authorized_voter, manager_state_account];
+            rpc.set_vote_account_roles(vote_account, roles, 5, 1_000_000_000);
+            let roles = [other, administrator.pubkey(), operational_authority.pubkey(), rewards_authority];
+            rpc.set_manager_state_roles(program_id, manager_state_account, roles, None, 0);
+            vote_account
+        };
+
+        let voted = manage_voting([other, compromised], other);
+        // Roles in the manager state and in the vote account are reported together
+        let identified = manage_voting([compromised, other], compromised);
+
+        // Vote accounts outside of the program are not the program's concern
+        rpc.set_vote_account_roles(Pubkey::new_unique(), [compromised, compromised, other], 5, 1_000_000_000);
+
+        let exposures = find(&rpc, &program_id, &compromised).unwrap();
+
+        let found : Vec<(Pubkey, Vec<Role>)> =
+            exposures.iter().map(|exposure| (exposure.vote_account, exposure.roles.clone())).collect();
+
+        let mut expected = vec![
+            (voted, vec![Role::VoteAuthority]),
+            (identified, vec![Role::RewardsAuthority, Role::ValidatorIdentity]),
+        ];
+        expected.sort_by_key(|(vote_account, _)| *vote_account);
+
+        assert_eq!(found, expected);
+
+        let report = report(&compromised, &exposures, 100);
+        assert!(report.contains(&format!("  Vote authority:        {} (compromised)\n", compromised)));
+        assert!(report.contains(&format!("  Validator identity:    {} (compromised)\n", compromised)));
+        assert!(report.contains("Replace the vote authority with 'set-vote-authority', signed by the operational"));
+
+        assert_eq!(replacement_flags(&exposures), vec![
+            "--new-rewards-authority",
+            "--new-vote-authority",
+            "--new-validator-identity"
+        ]);
+
+        let replacements = Rotation {
+            administrator : Some(administrator.path.clone()),
+            operational_authority : Some(operational_authority.path.clone()),
+            new_rewards_authority : Some(other.to_string()),
+            new_vote_authority : Some(other.to_string()),
+            new_validator_identity : Some(new_identity.path.clone()),
+            ..Rotation::default()
+        };
+
+        let (steps, unsigned) = plan(&compromised, &exposures, &replacements).unwrap();
+        assert!(unsigned.is_empty());
+
+        let steps : Vec<(String, Command)> =
+            steps.into_iter().map(|(_, (_, vote_account, command))| (vote_account, command)).collect();
+
+        let mut expected = vec![
+            (voted.to_string(), vec![Command::SetVoteAuthority {
+                operational_authority : operational_authority.path.clone(),
+                authority : other.to_string()
+            }]),
+            (identified.to_string(), vec![
+                Command::SetRewardsAuthority {
+                    administrator : administrator.path.clone(),
+                    authority : other.to_string()
+                },
+                Command::SetValidatorIdentity {
+                    operational_authority : operational_authority.path.clone(),
+                    validator_identity : new_identity.path.clone()
+                },
+            ]),
+        ];
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let expected = expected
+            .into_iter()
+            .flat_map(|(vote_account, commands)| {
+                commands.into_iter().map(move |command| (vote_account.clone(), command))
+            })
+            .collect::<Vec<(String, Command)>>();
+
+        assert_eq!(format!("{:?}", steps), format!("{:?}", expected));
+    }
+
+    #[test]
+    fn vote_accounts_which_cannot_be_signed_for_are_reported()
+    {
+        let rpc = MockRpc::default();
+        let program_id = Pubkey::new_unique();
+        let compromised = Pubkey::new_unique();
+        let withdraw_authority = KeypairFile::new();
+        let other = Pubkey::new_unique();
+
+        // The compromised administrator of two vote accounts, only one of whose withdraw authorities is at hand
+        let signable = manage(&rpc, &program_id, [withdraw_authority.pubkey(), compromised, other, other], None);
+        let unsignable = manage(&rpc, &program_id, [other, compromised, other, other], None);
+
+        let exposures = find(&rpc, &program_id, &compromised).unwrap();
+
+        let replacements = Rotation {
+            withdraw_authority : Some(withdraw_authority.path.clone()),
+            new_administrator : Some(Pubkey::new_unique().to_string()),
+            ..Rotation::default()
+        };
+
+        let (steps, unsigned) = plan(&compromised, &exposures, &replacements).unwrap();
+
+        assert_eq!(steps.iter().map(|(_, (_, vote_account, _))| vote_account.clone()).collect::<Vec<String>>(), vec![
+            signable.to_string()
+        ]);
+        assert_eq!(unsigned, vec![(
+            unsignable,
+            format!(
+                "--withdraw-authority {} holds {}, but the withdraw authority is {}",
+                withdraw_authority.path,
+                withdraw_authority.pubkey(),
+                other
+            )
+        )]);
+    }
+}
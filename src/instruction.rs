@@ -0,0 +1,230 @@
+// Construction of vamp program instructions, with the accounts that the program requires for each
+
+use crate::transaction_data::VampInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+const SYSTEM_PROGRAM_PUBKEY : &str = "11111111111111111111111111111111";
+const VOTE_PROGRAM_PUBKEY : &str = "Vote111111111111111111111111111111111111111";
+const CLOCK_SYSVAR_PUBKEY : &str = "SysvarC1ock11111111111111111111111111111111";
+
+// Compute the vote account manager account as a PDA of the program, with the seed being the vote account
+pub fn get_vote_account_manager_state_account(
+    program_id : &Pubkey,
+    vote_account : &Pubkey
+) -> Pubkey
+{
+    Pubkey::find_program_address(&[vote_account.to_bytes().as_slice()], program_id).0
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn enter(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    funding_account : Pubkey,
+    withdraw_authority : Pubkey,
+    administrator : Pubkey,
+    commission_caps : Option<(u8, u8)>
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
+        // Funding Account
+        AccountMeta { pubkey : funding_account, is_signer : true, is_writable : true },
+        // Current Withdraw Authority
+        AccountMeta { pubkey : withdraw_authority, is_signer : true, is_writable : false },
+        // System Program Id
+        AccountMeta {
+            pubkey : Pubkey::from_str(SYSTEM_PROGRAM_PUBKEY).unwrap(),
+            is_signer : false,
+            is_writable : false
+        },
+        // Vote Program Id
+        AccountMeta { pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+        // Clock Sysvar Id
+        AccountMeta { pubkey : Pubkey::from_str(CLOCK_SYSVAR_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+    ];
+
+    let data = VampInstruction::Enter {
+        administrator : administrator.to_bytes(),
+
+        use_commission_caps : commission_caps.is_some(),
+
+        max_commission : commission_caps.map(|caps| caps.0).unwrap_or(0),
+
+        max_commission_increase_per_epoch : commission_caps.map(|caps| caps.1).unwrap_or(0)
+    };
+
+    Instruction { program_id, accounts, data : data.encode() }
+}
+
+pub fn set_leave_epoch(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    withdraw_authority : Pubkey,
+    leave_epoch : u64
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
+        // Withdraw Authority
+        AccountMeta { pubkey : withdraw_authority, is_signer : true, is_writable : false },
+    ];
+
+    Instruction { program_id, accounts, data : VampInstruction::SetLeaveEpoch { leave_epoch }.encode() }
+}
+
+pub fn leave(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    withdraw_authority : Pubkey,
+    recipient : Pubkey
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
+        // Withdraw Authority
+        AccountMeta { pubkey : withdraw_authority, is_signer : true, is_writable : false },
+        // Lamports Recipient
+        AccountMeta { pubkey : recipient, is_signer : false, is_writable : true },
+        // Vote Program Id
+        AccountMeta { pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+        // Clock Sysvar Id
+        AccountMeta { pubkey : Pubkey::from_str(CLOCK_SYSVAR_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+    ];
+
+    Instruction { program_id, accounts, data : VampInstruction::Leave.encode() }
+}
+
+// Builds a SetAdministrator, SetOperationalAuthority, or SetRewardsAuthority instruction.  authority is the withdraw
+// authority for SetAdministrator, and the administrator otherwise.
+pub fn set_authority(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    authority : Pubkey,
+    data : VampInstruction
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : false },
+        // Authority
+        AccountMeta { pubkey : authority, is_signer : true, is_writable : false },
+    ];
+
+    Instruction { program_id, accounts, data : data.encode() }
+}
+
+pub fn set_vote_authority(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    operational_authority : Pubkey,
+    new_authority : Pubkey
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : false },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
+        // Operational Authority
+        AccountMeta { pubkey : operational_authority, is_signer : true, is_writable : false },
+        // Vote Program Id
+        AccountMeta { pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+        // Clock Sysvar Id
+        AccountMeta { pubkey : Pubkey::from_str(CLOCK_SYSVAR_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+    ];
+
+    let data = VampInstruction::SetVoteAuthority { new_authority : new_authority.to_bytes() };
+
+    Instruction { program_id, accounts, data : data.encode() }
+}
+
+pub fn set_validator_identity(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    operational_authority : Pubkey,
+    new_identity : Pubkey
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : false },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
+        // Operational Authority
+        AccountMeta { pubkey : operational_authority, is_signer : true, is_writable : false },
+        // New Validator Identity
+        AccountMeta { pubkey : new_identity, is_signer : true, is_writable : false },
+        // Vote Program Id
+        AccountMeta { pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+    ];
+
+    Instruction { program_id, accounts, data : VampInstruction::SetValidatorIdentity.encode() }
+}
+
+// A lamports value of 0 withdraws everything that can be withdrawn
+pub fn withdraw(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    rewards_authority : Pubkey,
+    recipient : Pubkey,
+    lamports : u64
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : false },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
+        // Rewards Authority
+        AccountMeta { pubkey : rewards_authority, is_signer : true, is_writable : false },
+        // Recipient
+        AccountMeta { pubkey : recipient, is_signer : false, is_writable : true },
+        // Vote Program Id
+        AccountMeta { pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+    ];
+
+    Instruction { program_id, accounts, data : VampInstruction::Withdraw { lamports }.encode() }
+}
+
+pub fn set_commission(
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    rewards_authority : Pubkey,
+    new_commission : u8
+) -> Instruction
+{
+    let accounts = vec![
+        // Vote Account Manager State Account
+        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
+        // Vote Account
+        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
+        // Rewards Authority
+        AccountMeta { pubkey : rewards_authority, is_signer : true, is_writable : false },
+        // Vote Program Id
+        AccountMeta { pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(), is_signer : false, is_writable : false },
+    ];
+
+    Instruction { program_id, accounts, data : VampInstruction::SetCommission { new_commission }.encode() }
+}
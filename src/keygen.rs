@@ -0,0 +1,254 @@
+// Generation of the authority keypairs of a new validator, for 'solana-vamp keygen'.  Each authority gets its own
+// keypair file, readable only by its owner, and a profile is written that points the solana-vamp arguments at them.
+// Nothing is overwritten: if any of the files already exists, nothing is written at all.  Each file is created only if
+// it does not exist, so that one which appears while keygen runs is not overwritten either.
+
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use solana_sdk::signer::keypair::{keypair_from_seed, write_keypair, Keypair};
+use solana_sdk::signer::Signer;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// The name of the profile written alongside the keypair files
+const PROFILE_FILE : &str = "profile";
+
+// (authority, keypair file, the argument that the profile sets to the keypair file if any)
+const KEYS : &[(&str, &str, Option<&str>)] = &[
+    ("administrator", "administrator.json", Some("administrator")),
+    ("operational authority", "operational_authority.json", Some("operational-authority")),
+    ("rewards authority", "rewards_authority.json", Some("rewards-authority")),
+    ("vote authority", "vote_authority.json", None)
+];
+
+// A new keypair, and the seed phrase from which it can be recovered if one was asked for.  As with solana-keygen, the
+// seed phrase has no passphrase.
+fn new_keypair(seed_phrase : bool) -> (Keypair, Option<String>)
+{
+    if !seed_phrase {
+        return (Keypair::new(), None);
+    }
+
+    let mnemonic = Mnemonic::new(MnemonicType::Words12, Language::English);
+
+    let keypair = keypair_from_seed(Seed::new(&mnemonic, "").as_bytes()).unwrap();
+
+    (keypair, Some(mnemonic.phrase().to_string()))
+}
+
+// Creates a file which does not already exist, readable only by its owner if private
+fn create_new(
+    path : &Path,
+    private : bool
+) -> Result<File, String>
+{
+    let mut options = OpenOptions::new();
+
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))
+}
+
+// Generates the keypairs into out_dir, which is created if necessary, writes the profile, and reports each keypair
+// and any seed phrase, followed by the profile, to output
+pub fn generate(
+    out_dir : &str,
+    seed_phrases : bool,
+    output : &mut dyn Write
+) -> Result<(), String>
+{
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create {}: {}", out_dir, e))?;
+
+    // The profile is used from other directories, so it must give absolute paths
+    let out_dir = Path::new(out_dir).canonicalize().map_err(|e| format!("Failed to locate {}: {}", out_dir, e))?;
+
+    let paths = KEYS.iter().map(|(_, file, _)| out_dir.join(file)).collect::<Vec<_>>();
+
+    let profile_path = out_dir.join(PROFILE_FILE);
+
+    if let Some(existing) = paths.iter().chain(std::iter::once(&profile_path)).find(|path| path.exists()) {
+        return Err(format!("{} already exists; keygen never overwrites files", existing.display()));
+    }
+
+    let mut profile = String::from(
+        "# solana-vamp profile written by 'solana-vamp keygen'.  To use it, copy it to\n# \
+         ~/.config/solana-vamp/profiles/<PROFILE> and select it with --profile <PROFILE>.\n"
+    );
+
+    let mut report = String::new();
+
+    // The files written so far, which are removed again if a later one cannot be written
+    let mut written : Vec<&PathBuf> = vec![];
+
+    let undo = |written : &[&PathBuf], e : String| {
+        written.iter().for_each(|path| {
+            let _ = std::fs::remove_file(path);
+        });
+        e
+    };
+
+    for ((authority, _, setting), path) in KEYS.iter().zip(&paths) {
+        let (keypair, seed_phrase) = new_keypair(seed_phrases);
+
+        let mut file = create_new(path, true).map_err(|e| undo(&written, e))?;
+
+        written.push(path);
+
+        write_keypair(&keypair, &mut file)
+            .map_err(|e| undo(&written, format!("Failed to write {}: {}", path.display(), e)))?;
+
+        report.push_str(&format!("Wrote {} {} to {}\n", authority, keypair.pubkey(), path.display()));
+
+        if let Some(seed_phrase) = seed_phrase {
+            report.push_str(&format!("  Seed phrase: {}\n", seed_phrase));
+        }
+
+        match setting {
+            Some(setting) => profile.push_str(&format!("{} = {}\n", setting, path.display())),
+            // The vote authority is only ever the new value of set-vote-authority, which is never taken from a profile
+            None => profile.push_str(&format!(
+                "# The {} is not a profile setting; set it with:\n#   solana-vamp set-vote-authority --vote-authority \
+                 {}\n",
+                authority,
+                path.display()
+            ))
+        }
+    }
+
+    create_new(&profile_path, false)
+        .and_then(|mut file| {
+            file.write_all(profile.as_bytes()).map_err(|e| format!("Failed to write {}: {}", profile_path.display(), e))
+        })
+        .map_err(|e| undo(&written, e))?;
+
+    if seed_phrases {
+        report.push_str(
+            "\nThe seed phrases are shown only this once.  Write them down and keep them somewhere safe; anyone who \
+             has them holds the authorities.\n"
+        );
+    }
+
+    report.push_str(&format!("\nWrote profile {}:\n\n{}", profile_path.display(), profile));
+
+    output.write_all(report.as_bytes()).map_err(|e| format!("Failed to write output: {}", e))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::load_keypair_helper;
+    use solana_sdk::signer::keypair::keypair_from_seed_phrase_and_passphrase;
+
+    // A directory for the keys, within a directory that is removed when dropped
+    struct OutDir
+    {
+        root : PathBuf,
+
+        keys : String
+    }
+
+    impl Drop for OutDir
+    {
+        fn drop(&mut self)
+        {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn out_dir(name : &str) -> OutDir
+    {
+        let root = std::env::temp_dir().join(format!("solana-vamp-keygen-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let keys = root.join("keys").to_str().unwrap().to_string();
+        OutDir { root, keys }
+    }
+
+    #[test]
+    fn writes_private_keypairs_and_a_profile()
+    {
+        let dir = out_dir("profile");
+        let mut output = vec![];
+
+        generate(&dir.keys, false, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let out_dir = Path::new(&dir.keys).canonicalize().unwrap();
+        let profile = std::fs::read_to_string(out_dir.join(PROFILE_FILE)).unwrap();
+
+        for (_, file, setting) in KEYS {
+            let path = out_dir.join(file);
+            let pubkey = load_keypair_helper(path.to_str().unwrap()).unwrap().pubkey();
+            assert!(output.contains(&format!("{} to {}\n", pubkey, path.display())));
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+            }
+
+            if let Some(setting) = setting {
+                assert!(profile.contains(&format!("\n{} = {}\n", setting, path.display())));
+            }
+        }
+
+        assert!(output.ends_with(&profile));
+        assert!(!output.contains("Seed phrase"));
+
+        // Generating again is refused rather than overwriting the keypairs
+        let administrator = std::fs::read(out_dir.join("administrator.json")).unwrap();
+        assert_eq!(
+            generate(out_dir.to_str().unwrap(), false, &mut vec![]).err(),
+            Some(format!(
+                "{} already exists; keygen never overwrites files",
+                out_dir.join("administrator.json").display()
+            ))
+        );
+        assert_eq!(std::fs::read(out_dir.join("administrator.json")).unwrap(), administrator);
+    }
+
+    #[test]
+    fn files_which_exist_are_never_overwritten()
+    {
+        let dir = out_dir("existing");
+
+        std::fs::create_dir_all(&dir.keys).unwrap();
+
+        let existing = Path::new(&dir.keys).join("rewards_authority.json");
+
+        std::fs::write(&existing, "existing").unwrap();
+
+        let error = create_new(&existing, true).unwrap_err();
+        assert!(error.starts_with(&format!("Failed to create {}: ", existing.display())), "{}", error);
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "existing");
+
+        // Nothing is written when any of the files exists
+        assert!(generate(&dir.keys, false, &mut vec![]).is_err());
+        assert_eq!(std::fs::read_dir(&dir.keys).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn seed_phrases_recover_the_keypairs()
+    {
+        let dir = out_dir("seed-phrases");
+        let mut output = vec![];
+
+        generate(&dir.keys, true, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<&str>>();
+
+        for (i, (_, file, _)) in KEYS.iter().enumerate() {
+            let seed_phrase = lines[(i * 2) + 1].strip_prefix("  Seed phrase: ").unwrap();
+            let recovered = keypair_from_seed_phrase_and_passphrase(seed_phrase, "").unwrap();
+            let path = Path::new(&dir.keys).join(file);
+            assert_eq!(load_keypair_helper(path.to_str().unwrap()).unwrap().pubkey(), recovered.pubkey());
+        }
+    }
+}
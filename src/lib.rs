@@ -0,0 +1,7 @@
+// Library interface to solana-vamp, for tools which build or parse Vote Account Manager program instructions and
+// state
+
+pub mod instruction;
+pub mod model;
+pub mod state;
+pub mod transaction_data;
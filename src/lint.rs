@@ -0,0 +1,360 @@
+// Checks of managed vote accounts for key reuse and risky settings, for 'solana-vamp lint'.  Each rule warns by
+// default, and may be allowed, which silences it, or denied, which makes its findings errors so that 'solana-vamp lint'
+// exits with a failure status and can gate CI.
+
+use crate::spec::LINT_RULES;
+use crate::ManagedAccount;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level
+{
+    Allow,
+
+    Warn,
+
+    Deny
+}
+
+pub struct Finding
+{
+    pub rule : &'static str,
+
+    pub level : Level,
+
+    // None for findings about the fleet as a whole
+    pub vote_account : Option<Pubkey>,
+
+    pub message : String
+}
+
+// Works out the level of each rule from the --allow and --deny arguments, in LINT_RULES order.  "all" names every rule,
+// and a rule named by itself takes precedence over "all".
+pub fn levels(
+    allow : &[String],
+    deny : &[String]
+) -> Result<Vec<(&'static str, Level)>, String>
+{
+    let named = |names : &[String], rule : &str| names.iter().any(|name| name == rule);
+
+    if named(allow, "all") && named(deny, "all") {
+        return Err("--allow all and --deny all may not both be given".to_string());
+    }
+
+    LINT_RULES
+        .iter()
+        .map(|rule| match (named(allow, rule), named(deny, rule)) {
+            (true, true) => Err(format!("--allow and --deny both name {}", rule)),
+            (true, false) => Ok((*rule, Level::Allow)),
+            (false, true) => Ok((*rule, Level::Deny)),
+            (false, false) if named(allow, "all") => Ok((*rule, Level::Allow)),
+            (false, false) if named(deny, "all") => Ok((*rule, Level::Deny)),
+            (false, false) => Ok((*rule, Level::Warn))
+        })
+        .collect()
+}
+
+// The VAMP roles of a managed vote account held by each key, in order of the roles
+fn roles_by_key(account : &ManagedAccount) -> BTreeMap<Pubkey, Vec<&'static str>>
+{
+    let manager_state = &account.manager_state;
+
+    let mut roles : BTreeMap<Pubkey, Vec<&'static str>> = BTreeMap::new();
+
+    for (pubkey, role) in [
+        (manager_state.withdraw_authority, "withdraw authority"),
+        (manager_state.administrator, "administrator"),
+        (manager_state.operational_authority, "operational authority"),
+        (manager_state.rewards_authority, "rewards authority")
+    ] {
+        roles.entry(pubkey).or_default().push(role);
+    }
+
+    roles
+}
+
+// Applies every rule that is not allowed to the managed vote accounts, the vote accounts that turned out not to be
+// managed, and the fee payer if one is known
+pub fn check(
+    levels : &[(&'static str, Level)],
+    accounts : &[ManagedAccount],
+    unmanaged : &[Pubkey],
+    fee_payer : Option<Pubkey>
+) -> Vec<Finding>
+{
+    let mut findings = vec![];
+
+    let mut add = |rule : &'static str, vote_account : Option<Pubkey>, message : String| {
+        let level = levels.iter().find(|(name, _)| *name == rule).map(|(_, level)| *level).unwrap_or(Level::Warn);
+        if level != Level::Allow {
+            findings.push(Finding { rule, level, vote_account, message });
+        }
+    };
+
+    for vote_account in unmanaged {
+        add("unmanaged", Some(*vote_account), "is not managed by the Vote Account Manager program".to_string());
+    }
+
+    for account in accounts {
+        let manager_state = &account.manager_state;
+        let vote_account = Some(account.vote_account);
+
+        for (pubkey, roles) in roles_by_key(account) {
+            if roles.len() > 1 {
+                add("shared-role", vote_account, format!("{} is the {}", pubkey, roles.join(" and the ")));
+            }
+        }
+
+        if fee_payer == Some(manager_state.withdraw_authority) {
+            add(
+                "fee-payer-is-withdraw-authority",
+                vote_account,
+                format!(
+                    "the fee payer {} is the withdraw authority, so the withdraw authority keypair is used for every \
+                     transaction",
+                    manager_state.withdraw_authority
+                )
+            );
+        }
+
+        if account.vote_authority == account.validator_identity {
+            add(
+                "vote-authority-is-validator-identity",
+                vote_account,
+                format!("{} is both the vote authority and the validator identity", account.vote_authority)
+            );
+        }
+
+        if manager_state.commission_caps.is_none() {
+            add(
+                "no-commission-caps",
+                vote_account,
+                "commission caps are not set, so the rewards authority may raise the commission to 100% at once, and \
+                 the vote account may leave the program at any time"
+                    .to_string()
+            );
+        }
+
+        if manager_state.leave_epoch != 0 {
+            add(
+                "leave-epoch-set",
+                vote_account,
+                format!(
+                    "a leave epoch of {} is set, after which the vote account may leave the program",
+                    manager_state.leave_epoch
+                )
+            );
+        }
+    }
+
+    // Key -> vote accounts in which the key holds a role
+    let mut fleet : BTreeMap<Pubkey, Vec<Pubkey>> = BTreeMap::new();
+
+    for account in accounts {
+        for pubkey in roles_by_key(account).into_keys() {
+            fleet.entry(pubkey).or_default().push(account.vote_account);
+        }
+    }
+
+    for (pubkey, vote_accounts) in fleet {
+        if vote_accounts.len() > 1 {
+            let vote_accounts = vote_accounts.iter().map(|vote_account| vote_account.to_string()).collect::<Vec<_>>();
+            add(
+                "shared-key",
+                None,
+                format!(
+                    "{} holds roles in {} vote accounts: {}",
+                    pubkey,
+                    vote_accounts.len(),
+                    vote_accounts.join(", ")
+                )
+            );
+        }
+    }
+
+    findings
+}
+
+fn level_name(level : Level) -> &'static str
+{
+    match level {
+        Level::Allow => "allowed",
+        Level::Warn => "warning",
+        Level::Deny => "error"
+    }
+}
+
+fn count(
+    findings : &[Finding],
+    level : Level
+) -> usize
+{
+    findings.iter().filter(|finding| finding.level == level).count()
+}
+
+// One line per finding, followed by a summary line
+pub fn report(
+    findings : &[Finding],
+    vote_account_count : usize
+) -> String
+{
+    if findings.is_empty() {
+        return match vote_account_count {
+            1 => "No problems found in 1 vote account\n".to_string(),
+            n => format!("No problems found in {} vote accounts\n", n)
+        };
+    }
+
+    let mut report = String::new();
+
+    for finding in findings {
+        report.push_str(&format!("{}[{}]: ", level_name(finding.level), finding.rule));
+        if let Some(vote_account) = &finding.vote_account {
+            report.push_str(&format!("{}: ", vote_account));
+        }
+        report.push_str(&format!("{}\n", finding.message));
+    }
+
+    let plural = |n : usize, what : &str| format!("{} {}{}", n, what, if n == 1 { "" } else { "s" });
+
+    report.push_str(&format!(
+        "{}, {}\n",
+        plural(count(findings, Level::Warn), "warning"),
+        plural(count(findings, Level::Deny), "error")
+    ));
+
+    report
+}
+
+pub fn json(findings : &[Finding]) -> String
+{
+    let findings_json = findings
+        .iter()
+        .map(|finding| {
+            let mut json = format!("{{\"rule\":\"{}\",\"level\":\"{}\"", finding.rule, level_name(finding.level));
+            if let Some(vote_account) = &finding.vote_account {
+                json.push_str(&format!(",\"vote_account\":\"{}\"", vote_account));
+            }
+            json.push_str(&format!(",\"message\":\"{}\"}}", finding.message));
+            json
+        })
+        .collect::<Vec<String>>();
+
+    format!(
+        "{{\"findings\":[{}],\"warnings\":{},\"errors\":{}}}",
+        findings_json.join(","),
+        count(findings, Level::Warn),
+        count(findings, Level::Deny)
+    )
+}
+
+// Whether any finding is an error, which fails the lint
+pub fn failed(findings : &[Finding]) -> bool
+{
+    count(findings, Level::Deny) > 0
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use solana_vamp::state::ManagerState;
+
+    fn account(
+        roles : [Pubkey; 4],
+        commission_caps : Option<(u8, u8)>,
+        leave_epoch : u64
+    ) -> ManagedAccount
+    {
+        ManagedAccount {
+            vote_account : Pubkey::new_unique(),
+            manager_state : ManagerState {
+                withdraw_authority : roles[0],
+                administrator : roles[1],
+                operational_authority : roles[2],
+                rewards_authority : roles[3],
+                commission_caps,
+                leave_epoch
+            },
+            vote_authority : Pubkey::new_unique(),
+            validator_identity : Pubkey::new_unique()
+        }
+    }
+
+    fn rules(findings : &[Finding]) -> Vec<(&'static str, Level)>
+    {
+        findings.iter().map(|finding| (finding.rule, finding.level)).collect()
+    }
+
+    fn strings(names : &[&str]) -> Vec<String>
+    {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn well_separated_accounts_pass()
+    {
+        let accounts = [[(); 4].map(|_| Pubkey::new_unique()), [(); 4].map(|_| Pubkey::new_unique())]
+            .map(|roles| account(roles, Some((10, 1)), 0));
+
+        let levels = levels(&[], &[]).unwrap();
+        let findings = check(&levels, &accounts, &[], Some(Pubkey::new_unique()));
+
+        assert!(findings.is_empty());
+        assert_eq!(report(&findings, 2), "No problems found in 2 vote accounts\n");
+        assert!(!failed(&findings));
+    }
+
+    #[test]
+    fn every_rule_is_applied()
+    {
+        let shared = Pubkey::new_unique();
+        let withdraw_authority = Pubkey::new_unique();
+        let mut first = account([withdraw_authority, shared, Pubkey::new_unique(), shared], None, 0);
+        first.validator_identity = first.vote_authority;
+        let second =
+            account([Pubkey::new_unique(), shared, Pubkey::new_unique(), Pubkey::new_unique()], Some((10, 1)), 50);
+        let unmanaged = Pubkey::new_unique();
+
+        let levels = levels(&[], &[]).unwrap();
+        let findings = check(&levels, &[first, second], &[unmanaged], Some(withdraw_authority));
+
+        assert_eq!(rules(&findings), vec![
+            ("unmanaged", Level::Warn),
+            ("shared-role", Level::Warn),
+            ("fee-payer-is-withdraw-authority", Level::Warn),
+            ("vote-authority-is-validator-identity", Level::Warn),
+            ("no-commission-caps", Level::Warn),
+            ("leave-epoch-set", Level::Warn),
+            ("shared-key", Level::Warn),
+        ]);
+        assert_eq!(findings[1].message, format!("{} is the administrator and the rewards authority", shared));
+        assert!(report(&findings, 3).ends_with("\n7 warnings, 0 errors\n"));
+        assert!(!failed(&findings));
+    }
+
+    #[test]
+    fn rules_may_be_allowed_or_denied()
+    {
+        assert_eq!(
+            levels(&strings(&["all"]), &strings(&["all"])).err().unwrap(),
+            "--allow all and --deny all may not both be given"
+        );
+        assert_eq!(
+            levels(&strings(&["shared-key"]), &strings(&["shared-key"])).err().unwrap(),
+            "--allow and --deny both name shared-key"
+        );
+
+        let shared = Pubkey::new_unique();
+        let accounts = [account([shared; 4], None, 5)];
+
+        // A rule named by itself takes precedence over all
+        let levels = levels(&strings(&["no-commission-caps"]), &strings(&["all"])).unwrap();
+        let findings = check(&levels, &accounts, &[], None);
+
+        assert_eq!(rules(&findings), vec![("shared-role", Level::Deny), ("leave-epoch-set", Level::Deny)]);
+        assert!(failed(&findings));
+        assert!(json(&findings).ends_with(",\"warnings\":0,\"errors\":2}"));
+    }
+}
@@ -1,209 +1,235 @@
+mod amount;
 mod args;
-mod transaction_data;
+mod batch;
+mod commission;
+mod completions;
+mod daemon;
+mod decode;
+mod describe;
+mod environment;
+mod fixture;
+mod graph;
+mod history;
+mod incident;
+mod keygen;
+mod lint;
+mod man;
+mod ramp;
+mod rotate;
+mod rpc;
+mod spec;
 mod usage;
+mod wizard;
 
-use args::Command;
+use amount::{format_sol, Amount};
+use args::{Command, RpcFixture};
+use ramp::RampState;
+use rpc::Rpc;
 use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::hash::Hash;
-use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::keypair::Keypair;
 use solana_sdk::signer::Signer;
+use solana_sdk::stake;
+use solana_sdk::stake::instruction as stake_instruction;
+use solana_sdk::stake::state::{Authorized, Lockup, StakeState};
 use solana_sdk::transaction::Transaction;
+use solana_sdk::vote::state::VoteState;
+use solana_vamp::instruction::{self, get_vote_account_manager_state_account};
+use solana_vamp::state::ManagerState;
+use solana_vamp::transaction_data::VampInstruction;
 use std::str::FromStr;
-use zerocopy::AsBytes;
+use std::time::Duration;
 
 const VAMP_PROGRAM_PUBKEY : &str = "vamp3angna1CBRcV6KqoxyaYw3mPybHEeoPLtmpS99N";
-const SYSTEM_PROGRAM_PUBKEY : &str = "11111111111111111111111111111111";
-const VOTE_PROGRAM_PUBKEY : &str = "Vote111111111111111111111111111111111111111";
-const CLOCK_SYSVAR_PUBKEY : &str = "SysvarC1ock11111111111111111111111111111111";
 
-const LAMPORTS_PER_SOL : f64 = 1e9;
+// Maximum number of accounts that may be requested in a single getMultipleAccounts call
+const MAX_MULTIPLE_ACCOUNTS : usize = 100;
 
-fn error_exit(err : &str) -> !
+// How often to check for a new epoch when waiting for one, if not specified by --poll-interval
+const DEFAULT_POLL_INTERVAL_SECONDS : u64 = 60;
+
+// Number of most recent transactions examined by the history command, if not specified by --limit
+const DEFAULT_HISTORY_LIMIT : usize = 100;
+
+// A vote account managed by the program, with the authorities of the vote account itself
+struct ManagedAccount
 {
-    eprintln!("{}\n", err);
+    vote_account : Pubkey,
 
-    eprintln!("Try 'solana-vamp help' for help.");
+    manager_state : ManagerState,
 
-    std::process::exit(-1)
-}
+    vote_authority : Pubkey,
 
-#[cfg(target_endian = "big")]
-fn exit_if_big_endian()
-{
-    error_exit("solana-vamp is currently unsupported on big endian CPU architectures, sorry.");
+    validator_identity : Pubkey
 }
 
-#[cfg(not(target_endian = "big"))]
-fn exit_if_big_endian()
+fn error_exit(err : &str) -> !
 {
+    eprintln!("{}\n", err);
+
+    eprintln!("Try 'solana-vamp help' for help.");
+
+    std::process::exit(-1)
 }
 
 fn main()
 {
-    // At the moment this program doesn't work on big endian because of the byte ordering of as_bytes data.
-    // It's unclear if there will ever be a big endian system that solana-vamp is compiled for, so punting on
-    // the issue for now.
-    exit_if_big_endian();
-
-    let (fee_payer, rpc_url, commitment, vote_account, command) = args::parse_command();
+    let (fee_payer, rpc_url, commitment, rpc_fixture, vote_account, command) = args::parse_command();
 
     let commitment = commitment
         .as_ref()
-        .map(|commitment| CommitmentConfig::from_str(commitment).expect(&format!("Invalid commitment {}", commitment)))
+        .map(|commitment| {
+            CommitmentConfig::from_str(commitment)
+                .unwrap_or_else(|_| error_exit(&format!("Invalid commitment {}", commitment)))
+        })
         .unwrap_or(CommitmentConfig::default());
 
-    let rpc_client = RpcClient::new_with_commitment(rpc_url, commitment);
+    let rpc_client = match rpc_fixture {
+        None => RpcClient::new_with_commitment(rpc_url.clone(), commitment),
+        Some(RpcFixture::Record(path)) => fixture::record(&path, rpc_url.clone(), commitment),
+        Some(RpcFixture::Replay(path)) => fixture::replay(&path, commitment)
+    };
+
+    run_command(&rpc_client, &rpc_url, fee_payer, vote_account, command)
+}
 
+// Runs a parsed command, making its RPC calls through rpc_client
+fn run_command(
+    rpc_client : &dyn Rpc,
+    rpc_url : &str,
+    fee_payer : Option<String>,
+    vote_account : Option<String>,
+    command : Command
+)
+{
     // Load fee_payer keypair
     let fee_payer = fee_payer.map(|fee_payer| load_keypair(&fee_payer, "fee payer"));
 
     // vamp program pubkey
     let program_id = Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap();
 
-    // vote_account pubkey
-    let vote_account = make_pubkey(&vote_account, "vote account");
+    // vote_account pubkey, for commands that operate on a single vote account
+    let vote_account = vote_account.map(|vote_account| make_pubkey(&vote_account, "vote account"));
 
-    // Compute the vote account manager account as a PDA of the program, with the seed being the vote account
-    let (vote_account_manager_state_account, _) =
-        Pubkey::find_program_address(&[vote_account.to_bytes().as_slice()], &program_id);
+    let vote_account_manager_state_account =
+        vote_account.map(|vote_account| get_vote_account_manager_state_account(&program_id, &vote_account));
 
     match command {
+        Command::Keygen { out_dir, seed_phrases } => {
+            keygen::generate(&out_dir, seed_phrases, &mut std::io::stdout()).unwrap_or_else(|e| error_exit(&e))
+        },
         Command::Enter { withdraw_authority, administrator, max_commission, max_commission_increase_per_epoch } => {
-            let fee_payer = fee_payer.unwrap();
-
-            let withdraw_authority = load_keypair(&withdraw_authority, "withdraw authority");
-
-            let accounts = vec![
-                // Vote Account Manager State Account
-                AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
-                // Vote Account
-                AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
-                // Funding Account
-                AccountMeta { pubkey : fee_payer.pubkey(), is_signer : true, is_writable : true },
-                // Current Withdraw Authority
-                AccountMeta { pubkey : withdraw_authority.pubkey(), is_signer : true, is_writable : false },
-                // System Program Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(SYSTEM_PROGRAM_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-                // Vote Program Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-                // Clock Sysvar Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(CLOCK_SYSVAR_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-            ];
-
-            let data = transaction_data::EnterData {
-                instruction_code : 0,
-
-                administrator : make_pubkey(&administrator, "administrator").to_bytes(),
-
-                use_commission_caps : max_commission.is_some() || max_commission_increase_per_epoch.is_some(),
-
-                max_commission : max_commission.unwrap_or(0),
-
-                max_commission_increase_per_epoch : max_commission_increase_per_epoch.unwrap_or(0)
+            enter(
+                rpc_client,
+                program_id,
+                vote_account.unwrap(),
+                &fee_payer.unwrap(),
+                &load_keypair(&withdraw_authority, "withdraw authority"),
+                make_pubkey(&administrator, "administrator"),
+                commission_caps(max_commission, max_commission_increase_per_epoch)
+            )
+        },
+        Command::EnterInteractive {
+            withdraw_authority,
+            administrator,
+            max_commission,
+            max_commission_increase_per_epoch,
+            fee_payer
+        } => {
+            let supplied = wizard::EnterArgs {
+                vote_account,
+                withdraw_authority,
+                administrator,
+                max_commission,
+                max_commission_increase_per_epoch,
+                fee_payer
             };
 
-            let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
-
-            let tx = Transaction::new_signed_with_payer(
-                &[instruction],
-                Some(&fee_payer.pubkey()),
-                &[&fee_payer, &withdraw_authority],
-                get_latest_blockhash(&rpc_client)
-            );
-
-            submit_transaction(&rpc_client, &tx)
+            let choices = wizard::enter(
+                rpc_client,
+                &program_id,
+                rpc_url,
+                supplied,
+                &mut std::io::stdin().lock(),
+                &mut std::io::stdout()
+            )
+            .unwrap_or_else(|e| error_exit(&e));
+
+            if let Some(choices) = choices {
+                let withdraw_authority = load_keypair(&choices.withdraw_authority, "withdraw authority");
+
+                // As for enter, the fee payer is the withdraw authority unless --fee-payer was supplied
+                let fee_payer = choices.fee_payer.map(|fee_payer| load_keypair(&fee_payer, "fee payer"));
+
+                enter(
+                    rpc_client,
+                    program_id,
+                    choices.vote_account,
+                    fee_payer.as_ref().unwrap_or(&withdraw_authority),
+                    &withdraw_authority,
+                    choices.administrator,
+                    choices.commission_caps
+                )
+            }
         },
         Command::SetLeaveEpoch { withdraw_authority, leave_epoch } => {
             let fee_payer = fee_payer.unwrap();
 
             let withdraw_authority = load_keypair(&withdraw_authority, "withdraw authority");
 
-            let accounts = vec![
-                // Vote Account Manager State Account
-                AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
-                // Vote Account
-                AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
-                // Withdraw Authority
-                AccountMeta { pubkey : withdraw_authority.pubkey(), is_signer : true, is_writable : false },
-            ];
-
-            let data = transaction_data::SetLeaveEpochData { instruction_code : 1, padding : [0_u8; 7], leave_epoch };
-
-            let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
+            let instruction = instruction::set_leave_epoch(
+                program_id,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
+                withdraw_authority.pubkey(),
+                leave_epoch
+            );
 
             let tx = Transaction::new_signed_with_payer(
                 &[instruction],
                 Some(&fee_payer.pubkey()),
                 &[&fee_payer, &withdraw_authority],
-                get_latest_blockhash(&rpc_client)
+                get_latest_blockhash(rpc_client)
             );
 
-            submit_transaction(&rpc_client, &tx)
+            submit_transaction(rpc_client, &tx)
         },
         Command::Leave { withdraw_authority } => {
             let fee_payer = fee_payer.unwrap();
 
             let withdraw_authority = load_keypair(&withdraw_authority, "withdraw authority");
 
-            let accounts = vec![
-                // Vote Account Manager State Account
-                AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
-                // Vote Account
-                AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
-                // Withdraw Authority
-                AccountMeta { pubkey : withdraw_authority.pubkey(), is_signer : true, is_writable : false },
-                // Lamports Recipient -- assume fee payer
-                AccountMeta { pubkey : fee_payer.pubkey(), is_signer : false, is_writable : true },
-                // Vote Program Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-                // Clock Sysvar Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(CLOCK_SYSVAR_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-            ];
-
-            let data = transaction_data::LeaveData { instruction_code : 2 };
-
-            let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
+            // Lamports recipient -- assume fee payer
+            let instruction = instruction::leave(
+                program_id,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
+                withdraw_authority.pubkey(),
+                fee_payer.pubkey()
+            );
 
             let tx = Transaction::new_signed_with_payer(
                 &[instruction],
                 Some(&fee_payer.pubkey()),
                 &[&fee_payer, &withdraw_authority],
-                get_latest_blockhash(&rpc_client)
+                get_latest_blockhash(rpc_client)
             );
 
-            submit_transaction(&rpc_client, &tx)
+            submit_transaction(rpc_client, &tx)
         },
         Command::SetAdministrator { withdraw_authority, administrator } => {
             set_authority(
                 rpc_client,
                 program_id,
-                vote_account,
-                vote_account_manager_state_account,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
                 fee_payer.unwrap(),
-                3,
+                |new_administrator| VampInstruction::SetAdministrator { new_administrator },
                 &withdraw_authority,
                 "withdraw authority",
                 &administrator,
@@ -214,10 +240,10 @@ fn main()
             set_authority(
                 rpc_client,
                 program_id,
-                vote_account,
-                vote_account_manager_state_account,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
                 fee_payer.unwrap(),
-                4,
+                |new_authority| VampInstruction::SetOperationalAuthority { new_authority },
                 &administrator,
                 "administrator",
                 &authority,
@@ -228,10 +254,10 @@ fn main()
             set_authority(
                 rpc_client,
                 program_id,
-                vote_account,
-                vote_account_manager_state_account,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
                 fee_payer.unwrap(),
-                5,
+                |new_authority| VampInstruction::SetRewardsAuthority { new_authority },
                 &administrator,
                 "administrator",
                 &authority,
@@ -243,44 +269,22 @@ fn main()
 
             let operational_authority = load_keypair(&operational_authority, "operational authority");
 
-            let new_authority = make_pubkey(&authority, "vote authority");
-
-            let accounts = vec![
-                // Vote Account Manager State Account
-                AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : false },
-                // Vote Account
-                AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
-                // Operational Authority
-                AccountMeta { pubkey : operational_authority.pubkey(), is_signer : true, is_writable : false },
-                // Vote Program Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-                // Clock Sysvar Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(CLOCK_SYSVAR_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-            ];
-
-            let data = transaction_data::SetVoteAuthorityData {
-                instruction_code : 6,
-                new_authority : new_authority.to_bytes()
-            };
-
-            let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
+            let instruction = instruction::set_vote_authority(
+                program_id,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
+                operational_authority.pubkey(),
+                make_pubkey(&authority, "vote authority")
+            );
 
             let tx = Transaction::new_signed_with_payer(
                 &[instruction],
                 Some(&fee_payer.pubkey()),
                 &[&fee_payer, &operational_authority],
-                get_latest_blockhash(&rpc_client)
+                get_latest_blockhash(rpc_client)
             );
 
-            submit_transaction(&rpc_client, &tx)
+            submit_transaction(rpc_client, &tx)
         },
         Command::SetValidatorIdentity { operational_authority, validator_identity } => {
             let fee_payer = fee_payer.unwrap();
@@ -289,185 +293,942 @@ fn main()
 
             let new_identity = load_keypair(&validator_identity, "validator identity");
 
-            let accounts = vec![
-                // Vote Account Manager State Account
-                AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : false },
-                // Vote Account
-                AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
-                // Operational Authority
-                AccountMeta { pubkey : operational_authority.pubkey(), is_signer : true, is_writable : false },
-                // New Validator Identity
-                AccountMeta { pubkey : new_identity.pubkey(), is_signer : true, is_writable : false },
-                // Vote Program Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-            ];
-
-            let data = transaction_data::SetValidatorIdentityData { instruction_code : 7 };
-
-            let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
+            let instruction = instruction::set_validator_identity(
+                program_id,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
+                operational_authority.pubkey(),
+                new_identity.pubkey()
+            );
 
             let tx = Transaction::new_signed_with_payer(
                 &[instruction],
                 Some(&fee_payer.pubkey()),
                 &[&fee_payer, &operational_authority, &new_identity],
-                get_latest_blockhash(&rpc_client)
+                get_latest_blockhash(rpc_client)
             );
 
-            submit_transaction(&rpc_client, &tx)
+            submit_transaction(rpc_client, &tx)
+        },
+        Command::Rotate { rotation, export } => {
+            let vote_account = vote_account.unwrap();
+
+            let (manager_state, _) =
+                get_managed_vote_account_state(rpc_client, &vote_account, &vote_account_manager_state_account.unwrap());
+
+            let steps = rotate::plan(&rotation, &vote_account, &manager_state).unwrap_or_else(|e| error_exit(&e));
+
+            match export {
+                Some(path) => {
+                    rotate::export(&steps, &path, "rotate").unwrap_or_else(|e| error_exit(&e));
+                    println!(
+                        "Wrote {} commands to {}; submit them with 'solana-vamp batch {}'",
+                        steps.len(),
+                        path,
+                        path
+                    );
+                },
+                None => {
+                    for (description, _) in &steps {
+                        println!("{}", description);
+                    }
+
+                    // As for a batch, the fee payer is the signer of the first rotation unless --fee-payer was supplied
+                    let (_, (first_signer, _, _)) = &steps[0];
+
+                    let fee_payer =
+                        fee_payer.unwrap_or_else(|| load_keypair(first_signer.as_ref().unwrap(), "fee payer"));
+
+                    batch::run(rpc_client, program_id, &fee_payer, steps.into_iter().map(|(_, step)| step).collect())
+                }
+            }
+        },
+        Command::Incident { compromised, replacements, export } => {
+            let compromised = make_pubkey(&compromised, "compromised key");
+
+            let exposures = incident::find(rpc_client, &program_id, &compromised).unwrap_or_else(|e| error_exit(&e));
+
+            print!("{}", incident::report(&compromised, &exposures, get_current_epoch(rpc_client)));
+
+            let flags = incident::replacement_flags(&exposures);
+
+            if flags.is_empty() {
+                return;
+            }
+
+            let supplied = [
+                &replacements.new_administrator,
+                &replacements.new_rewards_authority,
+                &replacements.new_operational_authority,
+                &replacements.new_vote_authority,
+                &replacements.new_validator_identity
+            ]
+            .iter()
+            .any(|replacement| replacement.is_some());
+
+            // Without any replacements the report is all that is wanted
+            if !supplied && export.is_none() {
+                println!("\nTo revoke the key, supply the new authorities with {}", flags.join(", "));
+                return;
+            }
+
+            let (steps, unsigned) =
+                incident::plan(&compromised, &exposures, &replacements).unwrap_or_else(|e| error_exit(&e));
+
+            println!();
+
+            for (vote_account, reason) in &unsigned {
+                println!("Not revoking the key from {}: {}", vote_account, reason);
+            }
+
+            if steps.is_empty() {
+                error_exit("The key cannot be revoked from any vote account with the keypairs supplied");
+            }
+
+            if !unsigned.is_empty() {
+                println!(
+                    "Run 'solana-vamp incident' again with the keypairs of their authorities to revoke the key from \
+                     them\n"
+                );
+            }
+
+            match export {
+                Some(path) => {
+                    rotate::export(&steps, &path, "incident").unwrap_or_else(|e| error_exit(&e));
+                    println!(
+                        "Wrote {} commands to {}; submit them with 'solana-vamp batch {}'",
+                        steps.len(),
+                        path,
+                        path
+                    );
+                },
+                None => {
+                    for (description, _) in &steps {
+                        println!("{}", description);
+                    }
+
+                    // As for rotate, the fee payer is the signer of the first rotation unless --fee-payer was supplied
+                    let (_, (first_signer, _, _)) = &steps[0];
+
+                    let fee_payer =
+                        fee_payer.unwrap_or_else(|| load_keypair(first_signer.as_ref().unwrap(), "fee payer"));
+
+                    batch::run(rpc_client, program_id, &fee_payer, steps.into_iter().map(|(_, step)| step).collect())
+                }
+            }
         },
-        Command::Withdraw { rewards_authority, recipient, amount } => {
+        Command::Withdraw { rewards_authority, recipients, amount } => {
             let fee_payer = fee_payer.unwrap();
 
             let rewards_authority = load_keypair(&rewards_authority, "rewards authority");
 
-            let recipient = make_pubkey(&recipient, "recipient");
-
-            let accounts = vec![
-                // Vote Account Manager State Account
-                AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : false },
-                // Vote Account
-                AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
-                // Rewards Authority
-                AccountMeta { pubkey : rewards_authority.pubkey(), is_signer : true, is_writable : false },
-                // Recipient
-                AccountMeta { pubkey : recipient, is_signer : false, is_writable : true },
-                // Vote Program Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-            ];
+            let recipients = recipients
+                .iter()
+                .map(|(recipient, basis_points)| (make_pubkey(recipient, "recipient"), *basis_points))
+                .collect::<Vec<(Pubkey, u32)>>();
+
+            // A lamports value of 0 is taken by the program to mean everything that can be withdrawn, and so is only
+            // sent for ALL, but splitting between recipients requires knowing the exact number of lamports up front
+            let lamports = match amount.unwrap_or(Amount::All) {
+                Amount::All if recipients.len() == 1 => 0,
+                amount => get_exact_lamports(rpc_client, &vote_account.unwrap(), amount)
+            };
 
-            let data = transaction_data::WithdrawData {
-                instruction_code : 8,
-                padding : [0_u8; 7],
-                lamports : amount.map(|sol| (sol * LAMPORTS_PER_SOL) as u64).unwrap_or(0)
+            let amounts = if recipients.len() == 1 {
+                vec![lamports]
+            }
+            else {
+                let amounts = split_lamports(
+                    lamports,
+                    &recipients.iter().map(|(_, basis_points)| *basis_points).collect::<Vec<u32>>()
+                );
+                if amounts.contains(&0) {
+                    error_exit(&format!(
+                        "{} lamports is too few to split between {} recipients",
+                        lamports,
+                        recipients.len()
+                    ));
+                }
+                for ((recipient, basis_points), amount) in recipients.iter().zip(amounts.iter()) {
+                    println!(
+                        "Withdrawing {} SOL ({}.{:02}%) to {}",
+                        format_sol(*amount),
+                        basis_points / 100,
+                        basis_points % 100,
+                        recipient
+                    );
+                }
+                amounts
             };
 
-            let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
+            let instructions = recipients
+                .iter()
+                .zip(amounts)
+                .map(|((recipient, _), amount)| {
+                    instruction::withdraw(
+                        program_id,
+                        vote_account.unwrap(),
+                        vote_account_manager_state_account.unwrap(),
+                        rewards_authority.pubkey(),
+                        *recipient,
+                        amount
+                    )
+                })
+                .collect::<Vec<Instruction>>();
 
             let tx = Transaction::new_signed_with_payer(
-                &[instruction],
+                &instructions,
                 Some(&fee_payer.pubkey()),
                 &[&fee_payer, &rewards_authority],
-                get_latest_blockhash(&rpc_client)
+                get_latest_blockhash(rpc_client)
             );
 
-            submit_transaction(&rpc_client, &tx)
+            // A serialized transaction is a compact array of signatures followed by the serialized message
+            if (1 + (tx.signatures.len() * 64) + tx.message.serialize().len()) > PACKET_DATA_SIZE {
+                error_exit(&format!(
+                    "Too many recipients ({}) to withdraw to in a single transaction",
+                    recipients.len()
+                ));
+            }
+
+            submit_transaction(rpc_client, &tx)
         },
-        Command::SetCommission { rewards_authority, commission } => {
-            let fee_payer = fee_payer.unwrap();
+        Command::WithdrawAndStake { rewards_authority, amount, stake_account, seed, stake_authority } => {
+            withdraw_and_stake(
+                rpc_client,
+                program_id,
+                vote_account.unwrap(),
+                vote_account_manager_state_account.unwrap(),
+                &fee_payer.unwrap(),
+                &load_keypair(&rewards_authority, "rewards authority"),
+                amount,
+                stake_account,
+                seed,
+                stake_authority
+            )
+        },
+        Command::SetCommission { rewards_authority, commission } => set_commission(
+            rpc_client,
+            program_id,
+            vote_account.unwrap(),
+            vote_account_manager_state_account.unwrap(),
+            &fee_payer.unwrap(),
+            &load_keypair(&rewards_authority, "rewards authority"),
+            commission
+        ),
+        Command::Batch { script, profile } => {
+            let batch = args::parse_batch_script(&script, profile).unwrap_or_else(|e| error_exit(&e));
+
+            // As for a single command, the fee payer is the signing authority of the first command unless
+            // --fee-payer was supplied
+            let fee_payer = fee_payer
+                .or_else(|| batch[0].0.as_ref().map(|fee_payer| load_keypair(fee_payer, "fee payer")))
+                .unwrap();
+
+            batch::run(rpc_client, program_id, &fee_payer, batch)
+        },
+        Command::Lint { vote_accounts, fee_payer, allow, deny, json } => {
+            let levels = lint::levels(&allow, &deny).unwrap_or_else(|e| error_exit(&e));
 
-            let rewards_authority = load_keypair(&rewards_authority, "rewards authority");
+            let vote_accounts = vote_accounts
+                .iter()
+                .map(|vote_account| make_pubkey(vote_account, "vote account"))
+                .collect::<Vec<Pubkey>>();
 
-            let accounts = vec![
-                // Vote Account Manager State Account
-                AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
-                // Vote Account
-                AccountMeta { pubkey : vote_account, is_signer : false, is_writable : true },
-                // Rewards Authority
-                AccountMeta { pubkey : rewards_authority.pubkey(), is_signer : true, is_writable : false },
-                // Vote Program Id
-                AccountMeta {
-                    pubkey : Pubkey::from_str(VOTE_PROGRAM_PUBKEY).unwrap(),
-                    is_signer : false,
-                    is_writable : false
-                },
-            ];
+            let (accounts, unmanaged) = get_managed_accounts(rpc_client, &program_id, &vote_accounts);
 
-            let data = transaction_data::SetCommissionData { instruction_code : 9, new_commission : commission };
+            let fee_payer = fee_payer.map(|fee_payer| make_pubkey(&fee_payer, "fee payer"));
 
-            let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
+            let findings = lint::check(&levels, &accounts, &unmanaged, fee_payer);
 
-            let tx = Transaction::new_signed_with_payer(
-                &[instruction],
-                Some(&fee_payer.pubkey()),
-                &[&fee_payer, &rewards_authority],
-                get_latest_blockhash(&rpc_client)
-            );
+            if json {
+                println!("{}", lint::json(&findings));
+            }
+            else {
+                print!("{}", lint::report(&findings, accounts.len() + unmanaged.len()));
+            }
 
-            submit_transaction(&rpc_client, &tx)
+            if lint::failed(&findings) {
+                std::process::exit(1);
+            }
         },
-        Command::Show { json } => {
-            match rpc_client.get_account_with_commitment(&vote_account_manager_state_account, commitment) {
-                Ok(response) => match response.value {
-                    Some(account) => {
-                        let data : [u8; 168] = account.data.try_into().unwrap_or_else(|_| {
-                            error_exit(&format!("{} is not managed by the Vote Account Manager program", vote_account))
-                        });
-                        let withdraw_authority = Pubkey::try_from(&data[0..32]).unwrap();
-                        let administrator = Pubkey::try_from(&data[32..64]).unwrap();
-                        let operational_authority = Pubkey::try_from(&data[64..96]).unwrap();
-                        let rewards_authority = Pubkey::try_from(&data[96..128]).unwrap();
-                        let commission_data = if data[128] == 0 { None } else { Some((data[129], data[130])) };
-                        let leave_epoch = u64::from_le_bytes(data[144..152].try_into().unwrap());
-                        if json {
-                            print!(
-                                "{{\"manager_account_pubkey\":\"{}\",\"withdraw_authority\":\"{}\",\"administrator\":\
-                                 \"{}\",\"operational_authority\":\"{}\",\"rewards_authority\":\"{}\"",
-                                vote_account_manager_state_account,
-                                withdraw_authority,
-                                administrator,
-                                operational_authority,
-                                rewards_authority
-                            );
-                            match commission_data {
-                                Some((max_commission, max_commission_increase_per_epoch)) => {
-                                    print!(
-                                        ",\"max_commission\":{},\"max_commission_increase_per_epoch\":{}",
-                                        max_commission, max_commission_increase_per_epoch
-                                    );
-                                },
-                                None => ()
-                            }
-                            if leave_epoch > 0 {
-                                print!(",\"leave_epoch\":{}", leave_epoch);
-                            }
-                            println!("}}");
-                        }
-                        else {
-                            println!();
-                            println!("Manager Account: {}", vote_account_manager_state_account);
-                            println!("Withdraw Authority: {}", withdraw_authority);
-                            println!("Administrator: {}", administrator);
-                            println!("Operational Authority: {}", operational_authority);
-                            println!("Rewards Authority: {}", rewards_authority);
-                            match commission_data {
-                                Some((max_commission, max_commission_increase_per_epoch)) => {
-                                    println!("Max Commission: {}", max_commission);
-                                    println!(
-                                        "Max Commission Increase per Epoch: {}",
-                                        max_commission_increase_per_epoch
-                                    );
-                                },
-                                None => ()
-                            }
-                            if leave_epoch > 0 {
-                                println!("Leave Epoch: {}", leave_epoch);
-                            }
-                            println!();
-                        }
-                    },
-                    None => error_exit(&format!("{} is not managed by the Vote Account Manager program", vote_account))
+        Command::Show { vote_accounts, json, graph } => show(rpc_client, &program_id, &vote_accounts, json, graph),
+        Command::PlanCommission { target, epoch, json } => plan_commission(
+            rpc_client,
+            vote_account.unwrap(),
+            vote_account_manager_state_account.unwrap(),
+            target,
+            epoch,
+            json
+        ),
+        Command::Daemon { rewards_authority, recipient, threshold, buffer, every_epoch, poll_interval } => daemon::run(
+            rpc_client,
+            program_id,
+            vote_account.unwrap(),
+            vote_account_manager_state_account.unwrap(),
+            &fee_payer.unwrap(),
+            &load_keypair(&rewards_authority, "rewards authority"),
+            &daemon::DaemonConfig {
+                recipient : make_pubkey(&recipient, "recipient"),
+                threshold : threshold.unwrap_or(0),
+                buffer : buffer.unwrap_or(0),
+                every_epoch,
+                poll_interval : Duration::from_secs(poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL_SECONDS))
+            }
+        ),
+        Command::RampCommission { rewards_authority, target, state_file, poll_interval } => ramp_commission(
+            rpc_client,
+            program_id,
+            vote_account.unwrap(),
+            vote_account_manager_state_account.unwrap(),
+            &fee_payer.unwrap(),
+            &load_keypair(&rewards_authority, "rewards authority"),
+            target,
+            &state_file.unwrap_or_else(|| format!("ramp-commission-{}.state", vote_account.unwrap())),
+            Duration::from_secs(poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL_SECONDS))
+        ),
+        Command::History { limit, json } => history::history(
+            rpc_client,
+            &program_id,
+            &vote_account_manager_state_account.unwrap(),
+            limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+            json
+        ),
+        Command::Decode { input } => decode::decode(rpc_client, &program_id, &input),
+        Command::Completions { shell } => print!("{}", completions::completions(&shell)),
+        Command::Man => print!("{}", man::man_page())
+    }
+}
+
+fn show(
+    rpc_client : &dyn Rpc,
+    program_id : &Pubkey,
+    vote_accounts : &[String],
+    json : bool,
+    graph : Option<String>
+)
+{
+    let vote_accounts =
+        vote_accounts.iter().map(|vote_account| make_pubkey(vote_account, "vote account")).collect::<Vec<Pubkey>>();
+
+    if let Some(format) = graph {
+        let (accounts, unmanaged) = get_managed_accounts(rpc_client, program_id, &vote_accounts);
+
+        // As when showing a single vote account, a single vote account must be managed
+        if let ([], [vote_account]) = (accounts.as_slice(), unmanaged.as_slice()) {
+            error_exit(&format!("{} is not managed by the Vote Account Manager program", vote_account));
+        }
+
+        print!("{}", graph::render(&format, &accounts, &unmanaged));
+
+        return;
+    }
+
+    let vote_account_manager_state_accounts = vote_accounts
+        .iter()
+        .map(|vote_account| get_vote_account_manager_state_account(program_id, vote_account))
+        .collect::<Vec<Pubkey>>();
+
+    let manager_states = get_multiple_accounts(rpc_client, &vote_account_manager_state_accounts)
+        .into_iter()
+        .map(|account| account.and_then(|account| ManagerState::decode(&account.data)))
+        .collect::<Vec<Option<ManagerState>>>();
+
+    // A single vote account is shown in the original single account format
+    if vote_accounts.len() == 1 {
+        let manager_state = manager_states[0].as_ref().unwrap_or_else(|| {
+            error_exit(&format!("{} is not managed by the Vote Account Manager program", vote_accounts[0]))
+        });
+
+        if json {
+            println!("{{{}}}", manager_state_json(&vote_account_manager_state_accounts[0], manager_state));
+        }
+        else {
+            println!();
+            print_manager_state(&vote_account_manager_state_accounts[0], manager_state);
+            println!();
+        }
+
+        return;
+    }
+
+    let vote_states = get_multiple_accounts(rpc_client, &vote_accounts)
+        .into_iter()
+        .map(|account| account.and_then(|account| VoteState::deserialize(&account.data).ok()))
+        .collect::<Vec<Option<VoteState>>>();
+
+    if json {
+        let entries = vote_accounts
+            .iter()
+            .enumerate()
+            .map(|(i, vote_account)| {
+                let mut entry = format!("{{\"vote_account\":\"{}\"", vote_account);
+                if let Some(vote_state) = &vote_states[i] {
+                    entry.push_str(&format!(
+                        ",\"validator_identity\":\"{}\",\"commission\":{}",
+                        vote_state.node_pubkey, vote_state.commission
+                    ));
+                }
+                match &manager_states[i] {
+                    Some(manager_state) => entry.push_str(&format!(
+                        ",\"managed\":true,{}",
+                        manager_state_json(&vote_account_manager_state_accounts[i], manager_state)
+                    )),
+                    None => entry.push_str(",\"managed\":false")
+                }
+                entry.push('}');
+                entry
+            })
+            .collect::<Vec<String>>();
+        println!("[{}]", entries.join(","));
+    }
+    else {
+        println!();
+        println!(
+            "{:<44}  {:>10}  {:>14}  {:>12}  {:>11}  {:<44}  {:<44}  {:<44}  {:<44}",
+            "Vote Account",
+            "Commission",
+            "Max Commission",
+            "Max Increase",
+            "Leave Epoch",
+            "Withdraw Authority",
+            "Administrator",
+            "Operational Authority",
+            "Rewards Authority"
+        );
+        for (i, vote_account) in vote_accounts.iter().enumerate() {
+            let commission = vote_states[i]
+                .as_ref()
+                .map(|vote_state| vote_state.commission.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            match &manager_states[i] {
+                Some(manager_state) => {
+                    let (max_commission, max_commission_increase_per_epoch) = manager_state
+                        .commission_caps
+                        .map(|(max_commission, max_commission_increase_per_epoch)| {
+                            (max_commission.to_string(), max_commission_increase_per_epoch.to_string())
+                        })
+                        .unwrap_or_else(|| ("-".to_string(), "-".to_string()));
+                    let leave_epoch = if manager_state.leave_epoch > 0 {
+                        manager_state.leave_epoch.to_string()
+                    }
+                    else {
+                        "-".to_string()
+                    };
+                    println!(
+                        "{:<44}  {:>10}  {:>14}  {:>12}  {:>11}  {:<44}  {:<44}  {:<44}  {:<44}",
+                        vote_account.to_string(),
+                        commission,
+                        max_commission,
+                        max_commission_increase_per_epoch,
+                        leave_epoch,
+                        manager_state.withdraw_authority.to_string(),
+                        manager_state.administrator.to_string(),
+                        manager_state.operational_authority.to_string(),
+                        manager_state.rewards_authority.to_string()
+                    );
                 },
-                Err(e) => error_exit(&format!("Failed to acquire vote account manager account: {}", e))
+                None => println!("{:<44}  {:>10}  (not managed)", vote_account.to_string(), commission)
+            }
+        }
+        println!();
+    }
+}
+
+// Returns the JSON fields (without enclosing braces) describing a manager state
+fn manager_state_json(
+    vote_account_manager_state_account : &Pubkey,
+    manager_state : &ManagerState
+) -> String
+{
+    let mut json = format!(
+        "\"manager_account_pubkey\":\"{}\",\"withdraw_authority\":\"{}\",\"administrator\":\"{}\",\
+         \"operational_authority\":\"{}\",\"rewards_authority\":\"{}\"",
+        vote_account_manager_state_account,
+        manager_state.withdraw_authority,
+        manager_state.administrator,
+        manager_state.operational_authority,
+        manager_state.rewards_authority
+    );
+    if let Some((max_commission, max_commission_increase_per_epoch)) = manager_state.commission_caps {
+        json.push_str(&format!(
+            ",\"max_commission\":{},\"max_commission_increase_per_epoch\":{}",
+            max_commission, max_commission_increase_per_epoch
+        ));
+    }
+    if manager_state.leave_epoch > 0 {
+        json.push_str(&format!(",\"leave_epoch\":{}", manager_state.leave_epoch));
+    }
+    json
+}
+
+fn print_manager_state(
+    vote_account_manager_state_account : &Pubkey,
+    manager_state : &ManagerState
+)
+{
+    println!("Manager Account: {}", vote_account_manager_state_account);
+    println!("Withdraw Authority: {}", manager_state.withdraw_authority);
+    println!("Administrator: {}", manager_state.administrator);
+    println!("Operational Authority: {}", manager_state.operational_authority);
+    println!("Rewards Authority: {}", manager_state.rewards_authority);
+    if let Some((max_commission, max_commission_increase_per_epoch)) = manager_state.commission_caps {
+        println!("Max Commission: {}", max_commission);
+        println!("Max Commission Increase per Epoch: {}", max_commission_increase_per_epoch);
+    }
+    if manager_state.leave_epoch > 0 {
+        println!("Leave Epoch: {}", manager_state.leave_epoch);
+    }
+}
+
+fn plan_commission(
+    rpc_client : &dyn Rpc,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    target : Option<u8>,
+    epoch : Option<u64>,
+    json : bool
+)
+{
+    let (manager_state, vote_state) =
+        get_managed_vote_account_state(rpc_client, &vote_account, &vote_account_manager_state_account);
+
+    let current_epoch = get_current_epoch(rpc_client);
+
+    let current_commission = vote_state.commission;
+
+    // Once a leave epoch has been set, the vamp program does not allow any commission changes
+    let frozen = manager_state.leave_epoch > 0;
+
+    match (target, epoch) {
+        (Some(target), _) => {
+            if frozen && (target != current_commission) {
+                error_exit(&format!(
+                    "Commission of {} cannot be changed because a leave epoch has been set",
+                    vote_account
+                ));
+            }
+            let schedule = commission::plan(current_commission, current_epoch, manager_state.commission_caps, target)
+                .unwrap_or_else(|| error_exit(&format!("Commission of {} can never reach {}", vote_account, target)));
+            let earliest_epoch = schedule.last().map(|(epoch, _)| *epoch).unwrap_or(current_epoch);
+            if json {
+                println!(
+                    "{{\"current_epoch\":{},\"current_commission\":{},\"target\":{},\"schedule\":[{}],\
+                     \"earliest_epoch\":{}}}",
+                    current_epoch,
+                    current_commission,
+                    target,
+                    schedule
+                        .iter()
+                        .map(|(epoch, commission)| format!("{{\"epoch\":{},\"commission\":{}}}", epoch, commission))
+                        .collect::<Vec<String>>()
+                        .join(","),
+                    earliest_epoch
+                );
+            }
+            else {
+                println!();
+                println!("Current Epoch: {}", current_epoch);
+                println!("Current Commission: {}", current_commission);
+                if let Some((max_commission, max_commission_increase_per_epoch)) = manager_state.commission_caps {
+                    println!("Max Commission: {}", max_commission);
+                    println!("Max Commission Increase per Epoch: {}", max_commission_increase_per_epoch);
+                }
+                println!();
+                if schedule.is_empty() {
+                    println!("Commission is already {}", target);
+                }
+                else {
+                    for (epoch, commission) in &schedule {
+                        println!("Epoch {}: set commission to {}", epoch, commission);
+                    }
+                    println!();
+                    println!("Commission {} can be reached at epoch {}", target, earliest_epoch);
+                }
+                println!();
+            }
+        },
+        (None, Some(epoch)) => {
+            if epoch < current_epoch {
+                error_exit(&format!("Epoch {} is before the current epoch {}", epoch, current_epoch));
+            }
+            let highest = if frozen {
+                current_commission
+            }
+            else {
+                commission::highest_at_epoch(current_commission, current_epoch, manager_state.commission_caps, epoch)
+            };
+            if json {
+                println!(
+                    "{{\"current_epoch\":{},\"current_commission\":{},\"epoch\":{},\"highest_commission\":{}}}",
+                    current_epoch, current_commission, epoch, highest
+                );
+            }
+            else {
+                println!();
+                println!("Current Epoch: {}", current_epoch);
+                println!("Current Commission: {}", current_commission);
+                println!();
+                println!("Highest commission {} could charge at epoch {}: {}", vote_account, epoch, highest);
+                println!();
+            }
+        },
+        (None, None) => unreachable!()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn ramp_commission(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    fee_payer : &Keypair,
+    rewards_authority : &Keypair,
+    target : u8,
+    state_file : &str,
+    poll_interval : Duration
+)
+{
+    let mut ramp_state = match RampState::load(state_file) {
+        Some(ramp_state) => {
+            ramp_state.check(&vote_account, target, state_file).unwrap_or_else(|e| error_exit(&e));
+            println!("Resuming ramp of {} to commission {} using {}", vote_account, target, state_file);
+            ramp_state
+        },
+        None => {
+            let ramp_state = RampState::new(vote_account, target);
+            ramp_state.save(state_file);
+            println!("Starting ramp of {} to commission {} using {}", vote_account, target, state_file);
+            ramp_state
+        }
+    };
+
+    while let Some(epoch) = ramp_step(
+        rpc_client,
+        program_id,
+        vote_account_manager_state_account,
+        fee_payer,
+        rewards_authority,
+        &mut ramp_state,
+        state_file
+    ) {
+        wait_for_next_epoch(rpc_client, epoch, poll_interval);
+    }
+
+    std::fs::remove_file(state_file)
+        .unwrap_or_else(|e| error_exit(&format!("Failed to remove ramp state file {}: {}", state_file, e)));
+
+    println!("Commission of {} has reached {}", vote_account, target);
+}
+
+// Takes the step of a ramp for the current epoch, recording it in the state file before it is submitted.  Returns
+// the epoch to wait out before the next step, or None once the commission has reached the target.
+fn ramp_step(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    fee_payer : &Keypair,
+    rewards_authority : &Keypair,
+    ramp_state : &mut RampState,
+    state_file : &str
+) -> Option<u64>
+{
+    let (vote_account, target) = (ramp_state.vote_account, ramp_state.target);
+
+    let (manager_state, vote_state) =
+        get_managed_vote_account_state(rpc_client, &vote_account, &vote_account_manager_state_account);
+
+    let current_epoch = get_current_epoch(rpc_client);
+
+    let current_commission = vote_state.commission;
+
+    if current_commission == target {
+        return None;
+    }
+
+    if manager_state.leave_epoch > 0 {
+        error_exit(&format!("Commission of {} cannot be changed because a leave epoch has been set", vote_account));
+    }
+
+    // If the step for this epoch was already submitted and has taken effect, there is nothing more to do until the
+    // next epoch.  If it was submitted but has not taken effect (for example because solana-vamp was interrupted
+    // before it was confirmed), it is submitted again.
+    match ramp_state.last_step {
+        Some((last_epoch, last_commission)) if last_epoch == current_epoch => {
+            if last_commission == current_commission {
+                return Some(current_epoch);
             }
+            println!("Commission step submitted in epoch {} did not take effect, retrying", current_epoch);
+        },
+        _ => ()
+    }
+
+    let commission = commission::plan(current_commission, current_epoch, manager_state.commission_caps, target)
+        .and_then(|schedule| schedule.first().map(|(_, commission)| *commission))
+        .unwrap_or_else(|| error_exit(&format!("Commission of {} can never reach {}", vote_account, target)));
+
+    ramp_state.last_step = Some((current_epoch, commission));
+
+    ramp_state.save(state_file);
+
+    println!(
+        "Epoch {}: setting commission of {} from {} to {}",
+        current_epoch, vote_account, current_commission, commission
+    );
+
+    set_commission(
+        rpc_client,
+        program_id,
+        vote_account,
+        vote_account_manager_state_account,
+        fee_payer,
+        rewards_authority,
+        commission
+    );
+
+    if commission == target {
+        None
+    }
+    else {
+        Some(current_epoch)
+    }
+}
+
+// Waits until an epoch after epoch has begun.  RPC failures are reported and retried, since this may wait for days.
+fn wait_for_next_epoch(
+    rpc_client : &dyn Rpc,
+    epoch : u64,
+    poll_interval : Duration
+)
+{
+    println!("Waiting for epoch {}", epoch + 1);
+
+    loop {
+        std::thread::sleep(poll_interval);
+
+        match rpc_client.get_epoch_info() {
+            Ok(epoch_info) if epoch_info.epoch > epoch => return,
+            Ok(_) => (),
+            Err(e) => eprintln!("Failed to fetch epoch info, will retry: {}", e)
         }
     }
 }
 
+// Commission caps are enforced if either cap is supplied, with the other defaulting to 0
+fn commission_caps(
+    max_commission : Option<u8>,
+    max_commission_increase_per_epoch : Option<u8>
+) -> Option<(u8, u8)>
+{
+    if max_commission.is_some() || max_commission_increase_per_epoch.is_some() {
+        Some((max_commission.unwrap_or(0), max_commission_increase_per_epoch.unwrap_or(0)))
+    }
+    else {
+        None
+    }
+}
+
+fn enter(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    fee_payer : &Keypair,
+    withdraw_authority : &Keypair,
+    administrator : Pubkey,
+    commission_caps : Option<(u8, u8)>
+)
+{
+    let instruction = instruction::enter(
+        program_id,
+        vote_account,
+        get_vote_account_manager_state_account(&program_id, &vote_account),
+        fee_payer.pubkey(),
+        withdraw_authority.pubkey(),
+        administrator,
+        commission_caps
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&fee_payer.pubkey()),
+        &[fee_payer, withdraw_authority],
+        get_latest_blockhash(rpc_client)
+    );
+
+    submit_transaction(rpc_client, &tx)
+}
+
+fn set_commission(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    fee_payer : &Keypair,
+    rewards_authority : &Keypair,
+    commission : u8
+)
+{
+    let instruction = instruction::set_commission(
+        program_id,
+        vote_account,
+        vote_account_manager_state_account,
+        rewards_authority.pubkey(),
+        commission
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&fee_payer.pubkey()),
+        &[fee_payer, rewards_authority],
+        get_latest_blockhash(rpc_client)
+    );
+
+    submit_transaction(rpc_client, &tx)
+}
+
+// Splits lamports according to shares given in basis points (which must total 10000).  Each share is rounded down to
+// a whole lamport, and any lamports left over from rounding go to the first recipient.
+fn split_lamports(
+    lamports : u64,
+    basis_points : &[u32]
+) -> Vec<u64>
+{
+    let mut amounts = basis_points
+        .iter()
+        .map(|basis_points| (((lamports as u128) * (*basis_points as u128)) / 10000) as u64)
+        .collect::<Vec<u64>>();
+
+    amounts[0] += lamports - amounts.iter().sum::<u64>();
+
+    amounts
+}
+
+// Withdraws from the vote account to the fee payer, and uses the withdrawn lamports to create a new stake account
+// which is delegated to the vote account, all in a single transaction
+#[allow(clippy::too_many_arguments)]
+fn withdraw_and_stake(
+    rpc_client : &dyn Rpc,
+    program_id : Pubkey,
+    vote_account : Pubkey,
+    vote_account_manager_state_account : Pubkey,
+    fee_payer : &Keypair,
+    rewards_authority : &Keypair,
+    amount : Option<Amount>,
+    stake_account : Option<String>,
+    seed : Option<String>,
+    stake_authority : Option<String>
+)
+{
+    let stake_authority = stake_authority.map(|stake_authority| load_keypair(&stake_authority, "stake authority"));
+
+    let authority =
+        stake_authority.as_ref().map(|stake_authority| stake_authority.pubkey()).unwrap_or(fee_payer.pubkey());
+
+    // The stake account is either derived from the fee payer and a seed, or is a keypair, which is generated if not
+    // supplied
+    let (stake_account, stake_account_keypair) = match &seed {
+        Some(seed) => (
+            Pubkey::create_with_seed(&fee_payer.pubkey(), seed, &stake::program::id())
+                .unwrap_or_else(|e| error_exit(&format!("Invalid seed {}: {}", seed, e))),
+            None
+        ),
+        None => {
+            let keypair = stake_account
+                .map(|stake_account| load_keypair(&stake_account, "stake account"))
+                .unwrap_or_else(Keypair::new);
+            (keypair.pubkey(), Some(keypair))
+        }
+    };
+
+    let lamports = get_exact_lamports(rpc_client, &vote_account, amount.unwrap_or(Amount::All));
+
+    let rent_exempt_minimum = rpc_client
+        .get_minimum_balance_for_rent_exemption(StakeState::size_of())
+        .unwrap_or_else(|e| error_exit(&format!("Failed to fetch rent exempt minimum: {}", e)));
+
+    let minimum_delegation = rpc_client
+        .get_stake_minimum_delegation()
+        .unwrap_or_else(|e| error_exit(&format!("Failed to fetch stake minimum delegation: {}", e)));
+
+    if lamports < (rent_exempt_minimum + minimum_delegation) {
+        error_exit(&format!(
+            "{} SOL is less than the {} SOL required to create and delegate a stake account",
+            format_sol(lamports),
+            format_sol(rent_exempt_minimum + minimum_delegation)
+        ));
+    }
+
+    let authorized = Authorized { staker : authority, withdrawer : authority };
+
+    let mut instructions = vec![instruction::withdraw(
+        program_id,
+        vote_account,
+        vote_account_manager_state_account,
+        rewards_authority.pubkey(),
+        fee_payer.pubkey(),
+        lamports
+    )];
+
+    instructions.extend(match &seed {
+        Some(seed) => stake_instruction::create_account_with_seed_and_delegate_stake(
+            &fee_payer.pubkey(),
+            &stake_account,
+            &fee_payer.pubkey(),
+            seed,
+            &vote_account,
+            &authorized,
+            &Lockup::default(),
+            lamports
+        ),
+        None => stake_instruction::create_account_and_delegate_stake(
+            &fee_payer.pubkey(),
+            &stake_account,
+            &vote_account,
+            &authorized,
+            &Lockup::default(),
+            lamports
+        )
+    });
+
+    // The same keypair may fill more than one role
+    let mut signers : Vec<&Keypair> = vec![];
+
+    for signer in [Some(fee_payer), Some(rewards_authority), stake_account_keypair.as_ref(), stake_authority.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        if !signers.iter().any(|existing| existing.pubkey() == signer.pubkey()) {
+            signers.push(signer);
+        }
+    }
+
+    println!(
+        "Withdrawing {} SOL into new stake account {} delegated to {}, with stake authority {}",
+        format_sol(lamports),
+        stake_account,
+        vote_account,
+        authority
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&fee_payer.pubkey()),
+        &signers,
+        get_latest_blockhash(rpc_client)
+    );
+
+    submit_transaction(rpc_client, &tx)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn set_authority(
-    rpc_client : RpcClient,
+    rpc_client : &dyn Rpc,
     program_id : Pubkey,
     vote_account : Pubkey,
     vote_account_manager_state_account : Pubkey,
     fee_payer : Keypair,
-    instruction_code : u8,
+    make_instruction : fn([u8; 32]) -> VampInstruction,
     authority : &str,
     authority_name : &str,
     new_authority : &str,
@@ -478,41 +1239,35 @@ fn set_authority(
 
     let new_authority = make_pubkey(new_authority, new_authority_name);
 
-    let accounts = vec![
-        // Vote Account Manager State Account
-        AccountMeta { pubkey : vote_account_manager_state_account, is_signer : false, is_writable : true },
-        // Vote Account
-        AccountMeta { pubkey : vote_account, is_signer : false, is_writable : false },
-        // Authority
-        AccountMeta { pubkey : authority.pubkey(), is_signer : true, is_writable : false },
-    ];
-
-    let data = transaction_data::SetAuthorityData { instruction_code, new_authority : new_authority.to_bytes() };
-
-    let instruction = Instruction { program_id, accounts, data : AsBytes::as_bytes(&data).to_vec() };
+    let instruction = instruction::set_authority(
+        program_id,
+        vote_account,
+        vote_account_manager_state_account,
+        authority.pubkey(),
+        make_instruction(new_authority.to_bytes())
+    );
 
     let tx = Transaction::new_signed_with_payer(
         &[instruction],
         Some(&fee_payer.pubkey()),
         &[&fee_payer, &authority],
-        get_latest_blockhash(&rpc_client)
+        get_latest_blockhash(rpc_client)
     );
 
-    submit_transaction(&rpc_client, &tx)
+    submit_transaction(rpc_client, &tx)
 }
 
 fn load_keypair_helper(s : &str) -> Result<Keypair, String>
 {
-    let contents =
-        std::fs::read_to_string(s).map_err(|e| format!("Failed to read keypair from {}: {}", s, e.to_string()))?;
+    let contents = std::fs::read_to_string(s).map_err(|e| format!("Failed to read keypair from {}: {}", s, e))?;
 
-    if !contents.starts_with("[") || !contents.ends_with("]") {
+    if !contents.starts_with('[') || !contents.ends_with(']') {
         return Err(format!("Malformed keypair file contents in {}", s));
     }
 
     let contents = &contents[1..(contents.len() - 1)];
 
-    Keypair::from_bytes(u8_list_to_vec(&contents)?.as_slice())
+    Keypair::from_bytes(u8_list_to_vec(contents)?.as_slice())
         .map_err(|e| format!("Invalid keypair file contents in {}: {}", s, e))
 }
 
@@ -521,21 +1276,21 @@ fn load_keypair(
     desc : &str
 ) -> Keypair
 {
-    load_keypair_helper(&s).unwrap_or_else(|e| error_exit(&format!("Failed to load {} keypair: {}", desc, e)))
+    load_keypair_helper(s).unwrap_or_else(|e| error_exit(&format!("Failed to load {} keypair: {}", desc, e)))
 }
 
 fn u8_list_to_vec(bytes : &str) -> Result<Vec<u8>, String>
 {
     bytes
-        .replace(" ", "")
-        .split(",")
+        .replace(' ', "")
+        .split(',')
         .map(|s| s.parse::<u8>().map_err(|e| e.to_string()))
         .collect::<Result<Vec<u8>, String>>()
 }
 
 fn make_pubkey_helper(s : &str) -> Result<Pubkey, String>
 {
-    load_keypair_helper(s).map(|keypair| keypair.pubkey()).or_else(|_| Pubkey::from_str(&s).map_err(|e| e.to_string()))
+    load_keypair_helper(s).map(|keypair| keypair.pubkey()).or_else(|_| Pubkey::from_str(s).map_err(|e| e.to_string()))
 }
 
 fn make_pubkey(
@@ -546,18 +1301,184 @@ fn make_pubkey(
     make_pubkey_helper(s).unwrap_or_else(|e| error_exit(&format!("Failed to create {} pubkey: {}", desc, e)))
 }
 
-fn get_latest_blockhash(rpc_client : &RpcClient) -> Hash
+// Fetches accounts using as few getMultipleAccounts calls as possible; the results are in the same order as pubkeys
+fn get_multiple_accounts(
+    rpc_client : &dyn Rpc,
+    pubkeys : &[Pubkey]
+) -> Vec<Option<Account>>
+{
+    pubkeys
+        .chunks(MAX_MULTIPLE_ACCOUNTS)
+        .flat_map(|chunk| {
+            rpc_client
+                .get_multiple_accounts(chunk)
+                .unwrap_or_else(|e| error_exit(&format!("Failed to acquire accounts: {}", e)))
+        })
+        .collect()
+}
+
+// Fetches and decodes the manager state account and vote account of a managed vote account
+fn get_managed_vote_account_state(
+    rpc_client : &dyn Rpc,
+    vote_account : &Pubkey,
+    vote_account_manager_state_account : &Pubkey
+) -> (ManagerState, VoteState)
+{
+    let accounts = get_multiple_accounts(rpc_client, &[*vote_account_manager_state_account, *vote_account]);
+
+    let manager_state = accounts[0]
+        .as_ref()
+        .and_then(|account| ManagerState::decode(&account.data))
+        .unwrap_or_else(|| error_exit(&format!("{} is not managed by the Vote Account Manager program", vote_account)));
+
+    let vote_state = accounts[1]
+        .as_ref()
+        .and_then(|account| VoteState::deserialize(&account.data).ok())
+        .unwrap_or_else(|| error_exit(&format!("{} is not a vote account", vote_account)));
+
+    (manager_state, vote_state)
+}
+
+// Fetches the state of several vote accounts, returning those managed by the program, and those which are not.  A vote
+// account given more than once is fetched and returned once, so that it is not taken to share its keys with itself.
+fn get_managed_accounts(
+    rpc_client : &dyn Rpc,
+    program_id : &Pubkey,
+    vote_accounts : &[Pubkey]
+) -> (Vec<ManagedAccount>, Vec<Pubkey>)
+{
+    let mut unique = vec![];
+
+    for vote_account in vote_accounts {
+        if !unique.contains(vote_account) {
+            unique.push(*vote_account);
+        }
+    }
+
+    let vote_accounts = &unique;
+
+    let vote_account_manager_state_accounts = vote_accounts
+        .iter()
+        .map(|vote_account| get_vote_account_manager_state_account(program_id, vote_account))
+        .collect::<Vec<Pubkey>>();
+
+    let manager_states = get_multiple_accounts(rpc_client, &vote_account_manager_state_accounts)
+        .into_iter()
+        .map(|account| account.and_then(|account| ManagerState::decode(&account.data)));
+
+    let vote_states = get_multiple_accounts(rpc_client, vote_accounts)
+        .into_iter()
+        .map(|account| account.and_then(|account| VoteState::deserialize(&account.data).ok()));
+
+    let mut accounts = vec![];
+    let mut unmanaged = vec![];
+
+    for ((vote_account, manager_state), vote_state) in vote_accounts.iter().zip(manager_states).zip(vote_states) {
+        match (manager_state, vote_state) {
+            (Some(manager_state), Some(vote_state)) => accounts.push(ManagedAccount {
+                vote_account : *vote_account,
+                manager_state,
+                vote_authority : vote_state
+                    .authorized_voters()
+                    .last()
+                    .map(|(_, authorized_voter)| *authorized_voter)
+                    .unwrap_or_default(),
+                validator_identity : vote_state.node_pubkey
+            }),
+            _ => unmanaged.push(*vote_account)
+        }
+    }
+
+    (accounts, unmanaged)
+}
+
+// Resolves an amount to withdraw from a vote account into an exact number of lamports
+fn get_exact_lamports(
+    rpc_client : &dyn Rpc,
+    vote_account : &Pubkey,
+    amount : Amount
+) -> u64
+{
+    match amount {
+        Amount::Lamports(lamports) => lamports,
+        amount => resolve_amount(amount, get_withdrawable_lamports(rpc_client, vote_account))
+            .unwrap_or_else(|e| error_exit(&e))
+    }
+}
+
+// Resolves an amount against the number of lamports that can be withdrawn
+fn resolve_amount(
+    amount : Amount,
+    withdrawable : u64
+) -> Result<u64, String>
+{
+    match amount {
+        Amount::All => Ok(withdrawable),
+        Amount::AllBut(remaining) if withdrawable <= remaining => Err(format!(
+            "Only {} SOL can be withdrawn, which is not more than {} SOL",
+            format_sol(withdrawable),
+            format_sol(remaining)
+        )),
+        Amount::AllBut(remaining) => Ok(withdrawable - remaining),
+        Amount::Lamports(lamports) => Ok(lamports)
+    }
+}
+
+// Returns the number of lamports that can be withdrawn from a vote account without going below its rent exempt minimum
+fn get_withdrawable_lamports(
+    rpc_client : &dyn Rpc,
+    vote_account : &Pubkey
+) -> u64
+{
+    let account = rpc_client
+        .get_account(vote_account)
+        .unwrap_or_else(|e| error_exit(&format!("Failed to fetch vote account {}: {}", vote_account, e)));
+
+    let rent_exempt_minimum = rpc_client
+        .get_minimum_balance_for_rent_exemption(account.data.len())
+        .unwrap_or_else(|e| error_exit(&format!("Failed to fetch rent exempt minimum: {}", e)));
+
+    account.lamports.saturating_sub(rent_exempt_minimum)
+}
+
+fn get_current_epoch(rpc_client : &dyn Rpc) -> u64
+{
+    rpc_client.get_epoch_info().unwrap_or_else(|e| error_exit(&format!("Failed to fetch epoch info: {}", e))).epoch
+}
+
+fn get_latest_blockhash(rpc_client : &dyn Rpc) -> Hash
 {
     rpc_client
         .get_latest_blockhash()
         .unwrap_or_else(|e| error_exit(&format!("Failed to fetch latest blockhash: {}", e)))
 }
 
+// Simulates a transaction before it is submitted, so that the program logs can be shown if it would fail
+fn simulate_transaction(
+    rpc_client : &dyn Rpc,
+    tx : &Transaction
+) -> Result<(), String>
+{
+    let simulation =
+        rpc_client.simulate_transaction(tx).map_err(|e| format!("Failed to simulate transaction: {}", e))?;
+
+    match simulation.err {
+        Some(err) => Err(format!(
+            "Transaction simulation failed: {}\nProgram logs:\n{}",
+            err,
+            simulation.logs.unwrap_or_default().iter().map(|log| format!("  {}\n", log)).collect::<String>()
+        )),
+        None => Ok(())
+    }
+}
+
 fn submit_transaction(
-    rpc_client : &RpcClient,
+    rpc_client : &dyn Rpc,
     tx : &Transaction
 )
 {
+    simulate_transaction(rpc_client, tx).unwrap_or_else(|e| error_exit(&e));
+
     println!(
         "Transaction submitted with signature: {}",
         rpc_client
@@ -565,3 +1486,861 @@ fn submit_transaction(
             .unwrap_or_else(|e| error_exit(&format!("Failed to submit transaction: {}", e)))
     );
 }
+
+// Formats seconds since the unix epoch as an ISO 8601 UTC timestamp
+fn format_utc(secs : i64) -> String
+{
+    let days = secs.div_euclid(86400);
+
+    let secs_of_day = secs.rem_euclid(86400);
+
+    // Convert days since 1970-01-01 to a civil date (proleptic Gregorian calendar)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use environment::Environment;
+    use proptest::prelude::*;
+    use rpc::bank::BankRpc;
+    use rpc::mock::{KeypairFile, MockRpc};
+    use solana_sdk::instruction::AccountMeta;
+    use solana_vamp::model::{Model, ModelError, VoteAccountModel};
+
+    const VOTE_ACCOUNT_LAMPORTS : u64 = 10 * amount::LAMPORTS_PER_SOL;
+
+    struct Setup
+    {
+        rpc : MockRpc,
+
+        program_id : Pubkey,
+
+        vote_account : Pubkey,
+
+        vote_account_manager_state_account : Pubkey,
+
+        // Holds every manager state role
+        authority : KeypairFile,
+
+        fee_payer : Keypair
+    }
+
+    fn setup(
+        commission : u8,
+        commission_caps : Option<(u8, u8)>,
+        leave_epoch : u64
+    ) -> Setup
+    {
+        let rpc = MockRpc::new();
+        let program_id = Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap();
+        let vote_account = Pubkey::new_unique();
+        let vote_account_manager_state_account = get_vote_account_manager_state_account(&program_id, &vote_account);
+        let authority = KeypairFile::new();
+
+        rpc.set_epoch(100);
+        rpc.set_vote_account(vote_account, vote_account_manager_state_account, commission, VOTE_ACCOUNT_LAMPORTS);
+        rpc.set_manager_state(
+            program_id,
+            vote_account_manager_state_account,
+            authority.pubkey(),
+            commission_caps,
+            leave_epoch
+        );
+
+        Setup {
+            rpc,
+            program_id,
+            vote_account,
+            vote_account_manager_state_account,
+            authority,
+            fee_payer : Keypair::new()
+        }
+    }
+
+    fn withdrawable(setup : &Setup) -> u64
+    {
+        VOTE_ACCOUNT_LAMPORTS - setup.rpc.get_minimum_balance_for_rent_exemption(VoteState::size_of()).unwrap()
+    }
+
+    // Returns the decoded vamp program instructions of a submitted transaction
+    fn vamp_instructions(
+        setup : &Setup,
+        tx : &Transaction
+    ) -> Vec<VampInstruction>
+    {
+        tx.message
+            .instructions
+            .iter()
+            .filter(|instruction| tx.message.account_keys[instruction.program_id_index as usize] == setup.program_id)
+            .map(|instruction| VampInstruction::decode(&instruction.data).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reads_managed_vote_account_state()
+    {
+        let setup = setup(7, Some((10, 2)), 123);
+
+        let (manager_state, vote_state) =
+            get_managed_vote_account_state(&setup.rpc, &setup.vote_account, &setup.vote_account_manager_state_account);
+
+        assert_eq!(manager_state.rewards_authority, setup.authority.pubkey());
+        assert_eq!(manager_state.commission_caps, Some((10, 2)));
+        assert_eq!(manager_state.leave_epoch, 123);
+        assert_eq!(vote_state.commission, 7);
+        assert_eq!(vote_state.authorized_withdrawer, setup.vote_account_manager_state_account);
+    }
+
+    #[test]
+    fn managed_accounts_are_fetched_once_each()
+    {
+        let setup = setup(7, None, 0);
+        let unmanaged = Pubkey::new_unique();
+
+        let (accounts, unmanaged_accounts) =
+            get_managed_accounts(&setup.rpc, &setup.program_id, &[setup.vote_account, unmanaged, setup.vote_account]);
+
+        assert_eq!(accounts.iter().map(|account| account.vote_account).collect::<Vec<Pubkey>>(), vec![
+            setup.vote_account
+        ]);
+        assert_eq!(accounts[0].manager_state.administrator, setup.authority.pubkey());
+        assert_eq!(unmanaged_accounts, vec![unmanaged]);
+
+        // A single unmanaged vote account is returned rather than refused, so that lint can report it
+        assert_eq!(get_managed_accounts(&setup.rpc, &setup.program_id, &[unmanaged]).1, vec![unmanaged]);
+    }
+
+    #[test]
+    fn fetches_accounts_in_order_across_requests()
+    {
+        let rpc = MockRpc::new();
+
+        let pubkeys = (0..(MAX_MULTIPLE_ACCOUNTS * 2 + 1)).map(|_| Pubkey::new_unique()).collect::<Vec<Pubkey>>();
+
+        for (i, pubkey) in pubkeys.iter().enumerate().filter(|(i, _)| i % 3 == 0) {
+            rpc.set_account(*pubkey, Account { lamports : i as u64, ..Account::default() });
+        }
+
+        let accounts = get_multiple_accounts(&rpc, &pubkeys);
+
+        assert_eq!(accounts.len(), pubkeys.len());
+
+        for (i, account) in accounts.iter().enumerate() {
+            assert_eq!(account.as_ref().map(|account| account.lamports), (i % 3 == 0).then_some(i as u64));
+        }
+    }
+
+    #[test]
+    fn resolves_amounts_against_withdrawable_lamports()
+    {
+        let setup = setup(7, None, 0);
+
+        let withdrawable = withdrawable(&setup);
+
+        assert_eq!(get_exact_lamports(&setup.rpc, &setup.vote_account, Amount::All), withdrawable);
+        // Only ALL means everything; an exact amount is never widened to it
+        assert_eq!(get_exact_lamports(&setup.rpc, &setup.vote_account, Amount::Lamports(0)), 0);
+        assert_eq!(get_exact_lamports(&setup.rpc, &setup.vote_account, Amount::Lamports(12345)), 12345);
+        assert_eq!(get_exact_lamports(&setup.rpc, &setup.vote_account, Amount::AllBut(1000)), withdrawable - 1000);
+    }
+
+    #[test]
+    fn set_commission_submits_signed_transaction()
+    {
+        let setup = setup(7, None, 0);
+
+        set_commission(
+            &setup.rpc,
+            setup.program_id,
+            setup.vote_account,
+            setup.vote_account_manager_state_account,
+            &setup.fee_payer,
+            &setup.authority.keypair,
+            9
+        );
+
+        let sent = setup.rpc.sent.borrow();
+
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].verify().is_ok());
+        assert_eq!(sent[0].message.account_keys[0], setup.fee_payer.pubkey());
+        assert_eq!(vamp_instructions(&setup, &sent[0]), vec![VampInstruction::SetCommission { new_commission : 9 }]);
+    }
+
+    #[test]
+    fn set_authority_loads_authority_keypair()
+    {
+        let setup = setup(7, None, 0);
+
+        let new_authority = Pubkey::new_unique();
+
+        set_authority(
+            &setup.rpc,
+            setup.program_id,
+            setup.vote_account,
+            setup.vote_account_manager_state_account,
+            Keypair::from_bytes(&setup.fee_payer.to_bytes()).unwrap(),
+            |new_authority| VampInstruction::SetRewardsAuthority { new_authority },
+            &setup.authority.path,
+            "administrator",
+            &new_authority.to_string(),
+            "rewards authority"
+        );
+
+        let sent = setup.rpc.sent.borrow();
+
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].verify().is_ok());
+        assert!(sent[0].message.account_keys.contains(&setup.authority.pubkey()));
+        assert_eq!(vamp_instructions(&setup, &sent[0]), vec![VampInstruction::SetRewardsAuthority {
+            new_authority : new_authority.to_bytes()
+        }]);
+    }
+
+    #[test]
+    fn withdraw_and_stake_withdraws_into_new_stake_account()
+    {
+        let setup = setup(7, None, 0);
+
+        withdraw_and_stake(
+            &setup.rpc,
+            setup.program_id,
+            setup.vote_account,
+            setup.vote_account_manager_state_account,
+            &setup.fee_payer,
+            &setup.fee_payer,
+            Some(Amount::AllBut(amount::LAMPORTS_PER_SOL)),
+            None,
+            Some("stake".to_string()),
+            None
+        );
+
+        let sent = setup.rpc.sent.borrow();
+
+        let stake_account =
+            Pubkey::create_with_seed(&setup.fee_payer.pubkey(), "stake", &stake::program::id()).unwrap();
+
+        assert_eq!(sent.len(), 1);
+        // The fee payer is also the rewards authority, and signs only once
+        assert_eq!(sent[0].signatures.len(), 1);
+        assert!(sent[0].verify().is_ok());
+        assert!(sent[0].message.account_keys.contains(&stake_account));
+        assert_eq!(vamp_instructions(&setup, &sent[0]), vec![VampInstruction::Withdraw {
+            lamports : withdrawable(&setup) - amount::LAMPORTS_PER_SOL
+        }]);
+    }
+
+    #[test]
+    fn ramp_commission_completes_when_target_is_reachable()
+    {
+        let setup = setup(7, None, 0);
+
+        let state_file = std::env::temp_dir().join(format!("solana-vamp-test-{}.ramp", setup.vote_account));
+
+        ramp_commission(
+            &setup.rpc,
+            setup.program_id,
+            setup.vote_account,
+            setup.vote_account_manager_state_account,
+            &setup.fee_payer,
+            &setup.authority.keypair,
+            3,
+            state_file.to_str().unwrap(),
+            Duration::from_secs(1)
+        );
+
+        assert!(!state_file.exists());
+        assert_eq!(vamp_instructions(&setup, &setup.rpc.sent.borrow()[0]), vec![VampInstruction::SetCommission {
+            new_commission : 3
+        }]);
+    }
+
+    // Takes one step of a ramp of the setup vote account to commission 9, from a state file holding last_step
+    fn ramp_step_from(
+        setup : &Setup,
+        last_step : Option<(u64, u8)>
+    ) -> (Option<u64>, RampState, Vec<u8>)
+    {
+        let state_file = std::env::temp_dir().join(format!("solana-vamp-test-{}.ramp", setup.vote_account));
+        let state_file = state_file.to_str().unwrap();
+
+        let mut ramp_state = RampState { last_step, ..RampState::new(setup.vote_account, 9) };
+
+        let sent_before = setup.rpc.sent.borrow().len();
+
+        let result = ramp_step(
+            &setup.rpc,
+            setup.program_id,
+            setup.vote_account_manager_state_account,
+            &setup.fee_payer,
+            &setup.authority.keypair,
+            &mut ramp_state,
+            state_file
+        );
+
+        let saved = RampState::load(state_file);
+
+        let _ = std::fs::remove_file(state_file);
+
+        let commissions = setup.rpc.sent.borrow()[sent_before..]
+            .iter()
+            .flat_map(|tx| vamp_instructions(setup, tx))
+            .map(|instruction| match instruction {
+                VampInstruction::SetCommission { new_commission } => new_commission,
+                instruction => panic!("unexpected {:?}", instruction)
+            })
+            .collect::<Vec<u8>>();
+
+        // The state file is written, recording the step, only when a step is submitted
+        assert_eq!(saved.map(|saved| saved.last_step), (!commissions.is_empty()).then_some(ramp_state.last_step));
+
+        (result, ramp_state, commissions)
+    }
+
+    #[test]
+    fn ramp_waits_for_the_next_epoch_once_its_step_has_taken_effect()
+    {
+        let setup = setup(5, Some((10, 2)), 0);
+
+        // Restarted in the epoch of a step which took effect
+        let (result, ramp_state, commissions) = ramp_step_from(&setup, Some((100, 5)));
+
+        assert_eq!((result, ramp_state.last_step, commissions), (Some(100), Some((100, 5)), vec![]));
+
+        // The next step is taken in the next epoch
+        setup.rpc.set_epoch(101);
+
+        let (result, ramp_state, commissions) = ramp_step_from(&setup, ramp_state.last_step);
+
+        assert_eq!((result, ramp_state.last_step, commissions), (Some(101), Some((101, 7)), vec![7]));
+    }
+
+    #[test]
+    fn ramp_retries_a_step_which_did_not_take_effect()
+    {
+        let setup = setup(5, Some((10, 2)), 0);
+
+        // Restarted in the epoch of a step which was recorded but never confirmed
+        let (result, ramp_state, commissions) = ramp_step_from(&setup, Some((100, 7)));
+
+        assert_eq!((result, ramp_state.last_step, commissions), (Some(100), Some((100, 7)), vec![7]));
+    }
+
+    #[test]
+    fn ramp_restarted_in_a_later_epoch_takes_the_step_of_that_epoch()
+    {
+        // Nothing is left to do once the target is reached
+        let reached = setup(9, Some((10, 2)), 0);
+
+        let (result, ramp_state, commissions) = ramp_step_from(&reached, Some((99, 9)));
+
+        assert_eq!((result, ramp_state.last_step, commissions), (None, Some((99, 9)), vec![]));
+
+        // The last step took effect in an earlier epoch; this step reaches the target
+        let setup = setup(7, Some((10, 2)), 0);
+
+        let (result, ramp_state, commissions) = ramp_step_from(&setup, Some((98, 7)));
+
+        assert_eq!((result, ramp_state.last_step, commissions), (None, Some((100, 9)), vec![9]));
+    }
+
+    #[test]
+    fn split_lamports_preserves_total()
+    {
+        assert_eq!(split_lamports(1001, &[5000, 5000]), vec![501, 500]);
+        assert_eq!(
+            split_lamports(u64::MAX, &[3333, 3333, 3334]).iter().map(|l| *l as u128).sum::<u128>(),
+            u64::MAX as u128
+        );
+    }
+
+    // Runs a command line as solana-vamp would, making its RPC calls through rpc
+    fn run(
+        rpc : &dyn Rpc,
+        args : &[&str]
+    )
+    {
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        let (fee_payer, rpc_url, _, _, vote_account, command) =
+            args::parse_command_line(args, &Environment::default()).unwrap();
+        run_command(rpc, &rpc_url, fee_payer, vote_account, command);
+    }
+
+    // The command line property tests run sequences of commands, each signed by one of a few keys, and apply the
+    // instructions that they submit to the model of the program, which must reject them only for the program's rules
+
+    const COMMAND_LINE_KEYS : usize = 4;
+
+    const RENT_EXEMPT_MINIMUM : u64 = 2_700_000;
+
+    // (command, signer, target, value): the command is one of those in command_line, or 10 to move to a later epoch;
+    // the signer and target index the keys; and value is any number that the command takes
+    type CommandLineOp = (usize, usize, usize, u64);
+
+    fn command_line(
+        model : &Model,
+        keys : &[KeypairFile],
+        (command, signer, target, value) : CommandLineOp
+    ) -> Vec<String>
+    {
+        let (signer, target, target_pubkey) =
+            (&keys[signer].path, &keys[target].path, keys[target].pubkey().to_string());
+        let args = match command {
+            0 if value == 0 => vec!["enter", "--withdraw-authority", signer, "--administrator", &target_pubkey],
+            0 => vec!["enter", "--withdraw-authority", signer, "--administrator", &target_pubkey, "--max-commission"],
+            1 => vec!["set-leave-epoch", "--withdraw-authority", signer, "--leave-epoch"],
+            2 => vec!["leave", "--withdraw-authority", signer],
+            3 => vec!["set-administrator", "--withdraw-authority", signer, "--administrator", &target_pubkey],
+            4 => {
+                vec!["set-operational-authority", "--administrator", signer, "--operational-authority", &target_pubkey]
+            },
+            5 => vec!["set-rewards-authority", "--administrator", signer, "--rewards-authority", &target_pubkey],
+            6 => vec!["set-vote-authority", "--operational-authority", signer, "--vote-authority", &target_pubkey],
+            7 => vec!["set-validator-identity", "--operational-authority", signer, "--validator-identity", target],
+            8 if value == 0 => {
+                vec!["withdraw", "--rewards-authority", signer, "--recipient", &target_pubkey, "--amount"]
+            },
+            8 => vec!["withdraw", "--rewards-authority", signer, "--recipient", &target_pubkey, "--lamports"],
+            _ => vec!["set-commission", "--rewards-authority", signer, "--commission"]
+        };
+        let value = match command {
+            0 if value == 0 => vec![],
+            0 => vec![
+                (value % 101).to_string(),
+                "--max-commission-increase-per-epoch".to_string(),
+                (value % 11).to_string(),
+            ],
+            1 => vec![(model.epoch + (value % 5)).to_string()],
+            8 if value == 0 => vec!["ALL".to_string()],
+            8 => vec![value.to_string()],
+            9 => vec![(value % 101).to_string()],
+            _ => vec![]
+        };
+        args.into_iter()
+            .map(String::from)
+            .chain(value)
+            .chain(["--vote-account".to_string(), model.vote_account.to_string()])
+            .collect()
+    }
+
+    // The key which must sign a command for the program to accept it, or None if the vote account is not managed (or
+    // for enter, if it is)
+    fn required_signer(
+        model : &Model,
+        command : usize
+    ) -> Option<Pubkey>
+    {
+        match (command, &model.manager) {
+            (0, None) => Some(model.vote.authorized_withdrawer),
+            (0, Some(_)) | (_, None) => None,
+            (1..=3, Some(manager)) => Some(manager.withdraw_authority),
+            (4 | 5, Some(manager)) => Some(manager.administrator),
+            (6 | 7, Some(manager)) => Some(manager.operational_authority),
+            (_, Some(manager)) => Some(manager.rewards_authority)
+        }
+    }
+
+    // The instructions of a transaction, with each account marked as a signer if the transaction requires its
+    // signature
+    fn instructions(tx : &Transaction) -> Vec<Instruction>
+    {
+        let message = &tx.message;
+        message
+            .instructions
+            .iter()
+            .map(|compiled| Instruction {
+                program_id : message.account_keys[compiled.program_id_index as usize],
+                accounts : compiled
+                    .accounts
+                    .iter()
+                    .map(|index| AccountMeta {
+                        pubkey : message.account_keys[*index as usize],
+                        is_signer : message.is_signer(*index as usize),
+                        is_writable : message.is_writable(*index as usize)
+                    })
+                    .collect(),
+                data : compiled.data.clone()
+            })
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn command_lines_submit_instructions_the_program_accepts(
+            commission in 0_u8..=100,
+            ops in proptest::collection::vec(
+                (0_usize..11, 0..COMMAND_LINE_KEYS, 0..COMMAND_LINE_KEYS, 0_u64..3_000_000),
+                1..20
+            )
+        ) {
+            let keys = [(); COMMAND_LINE_KEYS].map(|_| KeypairFile::new());
+            let rpc = MockRpc::new();
+            let mut model = Model::new(
+                Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap(),
+                Pubkey::new_unique(),
+                100,
+                VoteAccountModel {
+                    node_pubkey : keys[0].pubkey(),
+                    authorized_voter : keys[0].pubkey(),
+                    authorized_withdrawer : keys[0].pubkey(),
+                    commission,
+                    lamports : RENT_EXEMPT_MINIMUM + 2_000_000,
+                    rent_exempt_minimum : RENT_EXEMPT_MINIMUM
+                }
+            );
+
+            for op in ops {
+                let (command, signer, target, value) = op;
+
+                if command == 10 {
+                    model.advance_to_epoch(model.epoch + (value % 4));
+                    continue;
+                }
+
+                run(&rpc, &command_line(&model, &keys, op).iter().map(String::as_str).collect::<Vec<&str>>());
+
+                let tx = rpc.sent.borrow().last().unwrap().clone();
+                prop_assert!(tx.verify().is_ok());
+
+                let instructions = instructions(&tx);
+                prop_assert_eq!(instructions.len(), 1);
+
+                let before = model.clone();
+                let (signer, target) = (keys[signer].pubkey(), keys[target].pubkey());
+
+                match model.process(&instructions[0]) {
+                    Err(ModelError::MissingSignature(_)) => {
+                        prop_assert_ne!(required_signer(&before, command), Some(signer))
+                    },
+                    Err(e @ (ModelError::WrongProgram | ModelError::InvalidInstructionData(_) |
+                        ModelError::InvalidAccounts(_))) => prop_assert!(false, "{} for {:?}", e, op),
+                    // Rejected for one of the program's rules
+                    Err(_) => prop_assert_eq!(&model, &before),
+                    Ok(lamports) => {
+                        prop_assert_eq!(required_signer(&before, command), Some(signer));
+
+                        let manager = model.manager.clone();
+                        match command {
+                            0 => {
+                                let manager = manager.unwrap();
+                                prop_assert_eq!(manager.administrator, target);
+                                let caps = (value != 0).then_some(((value % 101) as u8, (value % 11) as u8));
+                                prop_assert_eq!(manager.commission_caps, caps);
+                            },
+                            1 => prop_assert_eq!(manager.unwrap().leave_epoch, before.epoch + (value % 5)),
+                            2 => {
+                                prop_assert!(manager.is_none());
+                                prop_assert_eq!(model.vote.authorized_withdrawer, signer);
+                            },
+                            3 => prop_assert_eq!(manager.unwrap().administrator, target),
+                            4 => prop_assert_eq!(manager.unwrap().operational_authority, target),
+                            5 => prop_assert_eq!(manager.unwrap().rewards_authority, target),
+                            6 => prop_assert_eq!(model.vote.authorized_voter, target),
+                            7 => prop_assert_eq!(model.vote.node_pubkey, target),
+                            8 => prop_assert_eq!(lamports, if value == 0 { before.withdrawable() } else { value }),
+                            _ => prop_assert_eq!(model.vote.commission, (value % 101) as u8)
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // The bank tests run commands end-to-end against the program, as tests/program.rs does for its instructions, and
+    // like those do nothing unless VAMP_PROGRAM_SO is set.  Any command which fails exits the test process.
+
+    fn bank_manager_state(
+        rpc : &BankRpc,
+        vote_account : &Pubkey
+    ) -> ManagerState
+    {
+        let program_id = Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap();
+        let manager = get_vote_account_manager_state_account(&program_id, vote_account);
+        ManagerState::decode(&rpc.get_account(&manager).unwrap().data).unwrap()
+    }
+
+    fn vote_state(
+        rpc : &BankRpc,
+        vote_account : &Pubkey
+    ) -> VoteState
+    {
+        VoteState::deserialize(&rpc.get_account(vote_account).unwrap().data).unwrap()
+    }
+
+    #[test]
+    fn commands_manage_a_vote_account_in_the_bank()
+    {
+        let Some(rpc) = BankRpc::start(Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap())
+        else {
+            return;
+        };
+        let [fee_payer, withdraw_authority, administrator, operational_authority, rewards_authority, identity, stake] =
+            [(); 7].map(|_| KeypairFile::new());
+        rpc.fund(&fee_payer.pubkey(), VOTE_ACCOUNT_LAMPORTS);
+        let vote_account =
+            rpc.create_vote_account(&Keypair::new(), withdraw_authority.pubkey(), 5, VOTE_ACCOUNT_LAMPORTS);
+
+        let (vote_account_arg, fee_payer_arg) = (vote_account.to_string(), fee_payer.path.as_str());
+        let command = |args : &[&str]| {
+            run(&rpc, &[args, &["--vote-account", &vote_account_arg, "--fee-payer", fee_payer_arg]].concat())
+        };
+
+        command(&[
+            "enter",
+            "--withdraw-authority",
+            &withdraw_authority.path,
+            "--administrator",
+            &withdraw_authority.pubkey().to_string(),
+            "--max-commission",
+            "10",
+            "--max-commission-increase-per-epoch",
+            "2"
+        ]);
+        assert_eq!(bank_manager_state(&rpc, &vote_account).commission_caps, Some((10, 2)));
+
+        command(&[
+            "set-administrator",
+            "--withdraw-authority",
+            &withdraw_authority.path,
+            "--administrator",
+            &administrator.pubkey().to_string()
+        ]);
+        command(&[
+            "set-operational-authority",
+            "--administrator",
+            &administrator.path,
+            "--operational-authority",
+            &operational_authority.pubkey().to_string()
+        ]);
+        command(&[
+            "set-rewards-authority",
+            "--administrator",
+            &administrator.path,
+            "--rewards-authority",
+            &rewards_authority.pubkey().to_string()
+        ]);
+
+        let state = bank_manager_state(&rpc, &vote_account);
+        assert_eq!(state.withdraw_authority, withdraw_authority.pubkey());
+        assert_eq!(state.administrator, administrator.pubkey());
+        assert_eq!(state.operational_authority, operational_authority.pubkey());
+        assert_eq!(state.rewards_authority, rewards_authority.pubkey());
+
+        let vote_authority = Pubkey::new_unique();
+        command(&[
+            "set-vote-authority",
+            "--operational-authority",
+            &operational_authority.path,
+            "--vote-authority",
+            &vote_authority.to_string()
+        ]);
+        command(&[
+            "set-validator-identity",
+            "--operational-authority",
+            &operational_authority.path,
+            "--validator-identity",
+            &identity.path
+        ]);
+        command(&["set-commission", "--rewards-authority", &rewards_authority.path, "--commission", "7"]);
+
+        let vote = vote_state(&rpc, &vote_account);
+        assert_eq!(vote.node_pubkey, identity.pubkey());
+        assert_eq!(vote.commission, 7);
+
+        let recipient = Pubkey::new_unique();
+        command(&[
+            "withdraw",
+            "--rewards-authority",
+            &rewards_authority.path,
+            "--recipient",
+            &recipient.to_string(),
+            "--amount",
+            "1"
+        ]);
+        assert_eq!(rpc.get_account(&recipient).unwrap().lamports, amount::LAMPORTS_PER_SOL);
+
+        command(&[
+            "withdraw-and-stake",
+            "--rewards-authority",
+            &rewards_authority.path,
+            "--amount",
+            "2",
+            "--stake-account",
+            &stake.path
+        ]);
+        let stake_account = rpc.get_account(&stake.pubkey()).unwrap();
+        assert_eq!(stake_account.owner, stake::program::id());
+        assert_eq!(stake_account.lamports, 2 * amount::LAMPORTS_PER_SOL);
+
+        let epoch = rpc.get_epoch_info().unwrap().epoch;
+        command(&[
+            "set-leave-epoch",
+            "--withdraw-authority",
+            &withdraw_authority.path,
+            "--leave-epoch",
+            &(epoch + 2).to_string()
+        ]);
+        assert_eq!(bank_manager_state(&rpc, &vote_account).leave_epoch, epoch + 2);
+
+        rpc.warp_to_epoch(epoch + 2);
+        command(&["leave", "--withdraw-authority", &withdraw_authority.path]);
+        assert_eq!(vote_state(&rpc, &vote_account).authorized_withdrawer, withdraw_authority.pubkey());
+    }
+
+    #[test]
+    fn batch_and_rotate_submit_several_changes_in_the_bank()
+    {
+        let Some(rpc) = BankRpc::start(Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap())
+        else {
+            return;
+        };
+        let [fee_payer, withdraw_authority, administrator] = [(); 3].map(|_| KeypairFile::new());
+        rpc.fund(&fee_payer.pubkey(), VOTE_ACCOUNT_LAMPORTS);
+        let vote_account =
+            rpc.create_vote_account(&Keypair::new(), withdraw_authority.pubkey(), 5, VOTE_ACCOUNT_LAMPORTS);
+        let operational_authority = Pubkey::new_unique();
+
+        let script = std::env::temp_dir().join(format!("solana-vamp-test-{}.vamp", vote_account));
+        std::fs::write(
+            &script,
+            format!(
+                "enter --withdraw-authority {} --vote-account {} --administrator {}\n\
+                 set-operational-authority --administrator {} --vote-account {} --operational-authority {}\n",
+                withdraw_authority.path,
+                vote_account,
+                administrator.pubkey(),
+                administrator.path,
+                vote_account,
+                operational_authority
+            )
+        )
+        .unwrap();
+
+        run(&rpc, &["batch", script.to_str().unwrap(), "--fee-payer", &fee_payer.path]);
+        std::fs::remove_file(&script).unwrap();
+
+        let state = bank_manager_state(&rpc, &vote_account);
+        assert_eq!(state.administrator, administrator.pubkey());
+        assert_eq!(state.operational_authority, operational_authority);
+
+        let (rewards_authority, operational_authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        run(&rpc, &[
+            "rotate",
+            "--vote-account",
+            &vote_account.to_string(),
+            "--new-rewards-authority",
+            &rewards_authority.to_string(),
+            "--new-operational-authority",
+            &operational_authority.to_string(),
+            "--administrator",
+            &administrator.path,
+            "--fee-payer",
+            &fee_payer.path
+        ]);
+
+        let state = bank_manager_state(&rpc, &vote_account);
+        assert_eq!(state.rewards_authority, rewards_authority);
+        assert_eq!(state.operational_authority, operational_authority);
+    }
+
+    fn manager_state(
+        commission_caps : Option<(u8, u8)>,
+        leave_epoch : u64
+    ) -> ManagerState
+    {
+        ManagerState {
+            withdraw_authority : Pubkey::new_unique(),
+            administrator : Pubkey::new_unique(),
+            operational_authority : Pubkey::new_unique(),
+            rewards_authority : Pubkey::new_unique(),
+            commission_caps,
+            leave_epoch
+        }
+    }
+
+    #[test]
+    fn manager_state_json_includes_only_the_fields_that_are_set()
+    {
+        let account = Pubkey::new_unique();
+
+        let state = manager_state(None, 0);
+
+        assert_eq!(
+            manager_state_json(&account, &state),
+            format!(
+                "\"manager_account_pubkey\":\"{}\",\"withdraw_authority\":\"{}\",\"administrator\":\"{}\",\
+                 \"operational_authority\":\"{}\",\"rewards_authority\":\"{}\"",
+                account,
+                state.withdraw_authority,
+                state.administrator,
+                state.operational_authority,
+                state.rewards_authority
+            )
+        );
+
+        let state = manager_state(Some((10, 2)), 500);
+
+        assert!(manager_state_json(&account, &state)
+            .ends_with(",\"max_commission\":10,\"max_commission_increase_per_epoch\":2,\"leave_epoch\":500"));
+    }
+
+    #[test]
+    fn manager_state_account_is_derived_from_the_vote_account()
+    {
+        let program_id = Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap();
+
+        let (vote_account, other_vote_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(
+            get_vote_account_manager_state_account(&program_id, &vote_account),
+            Pubkey::find_program_address(&[vote_account.as_ref()], &program_id).0
+        );
+        assert_ne!(
+            get_vote_account_manager_state_account(&program_id, &vote_account),
+            get_vote_account_manager_state_account(&program_id, &other_vote_account)
+        );
+    }
+
+    #[test]
+    fn amounts_resolve_against_withdrawable_lamports()
+    {
+        assert_eq!(resolve_amount(Amount::All, 5000), Ok(5000));
+        assert_eq!(resolve_amount(Amount::AllBut(1000), 5000), Ok(4000));
+        assert_eq!(
+            resolve_amount(Amount::AllBut(5000), 5000),
+            Err("Only 0.000005 SOL can be withdrawn, which is not more than 0.000005 SOL".to_string())
+        );
+        // Only ALL means everything; an exact amount is never widened to it
+        assert_eq!(resolve_amount(Amount::Lamports(0), 5000), Ok(0));
+        assert_eq!(resolve_amount(Amount::Lamports(12345), 5000), Ok(12345));
+    }
+
+    #[test]
+    fn formats_utc_timestamps()
+    {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(format_utc(-1), "1969-12-31T23:59:59Z");
+    }
+}
@@ -0,0 +1,69 @@
+// The solana-vamp man page, generated from the command specs and their help text
+
+use crate::spec::{usage_message, COMMANDS};
+
+pub fn man_page() -> String
+{
+    let mut page = format!(
+        ".TH SOLANA-VAMP 1 \"\" \"solana-vamp {}\" \"User Commands\"\n.SH NAME\nsolana-vamp \\- interact with the \
+         Solana Vote Account Manager program\n.SH SYNOPSIS\n.B solana-vamp\n.I COMMAND\n[\\fIARGUMENTS\\fR]\n.SH \
+         DESCRIPTION\n",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    page.push_str(&preformatted(&usage_message()));
+
+    page.push_str(".SH COMMANDS\n");
+
+    for spec in COMMANDS {
+        page.push_str(&format!(".SS {}\n", spec.name));
+        page.push_str(&preformatted(&spec.help_message()));
+    }
+
+    page
+}
+
+// The help text is already laid out for an 80 column terminal, so it is shown as is rather than filled by roff
+fn preformatted(text : &str) -> String
+{
+    let mut roff = String::from(".nf\n");
+
+    for line in text.trim_matches('\n').lines() {
+        let line = line.replace('\\', "\\e");
+        // A line beginning with . or ' would be taken as a request
+        if line.starts_with('.') || line.starts_with('\'') {
+            roff.push_str("\\&");
+        }
+        roff.push_str(&line);
+        roff.push('\n');
+    }
+
+    roff.push_str(".fi\n");
+
+    roff
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn every_command_has_a_section()
+    {
+        let page = man_page();
+
+        for spec in COMMANDS {
+            assert!(page.contains(&format!("\n.SS {}\n", spec.name)), "man page lacks {}", spec.name);
+        }
+
+        // The only requests are those that structure the page; every line of help text is escaped
+        for line in page.lines().filter(|line| line.starts_with('.') || line.starts_with('\'')) {
+            let request = line.split_whitespace().next().unwrap();
+            assert!([".TH", ".SH", ".SS", ".B", ".I", ".nf", ".fi"].contains(&request), "{}", line);
+        }
+
+        assert!(page.contains("\\&'solana-vamp enter' initiates"));
+        assert!(page.contains("--max-commission-increase-per-epoch 3"));
+    }
+}
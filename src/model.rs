@@ -0,0 +1,344 @@
+// A model of the rules that the vamp program enforces, for checking instructions without a validator.  The model
+// holds the state of a single vote account and its manager state, and applies instructions to it the way the program
+// would, rejecting those that the program would reject.
+
+use crate::instruction::get_vote_account_manager_state_account;
+use crate::transaction_data::VampInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{system_program, sysvar, vote};
+
+// The vote program does not allow commission above 100%
+pub const MAX_COMMISSION : u8 = 100;
+
+// The reasons that the program rejects an instruction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModelError
+{
+    // The instruction is for a different program
+    WrongProgram,
+
+    InvalidInstructionData(String),
+
+    // The accounts are not the accounts that the instruction requires, in the order that it requires them
+    InvalidAccounts(&'static str),
+
+    // The required authority did not sign
+    MissingSignature(&'static str),
+
+    AlreadyManaged,
+
+    NotManaged,
+
+    // The leave epoch is not at least 2 epochs past the current epoch
+    LeaveEpochTooSoon,
+
+    // Commission caps are in use and the leave epoch has not been set or has not been reached
+    LeaveNotAllowed,
+
+    // Commission changes are not allowed once a leave epoch has been set
+    LeaveEpochSet,
+
+    CommissionTooHigh,
+
+    // Withdrawing would take the vote account below its rent exempt minimum
+    InsufficientFunds
+}
+
+impl std::fmt::Display for ModelError
+{
+    fn fmt(
+        &self,
+        f : &mut std::fmt::Formatter
+    ) -> std::fmt::Result
+    {
+        match self {
+            ModelError::WrongProgram => write!(f, "Instruction is not for the Vote Account Manager program"),
+            ModelError::InvalidInstructionData(e) => write!(f, "Invalid instruction data: {}", e),
+            ModelError::InvalidAccounts(e) => write!(f, "Invalid accounts: {}", e),
+            ModelError::MissingSignature(authority) => write!(f, "Missing signature of the {}", authority),
+            ModelError::AlreadyManaged => write!(f, "Vote account is already managed by the program"),
+            ModelError::NotManaged => write!(f, "Vote account is not managed by the program"),
+            ModelError::LeaveEpochTooSoon => write!(f, "Leave epoch must be at least 2 epochs past the current epoch"),
+            ModelError::LeaveNotAllowed => write!(f, "Leave is not allowed until the leave epoch"),
+            ModelError::LeaveEpochSet => write!(f, "Commission cannot be changed once a leave epoch has been set"),
+            ModelError::CommissionTooHigh => write!(f, "Commission exceeds the commission caps"),
+            ModelError::InsufficientFunds => {
+                write!(f, "Withdraw would leave the vote account below rent exempt minimum")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteAccountModel
+{
+    pub node_pubkey : Pubkey,
+
+    pub authorized_voter : Pubkey,
+
+    pub authorized_withdrawer : Pubkey,
+
+    pub commission : u8,
+
+    pub lamports : u64,
+
+    pub rent_exempt_minimum : u64
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManagerModel
+{
+    pub withdraw_authority : Pubkey,
+
+    pub administrator : Pubkey,
+
+    pub operational_authority : Pubkey,
+
+    pub rewards_authority : Pubkey,
+
+    // (max_commission, max_commission_increase_per_epoch), if commission caps are in use
+    pub commission_caps : Option<(u8, u8)>,
+
+    // 0 if no leave epoch has been set
+    pub leave_epoch : u64
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Model
+{
+    pub program_id : Pubkey,
+
+    pub vote_account : Pubkey,
+
+    pub epoch : u64,
+
+    pub vote : VoteAccountModel,
+
+    // None if the vote account is not managed by the program
+    pub manager : Option<ManagerModel>,
+
+    // The commission that was in effect when the current epoch began
+    pub epoch_start_commission : u8
+}
+
+impl Model
+{
+    pub fn new(
+        program_id : Pubkey,
+        vote_account : Pubkey,
+        epoch : u64,
+        vote : VoteAccountModel
+    ) -> Self
+    {
+        let epoch_start_commission = vote.commission;
+
+        Model { program_id, vote_account, epoch, vote, manager : None, epoch_start_commission }
+    }
+
+    pub fn manager_state_account(&self) -> Pubkey
+    {
+        get_vote_account_manager_state_account(&self.program_id, &self.vote_account)
+    }
+
+    // Moves to a later epoch
+    pub fn advance_to_epoch(
+        &mut self,
+        epoch : u64
+    )
+    {
+        if epoch > self.epoch {
+            self.epoch = epoch;
+            self.epoch_start_commission = self.vote.commission;
+        }
+    }
+
+    // Returns the number of lamports that can be withdrawn from the vote account
+    pub fn withdrawable(&self) -> u64
+    {
+        self.vote.lamports.saturating_sub(self.vote.rent_exempt_minimum)
+    }
+
+    // Returns the highest commission that set-commission would currently accept
+    pub fn max_allowed_commission(&self) -> Option<u8>
+    {
+        let manager = self.manager.as_ref()?;
+
+        if manager.leave_epoch != 0 {
+            return None;
+        }
+
+        Some(match manager.commission_caps {
+            Some((max_commission, max_commission_increase_per_epoch)) => std::cmp::max(
+                self.vote.commission,
+                std::cmp::min(
+                    std::cmp::min(max_commission, MAX_COMMISSION),
+                    self.epoch_start_commission.saturating_add(max_commission_increase_per_epoch)
+                )
+            ),
+            None => MAX_COMMISSION
+        })
+    }
+
+    // Applies an instruction, leaving the model unchanged if the program would reject it.  Returns the number of
+    // lamports moved out of the vote account.
+    pub fn process(
+        &mut self,
+        instruction : &Instruction
+    ) -> Result<u64, ModelError>
+    {
+        if instruction.program_id != self.program_id {
+            return Err(ModelError::WrongProgram);
+        }
+
+        let data = VampInstruction::decode(&instruction.data).map_err(ModelError::InvalidInstructionData)?;
+
+        let (count, well_known) : (usize, &[(usize, Pubkey)]) = match data {
+            VampInstruction::Enter { .. } => {
+                (7, &[(4, system_program::ID), (5, vote::program::ID), (6, sysvar::clock::ID)])
+            },
+            VampInstruction::SetLeaveEpoch { .. } => (3, &[]),
+            VampInstruction::Leave => (6, &[(4, vote::program::ID), (5, sysvar::clock::ID)]),
+            VampInstruction::SetAdministrator { .. } |
+            VampInstruction::SetOperationalAuthority { .. } |
+            VampInstruction::SetRewardsAuthority { .. } => (3, &[]),
+            VampInstruction::SetVoteAuthority { .. } => (5, &[(3, vote::program::ID), (4, sysvar::clock::ID)]),
+            VampInstruction::SetValidatorIdentity => (5, &[(4, vote::program::ID)]),
+            VampInstruction::Withdraw { .. } => (5, &[(4, vote::program::ID)]),
+            VampInstruction::SetCommission { .. } => (4, &[(3, vote::program::ID)])
+        };
+
+        let accounts = &instruction.accounts;
+
+        if accounts.len() < count {
+            return Err(ModelError::InvalidAccounts("too few accounts"));
+        }
+
+        if accounts[0].pubkey != self.manager_state_account() {
+            return Err(ModelError::InvalidAccounts("not the manager state account of the vote account"));
+        }
+
+        if accounts[1].pubkey != self.vote_account {
+            return Err(ModelError::InvalidAccounts("not the vote account"));
+        }
+
+        if well_known.iter().any(|(index, pubkey)| accounts[*index].pubkey != *pubkey) {
+            return Err(ModelError::InvalidAccounts("program or sysvar account out of place"));
+        }
+
+        // Account 2 is the authority for every instruction except enter, in which it is the funding account and
+        // account 3 is the authority
+        let authority = &accounts[if let VampInstruction::Enter { .. } = data { 3 } else { 2 }];
+
+        let require = |expected : Pubkey, role : &'static str| {
+            if authority.is_signer && (authority.pubkey == expected) {
+                Ok(())
+            }
+            else {
+                Err(ModelError::MissingSignature(role))
+            }
+        };
+
+        if let VampInstruction::Enter {
+            administrator,
+            use_commission_caps,
+            max_commission,
+            max_commission_increase_per_epoch
+        } = data
+        {
+            if self.manager.is_some() {
+                return Err(ModelError::AlreadyManaged);
+            }
+            if !accounts[2].is_signer {
+                return Err(ModelError::MissingSignature("funding account"));
+            }
+            require(self.vote.authorized_withdrawer, "withdraw authority")?;
+            let administrator = Pubkey::new_from_array(administrator);
+            self.manager = Some(ManagerModel {
+                withdraw_authority : self.vote.authorized_withdrawer,
+                administrator,
+                operational_authority : administrator,
+                rewards_authority : administrator,
+                commission_caps : use_commission_caps.then_some((max_commission, max_commission_increase_per_epoch)),
+                leave_epoch : 0
+            });
+            self.vote.authorized_withdrawer = self.manager_state_account();
+            return Ok(0);
+        }
+
+        let epoch = self.epoch;
+
+        let max_allowed_commission = self.max_allowed_commission();
+
+        let withdrawable = self.withdrawable();
+
+        let manager = self.manager.as_mut().ok_or(ModelError::NotManaged)?;
+
+        match data {
+            VampInstruction::Enter { .. } => unreachable!(),
+            VampInstruction::SetLeaveEpoch { leave_epoch } => {
+                require(manager.withdraw_authority, "withdraw authority")?;
+                if leave_epoch < epoch.saturating_add(2) {
+                    return Err(ModelError::LeaveEpochTooSoon);
+                }
+                manager.leave_epoch = leave_epoch;
+            },
+            VampInstruction::Leave => {
+                require(manager.withdraw_authority, "withdraw authority")?;
+                if manager.commission_caps.is_some() && ((manager.leave_epoch == 0) || (epoch < manager.leave_epoch)) {
+                    return Err(ModelError::LeaveNotAllowed);
+                }
+                self.vote.authorized_withdrawer = manager.withdraw_authority;
+                self.manager = None;
+            },
+            VampInstruction::SetAdministrator { new_administrator } => {
+                require(manager.withdraw_authority, "withdraw authority")?;
+                manager.administrator = Pubkey::new_from_array(new_administrator);
+            },
+            VampInstruction::SetOperationalAuthority { new_authority } => {
+                require(manager.administrator, "administrator")?;
+                manager.operational_authority = Pubkey::new_from_array(new_authority);
+            },
+            VampInstruction::SetRewardsAuthority { new_authority } => {
+                require(manager.administrator, "administrator")?;
+                manager.rewards_authority = Pubkey::new_from_array(new_authority);
+            },
+            VampInstruction::SetVoteAuthority { new_authority } => {
+                require(manager.operational_authority, "operational authority")?;
+                self.vote.authorized_voter = Pubkey::new_from_array(new_authority);
+            },
+            VampInstruction::SetValidatorIdentity => {
+                require(manager.operational_authority, "operational authority")?;
+                if !accounts[3].is_signer {
+                    return Err(ModelError::MissingSignature("new validator identity"));
+                }
+                self.vote.node_pubkey = accounts[3].pubkey;
+            },
+            VampInstruction::Withdraw { lamports } => {
+                require(manager.rewards_authority, "rewards authority")?;
+                // 0 lamports means everything that can be withdrawn
+                let lamports = if lamports == 0 { withdrawable } else { lamports };
+                if lamports > withdrawable {
+                    return Err(ModelError::InsufficientFunds);
+                }
+                self.vote.lamports -= lamports;
+                return Ok(lamports);
+            },
+            VampInstruction::SetCommission { new_commission } => {
+                require(manager.rewards_authority, "rewards authority")?;
+                match max_allowed_commission {
+                    None => return Err(ModelError::LeaveEpochSet),
+                    Some(max_allowed_commission) => {
+                        // Decreases are always allowed
+                        if (new_commission > self.vote.commission) && (new_commission > max_allowed_commission) {
+                            return Err(ModelError::CommissionTooHigh);
+                        }
+                    }
+                }
+                self.vote.commission = new_commission;
+            }
+        }
+
+        Ok(0)
+    }
+}
@@ -0,0 +1,197 @@
+// Persistent progress of a ramp-commission run.  The state file is written before each set-commission transaction
+// is submitted, so that a restarted run can tell whether the step for the current epoch has already been taken.
+
+use crate::error_exit;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub struct RampState
+{
+    pub vote_account : Pubkey,
+
+    pub target : u8,
+
+    // The epoch in which the most recent set-commission step was submitted, and the commission it set
+    pub last_step : Option<(u64, u8)>
+}
+
+impl RampState
+{
+    pub fn new(
+        vote_account : Pubkey,
+        target : u8
+    ) -> Self
+    {
+        RampState { vote_account, target, last_step : None }
+    }
+
+    // Returns None if the state file does not exist
+    pub fn load(path : &str) -> Option<RampState>
+    {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => error_exit(&format!("Failed to read ramp state file {}: {}", path, e))
+        };
+
+        Some(Self::parse(&contents).unwrap_or_else(|e| error_exit(&format!("Invalid ramp state file {}: {}", path, e))))
+    }
+
+    pub fn save(
+        &self,
+        path : &str
+    )
+    {
+        // Write to a temporary file and rename it into place so that an interrupted write never leaves a truncated
+        // state file behind
+        let tmp_path = format!("{}.tmp", path);
+
+        std::fs::write(&tmp_path, self.to_string())
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .unwrap_or_else(|e| error_exit(&format!("Failed to write ramp state file {}: {}", path, e)));
+    }
+
+    // A state file left by a ramp of another vote account, or to another commission, is not resumed
+    pub fn check(
+        &self,
+        vote_account : &Pubkey,
+        target : u8,
+        path : &str
+    ) -> Result<(), String>
+    {
+        if (self.vote_account != *vote_account) || (self.target != target) {
+            return Err(format!(
+                "Ramp state file {} is for a ramp of {} to commission {}; remove it to start a new ramp",
+                path, self.vote_account, self.target
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn parse(contents : &str) -> Result<RampState, String>
+    {
+        let mut vote_account = None;
+
+        let mut target = None;
+
+        let mut last_epoch = None;
+
+        let mut last_commission = None;
+
+        for line in contents.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once('=').ok_or(format!("Malformed line: {}", line))?;
+            let value = value.trim();
+            match key.trim() {
+                "vote_account" => vote_account = Some(Pubkey::from_str(value).map_err(|e| e.to_string())?),
+                "target" => target = Some(value.parse::<u8>().map_err(|e| e.to_string())?),
+                "last_epoch" => last_epoch = Some(value.parse::<u64>().map_err(|e| e.to_string())?),
+                "last_commission" => last_commission = Some(value.parse::<u8>().map_err(|e| e.to_string())?),
+                key => return Err(format!("Unknown key: {}", key))
+            }
+        }
+
+        Ok(RampState {
+            vote_account : vote_account.ok_or("Missing vote_account")?,
+            target : target.ok_or("Missing target")?,
+            last_step : match (last_epoch, last_commission) {
+                (Some(last_epoch), Some(last_commission)) => Some((last_epoch, last_commission)),
+                (None, None) => None,
+                _ => return Err("last_epoch and last_commission must be supplied together".to_string())
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for RampState
+{
+    fn fmt(
+        &self,
+        f : &mut std::fmt::Formatter
+    ) -> std::fmt::Result
+    {
+        writeln!(f, "vote_account={}", self.vote_account)?;
+        writeln!(f, "target={}", self.target)?;
+        if let Some((last_epoch, last_commission)) = self.last_step {
+            writeln!(f, "last_epoch={}", last_epoch)?;
+            writeln!(f, "last_commission={}", last_commission)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn state_round_trips_through_its_file_format()
+    {
+        let mut state = RampState::new(Pubkey::new_unique(), 10);
+
+        assert_eq!(RampState::parse(&state.to_string()), Ok(RampState::new(state.vote_account, 10)));
+
+        state.last_step = Some((500, 7));
+
+        assert_eq!(
+            state.to_string(),
+            format!("vote_account={}\ntarget=10\nlast_epoch=500\nlast_commission=7\n", state.vote_account)
+        );
+        assert_eq!(RampState::parse(&state.to_string()), Ok(state));
+    }
+
+    #[test]
+    fn malformed_state_is_rejected()
+    {
+        let vote_account = Pubkey::new_unique();
+
+        let parse = |contents : String| RampState::parse(&contents).err();
+
+        // Blank lines and spacing around keys and values are allowed
+        assert_eq!(parse(format!("\n vote_account = {}\n\ntarget= 10\n", vote_account)), None);
+
+        assert_eq!(
+            parse(format!("vote_account={}\ntarget\n", vote_account)),
+            Some("Malformed line: target".to_string())
+        );
+        assert_eq!(
+            parse(format!("vote_account={}\ntarget=10\nstep=1\n", vote_account)),
+            Some("Unknown key: step".to_string())
+        );
+        assert_eq!(parse("target=10\n".to_string()), Some("Missing vote_account".to_string()));
+        assert_eq!(parse(format!("vote_account={}\n", vote_account)), Some("Missing target".to_string()));
+        assert!(parse(format!("vote_account={}\ntarget=1000\n", vote_account)).is_some());
+        assert!(parse("vote_account=not-a-pubkey\ntarget=10\n".to_string()).is_some());
+    }
+
+    #[test]
+    fn state_of_another_ramp_is_not_resumed()
+    {
+        let state = RampState::new(Pubkey::new_unique(), 10);
+
+        assert_eq!(state.check(&state.vote_account, 10, "ramp"), Ok(()));
+
+        let expected = Err(format!(
+            "Ramp state file ramp is for a ramp of {} to commission 10; remove it to start a new ramp",
+            state.vote_account
+        ));
+
+        assert_eq!(state.check(&Pubkey::new_unique(), 10, "ramp"), expected);
+        assert_eq!(state.check(&state.vote_account, 9, "ramp"), expected);
+    }
+
+    #[test]
+    fn last_epoch_and_last_commission_are_supplied_together()
+    {
+        let vote_account = Pubkey::new_unique();
+
+        for step in ["last_epoch=500\n", "last_commission=7\n"] {
+            assert_eq!(
+                RampState::parse(&format!("vote_account={}\ntarget=10\n{}", vote_account, step)),
+                Err("last_epoch and last_commission must be supplied together".to_string())
+            );
+        }
+    }
+}
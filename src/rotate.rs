@@ -0,0 +1,460 @@
+// Rotation of any of the authorities of a managed vote account, for 'solana-vamp rotate'.  Authorities are rotated top
+// down: administrator, rewards authority, operational authority, vote authority, and then validator identity.  Each
+// rotation is signed by the authority above it as it is at that point, so that a newly set administrator or
+// operational authority signs the rotations beneath it.  The rotations are submitted as a batch, or exported as a
+// batch script.
+
+use crate::args::{BatchStep, Command};
+use crate::{load_keypair_helper, make_pubkey_helper};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_vamp::state::ManagerState;
+
+// The authorities to rotate to, and the keypair files of the current authorities that may be needed to sign
+#[derive(Debug, Default)]
+pub struct Rotation
+{
+    pub withdraw_authority : Option<String>,
+
+    pub administrator : Option<String>,
+
+    pub operational_authority : Option<String>,
+
+    pub new_administrator : Option<String>,
+
+    pub new_rewards_authority : Option<String>,
+
+    pub new_operational_authority : Option<String>,
+
+    pub new_vote_authority : Option<String>,
+
+    pub new_validator_identity : Option<String>
+}
+
+// An authority as it is at some point during the rotation, with the keypair file that can sign as it, if known
+struct Authority
+{
+    name : &'static str,
+
+    // The argument that gives the keypair file
+    flag : &'static str,
+
+    pubkey : Pubkey,
+
+    keypair : Option<String>,
+
+    // Whether the authority is set by the rotation
+    new : bool
+}
+
+impl Authority
+{
+    fn current(
+        name : &'static str,
+        flag : &'static str,
+        pubkey : Pubkey,
+        keypair : &Option<String>
+    ) -> Self
+    {
+        Authority { name, flag, pubkey, keypair : keypair.clone(), new : false }
+    }
+
+    // An authority set by the rotation, which may be given as a pubkey or as a keypair file
+    fn new(
+        name : &'static str,
+        flag : &'static str,
+        value : &str
+    ) -> Result<Self, String>
+    {
+        let pubkey = make_pubkey_helper(value).map_err(|e| format!("Invalid {} {}: {}", name, value, e))?;
+
+        let keypair = load_keypair_helper(value).ok().map(|_| value.to_string());
+
+        Ok(Authority { name, flag, pubkey, keypair, new : true })
+    }
+
+    // The keypair file with which this authority signs the rotation of another
+    fn signer(
+        &self,
+        rotating : &str
+    ) -> Result<String, String>
+    {
+        match &self.keypair {
+            Some(path) => {
+                let pubkey = load_keypair_helper(path)?.pubkey();
+                if pubkey != self.pubkey {
+                    return Err(format!(
+                        "--{} {} holds {}, but the {} is {}",
+                        self.flag, path, pubkey, self.name, self.pubkey
+                    ));
+                }
+                Ok(path.clone())
+            },
+            // Setting an authority that then could not sign would leave the rotations beneath it unsignable
+            None if self.new => Err(format!(
+                "The {} must sign the rotation of the {}, so --{} must be a keypair file rather than a pubkey",
+                self.name, rotating, self.flag
+            )),
+            None => Err(format!(
+                "The {} must sign the rotation of the {}, so --{} must be supplied",
+                self.name, rotating, self.flag
+            ))
+        }
+    }
+}
+
+// Works out the commands that perform a rotation, in order, each with a description
+pub fn plan(
+    rotation : &Rotation,
+    vote_account : &Pubkey,
+    manager_state : &ManagerState
+) -> Result<Vec<(String, BatchStep)>, String>
+{
+    let vote_account = vote_account.to_string();
+
+    let withdraw_authority = Authority::current(
+        "withdraw authority",
+        "withdraw-authority",
+        manager_state.withdraw_authority,
+        &rotation.withdraw_authority
+    );
+
+    let mut administrator =
+        Authority::current("administrator", "administrator", manager_state.administrator, &rotation.administrator);
+
+    let mut operational_authority = Authority::current(
+        "operational authority",
+        "operational-authority",
+        manager_state.operational_authority,
+        &rotation.operational_authority
+    );
+
+    let mut steps = vec![];
+
+    let mut add = |role : &str, signer : &Authority, new : &Authority, command : Command| {
+        steps.push((
+            format!("Rotating the {} to {}, signed by the {}", role, new.pubkey, signer.name),
+            (Some(command_signer(&command).to_string()), vote_account.clone(), command)
+        ));
+    };
+
+    if let Some(value) = &rotation.new_administrator {
+        let new = Authority::new("new administrator", "new-administrator", value)?;
+        let command = Command::SetAdministrator {
+            withdraw_authority : withdraw_authority.signer("administrator")?,
+            administrator : value.clone()
+        };
+        add("administrator", &withdraw_authority, &new, command);
+        administrator = new;
+    }
+
+    if let Some(value) = &rotation.new_rewards_authority {
+        let new = Authority::new("new rewards authority", "new-rewards-authority", value)?;
+        let command = Command::SetRewardsAuthority {
+            administrator : administrator.signer("rewards authority")?,
+            authority : value.clone()
+        };
+        add("rewards authority", &administrator, &new, command);
+    }
+
+    if let Some(value) = &rotation.new_operational_authority {
+        let new = Authority::new("new operational authority", "new-operational-authority", value)?;
+        let command = Command::SetOperationalAuthority {
+            administrator : administrator.signer("operational authority")?,
+            authority : value.clone()
+        };
+        add("operational authority", &administrator, &new, command);
+        operational_authority = new;
+    }
+
+    if let Some(value) = &rotation.new_vote_authority {
+        let new = Authority::new("new vote authority", "new-vote-authority", value)?;
+        let command = Command::SetVoteAuthority {
+            operational_authority : operational_authority.signer("vote authority")?,
+            authority : value.clone()
+        };
+        add("vote authority", &operational_authority, &new, command);
+    }
+
+    if let Some(value) = &rotation.new_validator_identity {
+        // The new validator identity signs its own rotation
+        let new = Authority::new("new validator identity", "new-validator-identity", value)?;
+        new.signer("validator identity")?;
+        let command = Command::SetValidatorIdentity {
+            operational_authority : operational_authority.signer("validator identity")?,
+            validator_identity : value.clone()
+        };
+        add("validator identity", &operational_authority, &new, command);
+    }
+
+    Ok(steps)
+}
+
+// The keypair file of the authority that signs a command, which pays for it if it is run by itself
+fn command_signer(command : &Command) -> &str
+{
+    match command {
+        Command::SetAdministrator { withdraw_authority, .. } => withdraw_authority,
+        Command::SetRewardsAuthority { administrator, .. } | Command::SetOperationalAuthority { administrator, .. } => {
+            administrator
+        },
+        Command::SetVoteAuthority { operational_authority, .. } |
+        Command::SetValidatorIdentity { operational_authority, .. } => operational_authority,
+        command => unreachable!("{:?} is not a rotation", command)
+    }
+}
+
+// Writes the commands of a rotation as a batch script, to be submitted by 'solana-vamp batch', beneath a comment
+// naming the command that wrote it
+pub fn export(
+    steps : &[(String, BatchStep)],
+    path : &str,
+    written_by : &str
+) -> Result<(), String>
+{
+    let mut script = format!("# Authority rotation written by 'solana-vamp {}'\n", written_by);
+
+    for (description, (_, vote_account, command)) in steps {
+        let (name, args) = match command {
+            Command::SetAdministrator { withdraw_authority, administrator } => {
+                ("set-administrator", [("withdraw-authority", withdraw_authority), ("administrator", administrator)])
+            },
+            Command::SetRewardsAuthority { administrator, authority } => {
+                ("set-rewards-authority", [("administrator", administrator), ("rewards-authority", authority)])
+            },
+            Command::SetOperationalAuthority { administrator, authority } => {
+                ("set-operational-authority", [("administrator", administrator), ("operational-authority", authority)])
+            },
+            Command::SetVoteAuthority { operational_authority, authority } => ("set-vote-authority", [
+                ("operational-authority", operational_authority),
+                ("vote-authority", authority)
+            ]),
+            Command::SetValidatorIdentity { operational_authority, validator_identity } => {
+                ("set-validator-identity", [
+                    ("operational-authority", operational_authority),
+                    ("validator-identity", validator_identity)
+                ])
+            },
+            command => unreachable!("{:?} is not a rotation", command)
+        };
+
+        // Batch scripts separate arguments with spaces, so values cannot contain them
+        if let Some((_, value)) = args.iter().find(|(_, value)| value.contains(char::is_whitespace)) {
+            return Err(format!("Cannot export {} to a batch script because it contains a space", value));
+        }
+
+        script.push_str(&format!("\n# {}\n{} --vote-account {}", description, name, vote_account));
+
+        for (flag, value) in args {
+            script.push_str(&format!(" --{} {}", flag, value));
+        }
+
+        script.push('\n');
+    }
+
+    std::fs::write(path, script).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::args::parse_batch_script;
+    use crate::rpc::mock::KeypairFile;
+
+    struct Setup
+    {
+        manager_state : ManagerState,
+
+        vote_account : Pubkey,
+
+        // Keypair files of the current authorities, and of some new ones
+        withdraw_authority : KeypairFile,
+
+        administrator : KeypairFile,
+
+        operational_authority : KeypairFile,
+
+        new_administrator : KeypairFile,
+
+        new_operational_authority : KeypairFile,
+
+        new_validator_identity : KeypairFile
+    }
+
+    fn setup() -> Setup
+    {
+        let withdraw_authority = KeypairFile::new();
+        let administrator = KeypairFile::new();
+        let operational_authority = KeypairFile::new();
+
+        Setup {
+            manager_state : ManagerState {
+                withdraw_authority : withdraw_authority.pubkey(),
+                administrator : administrator.pubkey(),
+                operational_authority : operational_authority.pubkey(),
+                rewards_authority : administrator.pubkey(),
+                commission_caps : None,
+                leave_epoch : 0
+            },
+            vote_account : Pubkey::new_unique(),
+            withdraw_authority,
+            administrator,
+            operational_authority,
+            new_administrator : KeypairFile::new(),
+            new_operational_authority : KeypairFile::new(),
+            new_validator_identity : KeypairFile::new()
+        }
+    }
+
+    fn commands(steps : &[(String, BatchStep)]) -> Vec<String>
+    {
+        steps.iter().map(|(_, (_, _, command))| format!("{:?}", command)).collect()
+    }
+
+    #[test]
+    fn rotations_are_signed_by_the_authority_in_effect()
+    {
+        let setup = setup();
+        let rewards_authority = Pubkey::new_unique().to_string();
+        let vote_authority = Pubkey::new_unique().to_string();
+
+        // Everything is rotated, so the new administrator and operational authority sign beneath themselves
+        let rotation = Rotation {
+            withdraw_authority : Some(setup.withdraw_authority.path.clone()),
+            new_administrator : Some(setup.new_administrator.path.clone()),
+            new_rewards_authority : Some(rewards_authority.clone()),
+            new_operational_authority : Some(setup.new_operational_authority.path.clone()),
+            new_vote_authority : Some(vote_authority.clone()),
+            new_validator_identity : Some(setup.new_validator_identity.path.clone()),
+            ..Rotation::default()
+        };
+
+        let steps = plan(&rotation, &setup.vote_account, &setup.manager_state).unwrap();
+
+        assert_eq!(commands(&steps), vec![
+            format!(
+                "SetAdministrator {{ withdraw_authority: {:?}, administrator: {:?} }}",
+                setup.withdraw_authority.path, setup.new_administrator.path
+            ),
+            format!(
+                "SetRewardsAuthority {{ administrator: {:?}, authority: {:?} }}",
+                setup.new_administrator.path, rewards_authority
+            ),
+            format!(
+                "SetOperationalAuthority {{ administrator: {:?}, authority: {:?} }}",
+                setup.new_administrator.path, setup.new_operational_authority.path
+            ),
+            format!(
+                "SetVoteAuthority {{ operational_authority: {:?}, authority: {:?} }}",
+                setup.new_operational_authority.path, vote_authority
+            ),
+            format!(
+                "SetValidatorIdentity {{ operational_authority: {:?}, validator_identity: {:?} }}",
+                setup.new_operational_authority.path, setup.new_validator_identity.path
+            ),
+        ]);
+
+        // Each step is paid for by its signer if run by itself
+        assert!(steps.iter().all(|(_, (fee_payer, vote_account, command))| {
+            (fee_payer.as_deref() == Some(command_signer(command))) && (*vote_account == setup.vote_account.to_string())
+        }));
+
+        assert_eq!(
+            steps[1].0,
+            format!("Rotating the rewards authority to {}, signed by the new administrator", rewards_authority)
+        );
+
+        // Without a new administrator, the current one signs
+        let rotation = Rotation {
+            administrator : Some(setup.administrator.path.clone()),
+            new_rewards_authority : Some(rewards_authority.clone()),
+            ..Rotation::default()
+        };
+
+        assert_eq!(commands(&plan(&rotation, &setup.vote_account, &setup.manager_state).unwrap()), vec![format!(
+            "SetRewardsAuthority {{ administrator: {:?}, authority: {:?} }}",
+            setup.administrator.path, rewards_authority
+        )]);
+    }
+
+    #[test]
+    fn rotations_which_could_not_be_signed_are_refused()
+    {
+        let setup = setup();
+
+        let refused = |rotation : Rotation| plan(&rotation, &setup.vote_account, &setup.manager_state).unwrap_err();
+
+        // A new administrator given only as a pubkey could not sign the rotation of the operational authority
+        assert_eq!(
+            refused(Rotation {
+                withdraw_authority : Some(setup.withdraw_authority.path.clone()),
+                new_administrator : Some(Pubkey::new_unique().to_string()),
+                new_operational_authority : Some(Pubkey::new_unique().to_string()),
+                ..Rotation::default()
+            }),
+            "The new administrator must sign the rotation of the operational authority, so --new-administrator must \
+             be a keypair file rather than a pubkey"
+        );
+
+        assert_eq!(
+            refused(Rotation { new_vote_authority : Some(Pubkey::new_unique().to_string()), ..Rotation::default() }),
+            "The operational authority must sign the rotation of the vote authority, so --operational-authority \
+             must be supplied"
+        );
+
+        assert_eq!(
+            refused(Rotation {
+                operational_authority : Some(setup.operational_authority.path.clone()),
+                new_validator_identity : Some(Pubkey::new_unique().to_string()),
+                ..Rotation::default()
+            }),
+            "The new validator identity must sign the rotation of the validator identity, so \
+             --new-validator-identity must be a keypair file rather than a pubkey"
+        );
+
+        // The keypair of a current authority must hold that authority
+        assert!(refused(Rotation {
+            administrator : Some(setup.operational_authority.path.clone()),
+            new_rewards_authority : Some(Pubkey::new_unique().to_string()),
+            ..Rotation::default()
+        })
+        .starts_with(&format!("--administrator {} holds", setup.operational_authority.path)));
+    }
+
+    #[test]
+    fn exported_rotations_are_batch_scripts()
+    {
+        let setup = setup();
+
+        let rotation = Rotation {
+            withdraw_authority : Some(setup.withdraw_authority.path.clone()),
+            new_administrator : Some(setup.new_administrator.path.clone()),
+            new_operational_authority : Some(Pubkey::new_unique().to_string()),
+            ..Rotation::default()
+        };
+
+        let steps = plan(&rotation, &setup.vote_account, &setup.manager_state).unwrap();
+
+        let path = std::env::temp_dir().join(format!("solana-vamp-rotate-test-export-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        export(&steps, path, "rotate").unwrap();
+
+        let script = parse_batch_script(path, None).unwrap();
+
+        // Fee payers are not compared, since the script's may be taken from the environment of the test
+        assert_eq!(
+            script
+                .iter()
+                .map(|(_, vote_account, command)| format!("{} {:?}", vote_account, command))
+                .collect::<Vec<String>>(),
+            steps
+                .iter()
+                .map(|(_, (_, vote_account, command))| format!("{} {:?}", vote_account, command))
+                .collect::<Vec<String>>()
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,858 @@
+// The RPC calls that solana-vamp makes, as a trait so that commands can be run against an in-memory mock as well as
+// against a real RPC node
+
+// ClientError is large, but it is the error type that RpcClient returns
+#![allow(clippy::result_large_err)]
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::Result as ClientResult;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_response::{RpcConfirmedTransactionStatusWithSignature, RpcSimulateTransactionResult};
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::epoch_info::EpochInfo;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+
+pub trait Rpc
+{
+    fn commitment(&self) -> CommitmentConfig;
+
+    fn get_account(
+        &self,
+        pubkey : &Pubkey
+    ) -> ClientResult<Account>;
+
+    fn get_multiple_accounts(
+        &self,
+        pubkeys : &[Pubkey]
+    ) -> ClientResult<Vec<Option<Account>>>;
+
+    // The accounts owned by a program which pass every filter
+    fn get_program_accounts_with_filters(
+        &self,
+        program_id : &Pubkey,
+        filters : Vec<RpcFilterType>
+    ) -> ClientResult<Vec<(Pubkey, Account)>>;
+
+    fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len : usize
+    ) -> ClientResult<u64>;
+
+    fn get_stake_minimum_delegation(&self) -> ClientResult<u64>;
+
+    fn get_epoch_info(&self) -> ClientResult<EpochInfo>;
+
+    fn get_latest_blockhash(&self) -> ClientResult<Hash>;
+
+    fn simulate_transaction(
+        &self,
+        tx : &Transaction
+    ) -> ClientResult<RpcSimulateTransactionResult>;
+
+    fn send_and_confirm_transaction(
+        &self,
+        tx : &Transaction
+    ) -> ClientResult<Signature>;
+
+    // Same as send_and_confirm_transaction, but shows progress on the terminal while waiting for confirmation
+    fn send_and_confirm_transaction_with_spinner(
+        &self,
+        tx : &Transaction
+    ) -> ClientResult<Signature>
+    {
+        self.send_and_confirm_transaction(tx)
+    }
+
+    fn get_signatures_for_address_with_config(
+        &self,
+        address : &Pubkey,
+        config : GetConfirmedSignaturesForAddress2Config
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+
+    fn get_transaction_with_config(
+        &self,
+        signature : &Signature,
+        config : RpcTransactionConfig
+    ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta>;
+}
+
+impl Rpc for RpcClient
+{
+    fn commitment(&self) -> CommitmentConfig
+    {
+        RpcClient::commitment(self)
+    }
+
+    fn get_account(
+        &self,
+        pubkey : &Pubkey
+    ) -> ClientResult<Account>
+    {
+        RpcClient::get_account(self, pubkey)
+    }
+
+    fn get_multiple_accounts(
+        &self,
+        pubkeys : &[Pubkey]
+    ) -> ClientResult<Vec<Option<Account>>>
+    {
+        RpcClient::get_multiple_accounts(self, pubkeys)
+    }
+
+    fn get_program_accounts_with_filters(
+        &self,
+        program_id : &Pubkey,
+        filters : Vec<RpcFilterType>
+    ) -> ClientResult<Vec<(Pubkey, Account)>>
+    {
+        // Without an encoding, account data is returned as base58, which is not allowed for accounts larger than 128
+        // bytes
+        RpcClient::get_program_accounts_with_config(self, program_id, RpcProgramAccountsConfig {
+            filters : Some(filters),
+            account_config : RpcAccountInfoConfig {
+                encoding : Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        })
+    }
+
+    fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len : usize
+    ) -> ClientResult<u64>
+    {
+        RpcClient::get_minimum_balance_for_rent_exemption(self, data_len)
+    }
+
+    fn get_stake_minimum_delegation(&self) -> ClientResult<u64>
+    {
+        RpcClient::get_stake_minimum_delegation(self)
+    }
+
+    fn get_epoch_info(&self) -> ClientResult<EpochInfo>
+    {
+        RpcClient::get_epoch_info(self)
+    }
+
+    fn get_latest_blockhash(&self) -> ClientResult<Hash>
+    {
+        RpcClient::get_latest_blockhash(self)
+    }
+
+    fn simulate_transaction(
+        &self,
+        tx : &Transaction
+    ) -> ClientResult<RpcSimulateTransactionResult>
+    {
+        RpcClient::simulate_transaction(self, tx).map(|response| response.value)
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        tx : &Transaction
+    ) -> ClientResult<Signature>
+    {
+        RpcClient::send_and_confirm_transaction(self, tx)
+    }
+
+    fn send_and_confirm_transaction_with_spinner(
+        &self,
+        tx : &Transaction
+    ) -> ClientResult<Signature>
+    {
+        RpcClient::send_and_confirm_transaction_with_spinner(self, tx)
+    }
+
+    fn get_signatures_for_address_with_config(
+        &self,
+        address : &Pubkey,
+        config : GetConfirmedSignaturesForAddress2Config
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>>
+    {
+        RpcClient::get_signatures_for_address_with_config(self, address, config)
+    }
+
+    fn get_transaction_with_config(
+        &self,
+        signature : &Signature,
+        config : RpcTransactionConfig
+    ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta>
+    {
+        RpcClient::get_transaction_with_config(self, signature, config)
+    }
+}
+
+#[cfg(test)]
+pub mod mock
+{
+    // An in-memory stand-in for an RPC node.  Accounts, the current epoch, and past transactions are set up by the
+    // test; submitted transactions are recorded rather than executed.
+
+    use super::*;
+    use solana_client::client_error::{ClientError, ClientErrorKind};
+    use solana_sdk::account::AccountSharedData;
+    use solana_sdk::clock::Clock;
+    use solana_sdk::rent::Rent;
+    use solana_sdk::signer::keypair::{write_keypair_file, Keypair};
+    use solana_sdk::signer::Signer;
+    use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+    use solana_sdk::vote;
+    use solana_sdk::vote::state::{VoteInit, VoteState, VoteStateVersions};
+    use solana_transaction_status::{EncodedTransaction, EncodedTransactionWithStatusMeta, TransactionBinaryEncoding};
+    use solana_vamp::state::MANAGER_STATE_SIZE;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    const SLOTS_PER_EPOCH : u64 = 432_000;
+
+    #[derive(Default)]
+    pub struct MockRpc
+    {
+        pub accounts : RefCell<HashMap<Pubkey, Account>>,
+
+        pub epoch : RefCell<u64>,
+
+        pub stake_minimum_delegation : u64,
+
+        // Transactions with the slot they were included in, oldest first
+        pub transactions : RefCell<Vec<(u64, VersionedTransaction)>>,
+
+        // Every transaction that has been submitted, in the order submitted
+        pub sent : RefCell<Vec<Transaction>>,
+
+        // The error and logs returned by simulate_transaction
+        pub simulation_err : Option<TransactionError>,
+
+        pub simulation_logs : Vec<String>
+    }
+
+    impl MockRpc
+    {
+        pub fn new() -> Self
+        {
+            MockRpc { stake_minimum_delegation : 1, ..MockRpc::default() }
+        }
+
+        pub fn set_account(
+            &self,
+            pubkey : Pubkey,
+            account : Account
+        )
+        {
+            self.accounts.borrow_mut().insert(pubkey, account);
+        }
+
+        pub fn set_epoch(
+            &self,
+            epoch : u64
+        )
+        {
+            *self.epoch.borrow_mut() = epoch;
+        }
+
+        // Sets up a vote account whose identity, voter, and withdrawer are all authority
+        pub fn set_vote_account(
+            &self,
+            vote_account : Pubkey,
+            authority : Pubkey,
+            commission : u8,
+            lamports : u64
+        )
+        {
+            self.set_vote_account_roles(vote_account, [authority; 3], commission, lamports);
+        }
+
+        // As set_vote_account, but with the identity, voter, and withdrawer given separately, in that order
+        pub fn set_vote_account_roles(
+            &self,
+            vote_account : Pubkey,
+            [node_pubkey, authorized_voter, authorized_withdrawer] : [Pubkey; 3],
+            commission : u8,
+            lamports : u64
+        )
+        {
+            let vote_init = VoteInit { node_pubkey, authorized_voter, authorized_withdrawer, commission };
+
+            let vote_state = VoteState::new(&vote_init, &Clock::default());
+
+            let mut data = vec![0_u8; VoteState::size_of()];
+
+            VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data).unwrap();
+
+            self.set_account(vote_account, Account {
+                lamports,
+                data,
+                owner : vote::program::id(),
+                ..Account::default()
+            });
+        }
+
+        // Sets up a manager state account in which authority holds every role
+        pub fn set_manager_state(
+            &self,
+            program_id : Pubkey,
+            manager_state_account : Pubkey,
+            authority : Pubkey,
+            commission_caps : Option<(u8, u8)>,
+            leave_epoch : u64
+        )
+        {
+            self.set_manager_state_roles(
+                program_id,
+                manager_state_account,
+                [authority; 4],
+                commission_caps,
+                leave_epoch
+            );
+        }
+
+        // As set_manager_state, but with the withdraw authority, administrator, operational authority and rewards
+        // authority given separately, in that order
+        pub fn set_manager_state_roles(
+            &self,
+            program_id : Pubkey,
+            manager_state_account : Pubkey,
+            roles : [Pubkey; 4],
+            commission_caps : Option<(u8, u8)>,
+            leave_epoch : u64
+        )
+        {
+            let mut data = vec![0_u8; MANAGER_STATE_SIZE];
+
+            for (i, role) in roles.iter().enumerate() {
+                data[(i * 32)..((i + 1) * 32)].copy_from_slice(role.as_ref());
+            }
+
+            if let Some((max_commission, max_commission_increase_per_epoch)) = commission_caps {
+                data[128] = 1;
+                data[129] = max_commission;
+                data[130] = max_commission_increase_per_epoch;
+            }
+
+            data[144..152].copy_from_slice(&leave_epoch.to_le_bytes());
+
+            self.set_account(manager_state_account, Account {
+                lamports : Rent::default().minimum_balance(MANAGER_STATE_SIZE),
+                data,
+                owner : program_id,
+                ..Account::default()
+            });
+        }
+
+        pub fn add_transaction(
+            &self,
+            slot : u64,
+            transaction : VersionedTransaction
+        )
+        {
+            self.transactions.borrow_mut().push((slot, transaction));
+        }
+    }
+
+    fn error(msg : String) -> ClientError
+    {
+        ClientErrorKind::Custom(msg).into()
+    }
+
+    impl Rpc for MockRpc
+    {
+        fn commitment(&self) -> CommitmentConfig
+        {
+            CommitmentConfig::default()
+        }
+
+        fn get_account(
+            &self,
+            pubkey : &Pubkey
+        ) -> ClientResult<Account>
+        {
+            self.accounts.borrow().get(pubkey).cloned().ok_or_else(|| error(format!("AccountNotFound: {}", pubkey)))
+        }
+
+        fn get_multiple_accounts(
+            &self,
+            pubkeys : &[Pubkey]
+        ) -> ClientResult<Vec<Option<Account>>>
+        {
+            Ok(pubkeys.iter().map(|pubkey| self.accounts.borrow().get(pubkey).cloned()).collect())
+        }
+
+        fn get_program_accounts_with_filters(
+            &self,
+            program_id : &Pubkey,
+            filters : Vec<RpcFilterType>
+        ) -> ClientResult<Vec<(Pubkey, Account)>>
+        {
+            Ok(self
+                .accounts
+                .borrow()
+                .iter()
+                .filter(|(_, account)| {
+                    (account.owner == *program_id) &&
+                        filters.iter().all(|filter| filter.allows(&AccountSharedData::from(Account::clone(account))))
+                })
+                .map(|(pubkey, account)| (*pubkey, account.clone()))
+                .collect())
+        }
+
+        fn get_minimum_balance_for_rent_exemption(
+            &self,
+            data_len : usize
+        ) -> ClientResult<u64>
+        {
+            Ok(Rent::default().minimum_balance(data_len))
+        }
+
+        fn get_stake_minimum_delegation(&self) -> ClientResult<u64>
+        {
+            Ok(self.stake_minimum_delegation)
+        }
+
+        fn get_epoch_info(&self) -> ClientResult<EpochInfo>
+        {
+            let epoch = *self.epoch.borrow();
+
+            Ok(EpochInfo {
+                epoch,
+                slot_index : 0,
+                slots_in_epoch : SLOTS_PER_EPOCH,
+                absolute_slot : epoch * SLOTS_PER_EPOCH,
+                block_height : epoch * SLOTS_PER_EPOCH,
+                transaction_count : None
+            })
+        }
+
+        // A new blockhash after every submitted transaction, so that repeated transactions have distinct signatures
+        fn get_latest_blockhash(&self) -> ClientResult<Hash>
+        {
+            Ok(Hash::new_from_array([(self.sent.borrow().len() % 256) as u8; 32]))
+        }
+
+        fn simulate_transaction(
+            &self,
+            _tx : &Transaction
+        ) -> ClientResult<RpcSimulateTransactionResult>
+        {
+            Ok(RpcSimulateTransactionResult {
+                err : self.simulation_err.clone(),
+                logs : Some(self.simulation_logs.clone()),
+                accounts : None,
+                units_consumed : None,
+                return_data : None
+            })
+        }
+
+        fn send_and_confirm_transaction(
+            &self,
+            tx : &Transaction
+        ) -> ClientResult<Signature>
+        {
+            self.sent.borrow_mut().push(tx.clone());
+
+            Ok(tx.signatures[0])
+        }
+
+        fn get_signatures_for_address_with_config(
+            &self,
+            address : &Pubkey,
+            config : GetConfirmedSignaturesForAddress2Config
+        ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>>
+        {
+            Ok(self
+                .transactions
+                .borrow()
+                .iter()
+                .rev()
+                .filter(|(_, transaction)| transaction.message.static_account_keys().contains(address))
+                .skip_while(|(_, transaction)| {
+                    config.before.map(|before| before != transaction.signatures[0]).unwrap_or(false)
+                })
+                .skip(config.before.map(|_| 1).unwrap_or(0))
+                .take(config.limit.unwrap_or(1000))
+                .map(|(slot, transaction)| RpcConfirmedTransactionStatusWithSignature {
+                    signature : transaction.signatures[0].to_string(),
+                    slot : *slot,
+                    err : None,
+                    memo : None,
+                    block_time : None,
+                    confirmation_status : None
+                })
+                .collect())
+        }
+
+        fn get_transaction_with_config(
+            &self,
+            signature : &Signature,
+            _config : RpcTransactionConfig
+        ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta>
+        {
+            self.transactions
+                .borrow()
+                .iter()
+                .find(|(_, transaction)| transaction.signatures[0] == *signature)
+                .map(|(slot, transaction)| EncodedConfirmedTransactionWithStatusMeta {
+                    slot : *slot,
+                    transaction : EncodedTransactionWithStatusMeta {
+                        transaction : EncodedTransaction::Binary(
+                            base64::encode(bincode::serialize(transaction).unwrap()),
+                            TransactionBinaryEncoding::Base64
+                        ),
+                        meta : None,
+                        version : None
+                    },
+                    block_time : None
+                })
+                .ok_or_else(|| error(format!("Transaction {} not found", signature)))
+        }
+    }
+
+    // A new keypair written to a keypair file, as commands take their signers, which is removed when dropped.  The
+    // file is named by the pubkey, so that tests running in parallel never share one.
+    pub struct KeypairFile
+    {
+        pub keypair : Keypair,
+
+        pub path : String
+    }
+
+    impl KeypairFile
+    {
+        pub fn new() -> Self
+        {
+            let keypair = Keypair::new();
+            let path = std::env::temp_dir().join(format!("solana-vamp-test-{}.json", keypair.pubkey()));
+            write_keypair_file(&keypair, &path).unwrap();
+            KeypairFile { keypair, path : path.to_str().unwrap().to_string() }
+        }
+
+        pub fn pubkey(&self) -> Pubkey
+        {
+            self.keypair.pubkey()
+        }
+    }
+
+    impl Drop for KeypairFile
+    {
+        fn drop(&mut self)
+        {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod bank
+{
+    // The RPC calls made against a solana-program-test bank with the Vote Account Manager program loaded, so that
+    // commands can be run end-to-end.  The program is loaded from the shared object file named by the VAMP_PROGRAM_SO
+    // environment variable; the tests which use this skip themselves when it is not set.  Transaction history and
+    // program account queries are not supported.
+
+    use super::*;
+    use solana_client::client_error::{ClientError, ClientErrorKind};
+    use solana_program_test::{ProgramTest, ProgramTestContext};
+    use solana_sdk::bpf_loader;
+    use solana_sdk::clock::Clock;
+    use solana_sdk::rent::Rent;
+    use solana_sdk::signer::keypair::Keypair;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::stake::instruction as stake_instruction;
+    use solana_sdk::vote::instruction as vote_instruction;
+    use solana_sdk::vote::state::{VoteInit, VoteState};
+    use std::cell::RefCell;
+    use tokio::runtime::Runtime;
+
+    pub struct BankRpc
+    {
+        runtime : Runtime,
+
+        context : RefCell<ProgramTestContext>
+    }
+
+    fn error(msg : String) -> ClientError
+    {
+        ClientErrorKind::Custom(msg).into()
+    }
+
+    impl BankRpc
+    {
+        // Starts a bank with the program loaded at program_id, or returns None if VAMP_PROGRAM_SO is not set
+        pub fn start(program_id : Pubkey) -> Option<Self>
+        {
+            let Ok(path) = std::env::var("VAMP_PROGRAM_SO")
+            else {
+                eprintln!("VAMP_PROGRAM_SO is not set; skipping");
+                return None;
+            };
+
+            let data = std::fs::read(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+
+            let mut program_test = ProgramTest::default();
+
+            program_test.add_account(program_id, Account {
+                lamports : Rent::default().minimum_balance(data.len()),
+                data,
+                owner : bpf_loader::id(),
+                executable : true,
+                rent_epoch : 0
+            });
+
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+            let context = runtime.block_on(program_test.start_with_context());
+
+            Some(BankRpc { runtime, context : RefCell::new(context) })
+        }
+
+        // Creates a vote account whose identity and voter are identity, funded with lamports beyond its rent exempt
+        // minimum
+        pub fn create_vote_account(
+            &self,
+            identity : &Keypair,
+            withdraw_authority : Pubkey,
+            commission : u8,
+            lamports : u64
+        ) -> Pubkey
+        {
+            let vote_account = Keypair::new();
+
+            let mut context = self.context.borrow_mut();
+
+            let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+            let instructions = vote_instruction::create_account(
+                &payer.pubkey(),
+                &vote_account.pubkey(),
+                &VoteInit {
+                    node_pubkey : identity.pubkey(),
+                    authorized_voter : identity.pubkey(),
+                    authorized_withdrawer : withdraw_authority,
+                    commission
+                },
+                Rent::default().minimum_balance(VoteState::size_of()) + lamports
+            );
+
+            let tx = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &[&payer, &vote_account, identity],
+                context.last_blockhash
+            );
+
+            self.runtime.block_on(context.banks_client.process_transaction(tx)).unwrap();
+
+            vote_account.pubkey()
+        }
+
+        // Funds an account from the bank's payer, as fee payers must be
+        pub fn fund(
+            &self,
+            pubkey : &Pubkey,
+            lamports : u64
+        )
+        {
+            let mut context = self.context.borrow_mut();
+
+            let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+            let tx = Transaction::new_signed_with_payer(
+                &[solana_sdk::system_instruction::transfer(&payer.pubkey(), pubkey, lamports)],
+                Some(&payer.pubkey()),
+                &[&payer],
+                context.last_blockhash
+            );
+
+            self.runtime.block_on(context.banks_client.process_transaction(tx)).unwrap();
+        }
+
+        pub fn warp_to_epoch(
+            &self,
+            epoch : u64
+        )
+        {
+            let mut context = self.context.borrow_mut();
+
+            let slot = context.genesis_config().epoch_schedule.get_first_slot_in_epoch(epoch);
+
+            context.warp_to_slot(slot).unwrap();
+        }
+    }
+
+    impl Rpc for BankRpc
+    {
+        fn commitment(&self) -> CommitmentConfig
+        {
+            CommitmentConfig::default()
+        }
+
+        fn get_account(
+            &self,
+            pubkey : &Pubkey
+        ) -> ClientResult<Account>
+        {
+            self.get_multiple_accounts(&[*pubkey])?
+                .remove(0)
+                .ok_or_else(|| error(format!("AccountNotFound: {}", pubkey)))
+        }
+
+        fn get_multiple_accounts(
+            &self,
+            pubkeys : &[Pubkey]
+        ) -> ClientResult<Vec<Option<Account>>>
+        {
+            let mut context = self.context.borrow_mut();
+
+            pubkeys
+                .iter()
+                .map(|pubkey| {
+                    self.runtime.block_on(context.banks_client.get_account(*pubkey)).map_err(|e| error(e.to_string()))
+                })
+                .collect()
+        }
+
+        fn get_program_accounts_with_filters(
+            &self,
+            _program_id : &Pubkey,
+            _filters : Vec<RpcFilterType>
+        ) -> ClientResult<Vec<(Pubkey, Account)>>
+        {
+            Err(error("getProgramAccounts is not supported by the bank".to_string()))
+        }
+
+        fn get_minimum_balance_for_rent_exemption(
+            &self,
+            data_len : usize
+        ) -> ClientResult<u64>
+        {
+            let mut context = self.context.borrow_mut();
+
+            let rent = self.runtime.block_on(context.banks_client.get_rent()).map_err(|e| error(e.to_string()))?;
+
+            Ok(rent.minimum_balance(data_len))
+        }
+
+        // As RpcClient does, by simulating the stake program's GetMinimumDelegation instruction
+        fn get_stake_minimum_delegation(&self) -> ClientResult<u64>
+        {
+            let mut context = self.context.borrow_mut();
+
+            let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+            let tx = Transaction::new_signed_with_payer(
+                &[stake_instruction::get_minimum_delegation()],
+                Some(&payer.pubkey()),
+                &[&payer],
+                context.last_blockhash
+            );
+
+            let simulation = self
+                .runtime
+                .block_on(context.banks_client.simulate_transaction(tx))
+                .map_err(|e| error(e.to_string()))?;
+
+            let data = simulation
+                .simulation_details
+                .and_then(|details| details.return_data)
+                .map(|return_data| return_data.data)
+                .ok_or_else(|| error("GetMinimumDelegation returned nothing".to_string()))?;
+
+            // Trailing zero bytes of return data are dropped
+            let mut bytes = [0_u8; 8];
+            bytes[..data.len()].copy_from_slice(&data);
+
+            Ok(u64::from_le_bytes(bytes))
+        }
+
+        fn get_epoch_info(&self) -> ClientResult<EpochInfo>
+        {
+            let mut context = self.context.borrow_mut();
+
+            let clock =
+                self.runtime.block_on(context.banks_client.get_sysvar::<Clock>()).map_err(|e| error(e.to_string()))?;
+
+            let epoch_schedule = &context.genesis_config().epoch_schedule;
+
+            let (epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(clock.slot);
+
+            Ok(EpochInfo {
+                epoch,
+                slot_index,
+                slots_in_epoch : epoch_schedule.get_slots_in_epoch(epoch),
+                absolute_slot : clock.slot,
+                block_height : clock.slot,
+                transaction_count : None
+            })
+        }
+
+        // A new blockhash each time, so that repeating an identical command is not rejected as a duplicate
+        fn get_latest_blockhash(&self) -> ClientResult<Hash>
+        {
+            let mut context = self.context.borrow_mut();
+
+            self.runtime.block_on(context.get_new_latest_blockhash()).map_err(|e| error(e.to_string()))
+        }
+
+        fn simulate_transaction(
+            &self,
+            tx : &Transaction
+        ) -> ClientResult<RpcSimulateTransactionResult>
+        {
+            let mut context = self.context.borrow_mut();
+
+            let simulation = self
+                .runtime
+                .block_on(context.banks_client.simulate_transaction(tx.clone()))
+                .map_err(|e| error(e.to_string()))?;
+
+            let details = simulation.simulation_details;
+
+            Ok(RpcSimulateTransactionResult {
+                err : simulation.result.and_then(|result| result.err()),
+                logs : details.as_ref().map(|details| details.logs.clone()),
+                accounts : None,
+                units_consumed : details.map(|details| details.units_consumed),
+                return_data : None
+            })
+        }
+
+        fn send_and_confirm_transaction(
+            &self,
+            tx : &Transaction
+        ) -> ClientResult<Signature>
+        {
+            let mut context = self.context.borrow_mut();
+
+            self.runtime
+                .block_on(context.banks_client.process_transaction(tx.clone()))
+                .map_err(|e| error(e.to_string()))?;
+
+            Ok(tx.signatures[0])
+        }
+
+        fn get_signatures_for_address_with_config(
+            &self,
+            _address : &Pubkey,
+            _config : GetConfirmedSignaturesForAddress2Config
+        ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>>
+        {
+            Err(error("Transaction history is not supported by the bank".to_string()))
+        }
+
+        fn get_transaction_with_config(
+            &self,
+            _signature : &Signature,
+            _config : RpcTransactionConfig
+        ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta>
+        {
+            Err(error("Transaction history is not supported by the bank".to_string()))
+        }
+    }
+}
@@ -0,0 +1,759 @@
+// Declarative specifications of the commands of solana-vamp.  Each command's spec gives its arguments in positional
+// order, whether each is required, optional, or repeated, and the type of its value.  Command lines are parsed and
+// validated against these specs by args.rs, and the usage messages printed by 'solana-vamp help' (and shown in
+// README.md) are generated from them.
+
+use crate::usage;
+
+// The type of the value of an argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType
+{
+    // The path to a keypair file
+    Keypair,
+
+    // A pubkey, or the path to a keypair file from which the pubkey will be loaded
+    Address,
+
+    // The path to a file which is not a keypair file
+    File,
+
+    // A non-negative integer
+    Integer,
+
+    // A percentage from 0 to 100
+    Percent,
+
+    // An amount of SOL, or ALL, or ALL-BUT <SOL>
+    Amount,
+
+    // An amount of SOL
+    Sol,
+
+    // The name of a profile
+    Profile,
+
+    // Any text
+    Text,
+
+    // No value; the presence of the argument is its value
+    Switch
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Occurs
+{
+    Required,
+
+    Optional,
+
+    // Optional, and may be supplied any number of times
+    Repeated
+}
+
+// How an argument may be supplied on the command line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position
+{
+    // Only as --NAME VALUE
+    Flag,
+
+    // As --NAME VALUE, or positionally after the command name
+    Either,
+
+    // Only positionally after the command name
+    Positional
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ArgSpec
+{
+    // The name of the argument, which is also its flag without the leading --
+    pub name : &'static str,
+
+    pub short : Option<char>,
+
+    // The placeholder for the value shown in usage messages
+    pub value : &'static str,
+
+    pub value_type : ValueType,
+
+    pub occurs : Occurs,
+
+    pub position : Position,
+
+    // Values offered by shell completion, which are not the only values accepted
+    pub suggestions : &'static [&'static str],
+
+    // Whether a value not supplied on the command line is taken from the environment, profile or solana CLI config
+    pub env : bool
+}
+
+// A constraint on which of a set of arguments may be supplied together
+#[derive(Clone, Copy, Debug)]
+pub enum Group
+{
+    // Exactly one of the arguments must be supplied
+    Choice(&'static [&'static str]),
+
+    // At most one of the arguments may be supplied
+    Exclusive(&'static [&'static str]),
+
+    // At least one of the arguments must be supplied
+    AnyOf(&'static [&'static str])
+}
+
+pub struct CommandSpec
+{
+    pub name : &'static str,
+
+    // Shown in the list of commands in the usage message
+    pub summary : &'static str,
+
+    // In positional order
+    pub args : &'static [ArgSpec],
+
+    pub groups : &'static [Group],
+
+    // Whether the command makes requests of an RPC node, and so accepts the global arguments
+    pub rpc : bool,
+
+    // Shown after the generated usage of the command by 'solana-vamp help <COMMAND>'
+    pub help : &'static str
+}
+
+// A required argument which may be supplied as a flag or positionally
+const fn arg(
+    name : &'static str,
+    value : &'static str,
+    value_type : ValueType
+) -> ArgSpec
+{
+    ArgSpec {
+        name,
+        short : None,
+        value,
+        value_type,
+        occurs : Occurs::Required,
+        position : Position::Either,
+        suggestions : &[],
+        env : false
+    }
+}
+
+impl ArgSpec
+{
+    const fn optional(self) -> Self
+    {
+        ArgSpec { occurs : Occurs::Optional, ..self }
+    }
+
+    const fn repeated(self) -> Self
+    {
+        ArgSpec { occurs : Occurs::Repeated, ..self }
+    }
+
+    const fn flag_only(self) -> Self
+    {
+        ArgSpec { position : Position::Flag, ..self }
+    }
+
+    const fn positional_only(self) -> Self
+    {
+        ArgSpec { position : Position::Positional, ..self }
+    }
+
+    const fn short(
+        self,
+        short : char
+    ) -> Self
+    {
+        ArgSpec { short : Some(short), ..self }
+    }
+
+    const fn suggest(
+        self,
+        suggestions : &'static [&'static str]
+    ) -> Self
+    {
+        ArgSpec { suggestions, ..self }
+    }
+
+    const fn env(self) -> Self
+    {
+        ArgSpec { env : true, ..self }
+    }
+
+    pub fn flag(&self) -> String
+    {
+        format!("--{}", self.name)
+    }
+
+    // The environment variable from which the argument may be taken
+    pub fn env_var(&self) -> String
+    {
+        format!("SOLANA_VAMP_{}", self.name.to_uppercase().replace('-', "_"))
+    }
+
+    // The name of the argument as used in error messages
+    pub fn description(&self) -> String
+    {
+        self.name.replace('-', " ")
+    }
+}
+
+// Arguments accepted by every command
+pub const GLOBAL_ARGS : &[ArgSpec] = &[
+    URL,
+    arg("commitment", "COMMITMENT", ValueType::Text).optional().flag_only().short('c').env().suggest(&[
+        "processed",
+        "confirmed",
+        "finalized"
+    ]),
+    arg("profile", "PROFILE", ValueType::Profile).optional().flag_only(),
+    arg("record-rpc", "FILE", ValueType::File).optional().flag_only(),
+    arg("replay-rpc", "FILE", ValueType::File).optional().flag_only()
+];
+
+// The only global argument shown in the usage of each command
+const URL : ArgSpec = arg("url", "RPC_ENDPOINT", ValueType::Text).optional().flag_only().short('u').env().suggest(&[
+    "localhost",
+    "devnet",
+    "testnet",
+    "mainnet"
+]);
+
+const FEE_PAYER : ArgSpec = arg("fee-payer", "FEE_PAYER", ValueType::Keypair).optional().flag_only().short('f').env();
+
+const VOTE_ACCOUNT : ArgSpec = arg("vote-account", "VOTE_ACCOUNT", ValueType::Address).env();
+
+const WITHDRAW_AUTHORITY : ArgSpec = arg("withdraw-authority", "WITHDRAW_AUTHORITY_FILE", ValueType::Keypair).env();
+
+const ADMINISTRATOR : ArgSpec = arg("administrator", "ADMINISTRATOR_FILE", ValueType::Keypair).env();
+
+const OPERATIONAL_AUTHORITY : ArgSpec =
+    arg("operational-authority", "OPERATIONAL_AUTHORITY_FILE", ValueType::Keypair).env();
+
+const REWARDS_AUTHORITY : ArgSpec = arg("rewards-authority", "REWARDS_AUTHORITY", ValueType::Keypair).env();
+
+const AMOUNT : ArgSpec = arg("amount", "SOL_TO_WITHDRAW", ValueType::Amount).optional();
+
+const LAMPORTS : ArgSpec = arg("lamports", "LAMPORTS_TO_WITHDRAW", ValueType::Integer).optional().flag_only();
+
+const POLL_INTERVAL : ArgSpec = arg("poll-interval", "SECONDS", ValueType::Integer).optional().flag_only();
+
+const JSON : ArgSpec = arg("json", "", ValueType::Switch).optional().flag_only();
+
+pub const COMMANDS : &[CommandSpec] = &[
+    CommandSpec {
+        name : "keygen",
+        summary : "To generate the authority keypairs of a new validator",
+        args : &[
+            arg("out-dir", "DIRECTORY", ValueType::File),
+            arg("seed-phrases", "", ValueType::Switch).optional().flag_only()
+        ],
+        groups : &[],
+        rpc : false,
+        help : usage::KEYGEN_HELP
+    },
+    CommandSpec {
+        name : "enter",
+        summary : "To start using VAMP",
+        args : &[
+            WITHDRAW_AUTHORITY,
+            VOTE_ACCOUNT,
+            arg("administrator", "ADMINISTRATOR", ValueType::Address),
+            arg("max-commission", "MAX_COMMISSION", ValueType::Percent).optional(),
+            arg("max-commission-increase-per-epoch", "MAX_INCREASE_PER_EPOCH", ValueType::Percent).optional(),
+            FEE_PAYER,
+            arg("interactive", "", ValueType::Switch).optional().flag_only()
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::ENTER_HELP
+    },
+    CommandSpec {
+        name : "set-leave-epoch",
+        summary : "To set a leave epoch",
+        args : &[WITHDRAW_AUTHORITY, VOTE_ACCOUNT, arg("leave-epoch", "EPOCH", ValueType::Integer), FEE_PAYER],
+        groups : &[],
+        rpc : true,
+        help : usage::SET_LEAVE_EPOCH_HELP
+    },
+    CommandSpec {
+        name : "leave",
+        summary : "To stop using SOLANA-VAMP",
+        args : &[WITHDRAW_AUTHORITY, VOTE_ACCOUNT, FEE_PAYER],
+        groups : &[],
+        rpc : true,
+        help : usage::LEAVE_HELP
+    },
+    CommandSpec {
+        name : "set-administrator",
+        summary : "To set the administrator",
+        args : &[
+            WITHDRAW_AUTHORITY,
+            VOTE_ACCOUNT,
+            arg("administrator", "NEW_ADMINISTRATOR", ValueType::Address),
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::SET_ADMINISTRATOR_HELP
+    },
+    CommandSpec {
+        name : "set-operational-authority",
+        summary : "To set the operational authority",
+        args : &[
+            ADMINISTRATOR,
+            VOTE_ACCOUNT,
+            arg("operational-authority", "OPERATIONAL_AUTHORITY", ValueType::Address),
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::SET_OPERATIONAL_AUTHORITY_HELP
+    },
+    CommandSpec {
+        name : "set-rewards-authority",
+        summary : "To set the rewards authority",
+        args : &[
+            ADMINISTRATOR,
+            VOTE_ACCOUNT,
+            arg("rewards-authority", "REWARDS_AUTHORITY", ValueType::Address),
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::SET_REWARDS_AUTHORITY_HELP
+    },
+    CommandSpec {
+        name : "set-vote-authority",
+        summary : "To set the vote authority",
+        args : &[
+            OPERATIONAL_AUTHORITY,
+            VOTE_ACCOUNT,
+            arg("vote-authority", "NEW_VOTE_AUTHORITY", ValueType::Address),
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::SET_VOTE_AUTHORITY_HELP
+    },
+    CommandSpec {
+        name : "set-validator-identity",
+        summary : "To set the validator identity",
+        args : &[
+            OPERATIONAL_AUTHORITY,
+            VOTE_ACCOUNT,
+            arg("validator-identity", "NEW_VALIDATOR_IDENTITY_FILE", ValueType::Keypair),
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::SET_VALIDATOR_IDENTITY_HELP
+    },
+    CommandSpec {
+        name : "rotate",
+        summary : "To rotate several authorities at once",
+        args : &[
+            VOTE_ACCOUNT,
+            arg("new-administrator", "NEW_ADMINISTRATOR", ValueType::Address).optional().flag_only(),
+            arg("new-rewards-authority", "NEW_REWARDS_AUTHORITY", ValueType::Address).optional().flag_only(),
+            arg("new-operational-authority", "NEW_OPERATIONAL_AUTHORITY", ValueType::Address).optional().flag_only(),
+            arg("new-vote-authority", "NEW_VOTE_AUTHORITY", ValueType::Address).optional().flag_only(),
+            arg("new-validator-identity", "NEW_VALIDATOR_IDENTITY_FILE", ValueType::Keypair).optional().flag_only(),
+            WITHDRAW_AUTHORITY.optional().flag_only(),
+            ADMINISTRATOR.optional().flag_only(),
+            OPERATIONAL_AUTHORITY.optional().flag_only(),
+            arg("export", "SCRIPT_FILE", ValueType::File).optional().flag_only(),
+            FEE_PAYER
+        ],
+        groups : &[Group::AnyOf(&[
+            "new-administrator",
+            "new-rewards-authority",
+            "new-operational-authority",
+            "new-vote-authority",
+            "new-validator-identity"
+        ])],
+        rpc : true,
+        help : usage::ROTATE_HELP
+    },
+    CommandSpec {
+        name : "incident",
+        summary : "To find and revoke the roles of a compromised key",
+        args : &[
+            arg("compromised", "COMPROMISED_KEY", ValueType::Address),
+            arg("new-administrator", "NEW_ADMINISTRATOR", ValueType::Address).optional().flag_only(),
+            arg("new-rewards-authority", "NEW_REWARDS_AUTHORITY", ValueType::Address).optional().flag_only(),
+            arg("new-operational-authority", "NEW_OPERATIONAL_AUTHORITY", ValueType::Address).optional().flag_only(),
+            arg("new-vote-authority", "NEW_VOTE_AUTHORITY", ValueType::Address).optional().flag_only(),
+            arg("new-validator-identity", "NEW_VALIDATOR_IDENTITY_FILE", ValueType::Keypair).optional().flag_only(),
+            WITHDRAW_AUTHORITY.optional().flag_only(),
+            ADMINISTRATOR.optional().flag_only(),
+            OPERATIONAL_AUTHORITY.optional().flag_only(),
+            arg("export", "SCRIPT_FILE", ValueType::File).optional().flag_only(),
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::INCIDENT_HELP
+    },
+    CommandSpec {
+        name : "withdraw",
+        summary : "To withdraw from a vote account",
+        args : &[
+            REWARDS_AUTHORITY,
+            VOTE_ACCOUNT,
+            arg("recipient", "RECIPIENT_ACCOUNT", ValueType::Address).optional(),
+            arg("split", "SPLIT", ValueType::Text).optional().flag_only(),
+            arg("split-file", "SPLIT_FILE", ValueType::File).optional().flag_only(),
+            AMOUNT,
+            LAMPORTS,
+            FEE_PAYER
+        ],
+        groups : &[Group::Choice(&["recipient", "split", "split-file"]), Group::Exclusive(&["amount", "lamports"])],
+        rpc : true,
+        help : usage::WITHDRAW_HELP
+    },
+    CommandSpec {
+        name : "withdraw-and-stake",
+        summary : "To withdraw into a new stake account",
+        args : &[
+            REWARDS_AUTHORITY,
+            VOTE_ACCOUNT,
+            AMOUNT,
+            LAMPORTS,
+            arg("stake-account", "STAKE_ACCOUNT_FILE", ValueType::Keypair).optional().flag_only(),
+            arg("seed", "SEED", ValueType::Text).optional().flag_only(),
+            arg("stake-authority", "STAKE_AUTHORITY_FILE", ValueType::Keypair).optional().flag_only(),
+            FEE_PAYER
+        ],
+        groups : &[Group::Exclusive(&["amount", "lamports"]), Group::Exclusive(&["stake-account", "seed"])],
+        rpc : true,
+        help : usage::WITHDRAW_AND_STAKE_HELP
+    },
+    CommandSpec {
+        name : "set-commission",
+        summary : "To set commission",
+        args : &[REWARDS_AUTHORITY, VOTE_ACCOUNT, arg("commission", "NEW_COMMISSION", ValueType::Percent), FEE_PAYER],
+        groups : &[],
+        rpc : true,
+        help : usage::SET_COMMISSION_HELP
+    },
+    CommandSpec {
+        name : "batch",
+        summary : "To submit several commands together",
+        args : &[arg("script", "SCRIPT_FILE", ValueType::File), FEE_PAYER],
+        groups : &[],
+        rpc : true,
+        help : usage::BATCH_HELP
+    },
+    CommandSpec {
+        name : "show",
+        summary : "To show managed state",
+        args : &[
+            VOTE_ACCOUNT.repeated(),
+            arg("vote-accounts-file", "VOTE_ACCOUNTS_FILE", ValueType::File).optional().flag_only(),
+            JSON,
+            arg("graph", "dot | mermaid", ValueType::Text).optional().flag_only().suggest(GRAPH_FORMATS)
+        ],
+        groups : &[Group::AnyOf(&["vote-account", "vote-accounts-file"]), Group::Exclusive(&["json", "graph"])],
+        rpc : true,
+        help : usage::SHOW_HELP
+    },
+    CommandSpec {
+        name : "lint",
+        summary : "To check vote accounts for key reuse and risky settings",
+        args : &[
+            VOTE_ACCOUNT.repeated(),
+            arg("vote-accounts-file", "VOTE_ACCOUNTS_FILE", ValueType::File).optional().flag_only(),
+            arg("allow", "RULE", ValueType::Text).repeated().flag_only().suggest(LINT_RULES),
+            arg("deny", "RULE", ValueType::Text).repeated().flag_only().suggest(LINT_RULES),
+            JSON,
+            FEE_PAYER
+        ],
+        groups : &[Group::AnyOf(&["vote-account", "vote-accounts-file"])],
+        rpc : true,
+        help : usage::LINT_HELP
+    },
+    CommandSpec {
+        name : "plan-commission",
+        summary : "To plan commission changes",
+        args : &[
+            VOTE_ACCOUNT,
+            arg("target", "TARGET_COMMISSION", ValueType::Percent).optional(),
+            arg("epoch", "EPOCH", ValueType::Integer).optional().flag_only(),
+            JSON
+        ],
+        groups : &[Group::Choice(&["target", "epoch"])],
+        rpc : true,
+        help : usage::PLAN_COMMISSION_HELP
+    },
+    CommandSpec {
+        name : "ramp-commission",
+        summary : "To change commission over epochs",
+        args : &[
+            REWARDS_AUTHORITY,
+            VOTE_ACCOUNT,
+            arg("target", "TARGET_COMMISSION", ValueType::Percent),
+            arg("state-file", "STATE_FILE", ValueType::File).optional().flag_only(),
+            POLL_INTERVAL,
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::RAMP_COMMISSION_HELP
+    },
+    CommandSpec {
+        name : "daemon",
+        summary : "To withdraw rewards automatically",
+        args : &[
+            REWARDS_AUTHORITY,
+            VOTE_ACCOUNT,
+            arg("recipient", "RECIPIENT_ACCOUNT", ValueType::Address),
+            arg("threshold", "SOL", ValueType::Sol).optional().flag_only(),
+            arg("buffer", "SOL", ValueType::Sol).optional().flag_only(),
+            arg("every-epoch", "", ValueType::Switch).optional().flag_only(),
+            POLL_INTERVAL,
+            FEE_PAYER
+        ],
+        groups : &[],
+        rpc : true,
+        help : usage::DAEMON_HELP
+    },
+    CommandSpec {
+        name : "history",
+        summary : "To show past VAMP transactions",
+        args : &[VOTE_ACCOUNT, arg("limit", "LIMIT", ValueType::Integer).optional().flag_only(), JSON],
+        groups : &[],
+        rpc : true,
+        help : usage::HISTORY_HELP
+    },
+    CommandSpec {
+        name : "decode",
+        summary : "To review a VAMP transaction",
+        args : &[arg("signature, transaction or message", "SIGNATURE | TRANSACTION | MESSAGE", ValueType::Text)
+            .positional_only()],
+        groups : &[],
+        rpc : true,
+        help : usage::DECODE_HELP
+    },
+    CommandSpec {
+        name : "completions",
+        summary : "To print a shell completion script",
+        args : &[arg("shell", "bash | zsh | fish", ValueType::Text).positional_only().suggest(SHELLS)],
+        groups : &[],
+        rpc : false,
+        help : usage::COMPLETIONS_HELP
+    },
+    CommandSpec {
+        name : "man",
+        summary : "To print a man page",
+        args : &[],
+        groups : &[],
+        rpc : false,
+        help : usage::MAN_HELP
+    }
+];
+
+// The shells for which completion scripts may be generated
+pub const SHELLS : &[&str] = &["bash", "zsh", "fish"];
+
+// The formats in which 'show --graph' may write the permission graph
+pub const GRAPH_FORMATS : &[&str] = &["dot", "mermaid"];
+
+// The rules applied by 'solana-vamp lint', in the order in which they are applied
+pub const LINT_RULES : &[&str] = &[
+    "unmanaged",
+    "shared-role",
+    "fee-payer-is-withdraw-authority",
+    "vote-authority-is-validator-identity",
+    "no-commission-caps",
+    "leave-epoch-set",
+    "shared-key"
+];
+
+pub fn find_command(name : &str) -> Option<&'static CommandSpec>
+{
+    COMMANDS.iter().find(|spec| spec.name == name)
+}
+
+impl CommandSpec
+{
+    pub fn arg(
+        &self,
+        name : &str
+    ) -> Option<&'static ArgSpec>
+    {
+        self.accepted_args().find(|arg| arg.name == name)
+    }
+
+    // The arguments of the command followed by the global arguments, if it accepts them
+    pub fn accepted_args(&self) -> impl Iterator<Item = &'static ArgSpec> + Clone
+    {
+        let global_args = if self.rpc { GLOBAL_ARGS } else { &[] };
+
+        self.args.iter().chain(global_args.iter())
+    }
+
+    // Returns the usage of the command followed by its help text
+    pub fn help_message(&self) -> String
+    {
+        format!("\nUsage:\n{}{}", self.usage(), self.help)
+    }
+
+    // Returns the usage lines of the command: the command name and positional-only arguments, then one line per
+    // flag argument (or group of mutually exclusive flag arguments)
+    pub fn usage(&self) -> String
+    {
+        let mut usage = format!("  solana-vamp {}", self.name);
+
+        for arg in self.args.iter().filter(|arg| arg.position == Position::Positional) {
+            usage.push_str(&format!(" <{}>", arg.value));
+        }
+
+        usage.push('\n');
+
+        let mut rendered : Vec<&str> = vec![];
+
+        let url : &[ArgSpec] = if self.rpc { &[URL] } else { &[] };
+
+        for arg in self.args.iter().chain(url.iter()) {
+            if (arg.position == Position::Positional) || rendered.contains(&arg.name) {
+                continue;
+            }
+
+            let line = match self.exclusive_group(arg.name) {
+                Some((names, required)) => {
+                    rendered.extend(names);
+                    let alternatives = names
+                        .iter()
+                        .map(|name| flag_usage(self.arg(name).unwrap()))
+                        .collect::<Vec<String>>()
+                        .join(" | ");
+                    if required {
+                        format!("({})", alternatives)
+                    }
+                    else {
+                        format!("[{}]", alternatives)
+                    }
+                },
+                None => {
+                    rendered.push(arg.name);
+                    match arg.occurs {
+                        Occurs::Required => flag_usage(arg),
+                        Occurs::Optional => format!("[{}]", flag_usage(arg)),
+                        // A repeated argument is shown as needed once only when it is in a group of which at least
+                        // one argument is required
+                        Occurs::Repeated if self.any_of_group(arg.name) => {
+                            format!("{} [{} ...]", flag_usage(arg), flag_usage(arg))
+                        },
+                        Occurs::Repeated => format!("[{} ...]", flag_usage(arg))
+                    }
+                }
+            };
+
+            usage.push_str(&wrap_usage_line(&line));
+        }
+
+        usage
+    }
+
+    fn any_of_group(
+        &self,
+        name : &str
+    ) -> bool
+    {
+        self.groups.iter().any(|group| matches!(group, Group::AnyOf(members) if members.contains(&name)))
+    }
+
+    // Returns the argument and every argument in a group with it
+    pub fn related_args(
+        &self,
+        name : &'static str
+    ) -> Vec<&'static str>
+    {
+        let mut names = vec![name];
+
+        for group in self.groups {
+            let (Group::Choice(members) | Group::Exclusive(members) | Group::AnyOf(members)) = group;
+            if members.contains(&name) {
+                names.extend(members.iter());
+            }
+        }
+
+        names
+    }
+
+    // Returns the members of the Choice or Exclusive group that the named argument belongs to, and whether one of
+    // them is required
+    pub fn exclusive_group(
+        &self,
+        name : &str
+    ) -> Option<(&'static [&'static str], bool)>
+    {
+        self.groups.iter().find_map(|group| match group {
+            Group::Choice(names) if names.contains(&name) => Some((*names, true)),
+            Group::Exclusive(names) if names.contains(&name) => Some((*names, false)),
+            _ => None
+        })
+    }
+}
+
+fn flag_usage(arg : &ArgSpec) -> String
+{
+    if arg.value_type == ValueType::Switch {
+        arg.flag()
+    }
+    else {
+        format!("{} <{}>", arg.flag(), arg.value)
+    }
+}
+
+const USAGE_INDENT : usize = 14;
+
+const USAGE_WIDTH : usize = 78;
+
+// Indents a line of command usage, breaking it after a | if it is too long
+fn wrap_usage_line(line : &str) -> String
+{
+    let mut wrapped = String::new();
+
+    let mut current = " ".repeat(USAGE_INDENT);
+
+    for (i, alternative) in line.split(" | ").enumerate() {
+        if i > 0 {
+            if current.len() + alternative.len() + 3 > USAGE_WIDTH {
+                wrapped.push_str(&current);
+                wrapped.push_str(" |\n");
+                current = " ".repeat(USAGE_INDENT + 1);
+            }
+            else {
+                current.push_str(" | ");
+            }
+        }
+        current.push_str(alternative);
+    }
+
+    wrapped.push_str(&current);
+    wrapped.push('\n');
+
+    wrapped
+}
+
+// Returns the top level usage message, listing every command
+pub fn usage_message() -> String
+{
+    let mut message = format!("{}Usage:\n", usage::USAGE_INTRO);
+
+    let summaries = COMMANDS.iter().map(|spec| (spec.name, spec.summary));
+
+    for (name, summary) in summaries.chain(std::iter::once(("help", "To print this help message"))) {
+        message.push_str(&format!("  solana-vamp {:<27}-- {}\n", name, summary));
+    }
+
+    message.push_str(usage::USAGE_DETAILS);
+
+    message
+}
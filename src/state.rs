@@ -0,0 +1,93 @@
+// vamp program manager state account data is a C struct
+
+use solana_sdk::pubkey::Pubkey;
+
+// Size of the manager state account data
+pub const MANAGER_STATE_SIZE : usize = 168;
+
+#[derive(Debug)]
+pub struct ManagerState
+{
+    pub withdraw_authority : Pubkey,
+
+    pub administrator : Pubkey,
+
+    pub operational_authority : Pubkey,
+
+    pub rewards_authority : Pubkey,
+
+    // (max_commission, max_commission_increase_per_epoch), if commission caps are in use
+    pub commission_caps : Option<(u8, u8)>,
+
+    // 0 if no leave epoch has been set
+    pub leave_epoch : u64
+}
+
+impl ManagerState
+{
+    // Returns None if data is not manager state account data
+    pub fn decode(data : &[u8]) -> Option<ManagerState>
+    {
+        let data : &[u8; MANAGER_STATE_SIZE] = data.try_into().ok()?;
+
+        Some(ManagerState {
+            withdraw_authority : Pubkey::try_from(&data[0..32]).unwrap(),
+            administrator : Pubkey::try_from(&data[32..64]).unwrap(),
+            operational_authority : Pubkey::try_from(&data[64..96]).unwrap(),
+            rewards_authority : Pubkey::try_from(&data[96..128]).unwrap(),
+            commission_caps : if data[128] == 0 { None } else { Some((data[129], data[130])) },
+            leave_epoch : u64::from_le_bytes(data[144..152].try_into().unwrap())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // Manager state account data as laid out by the C struct of the on-chain program
+    fn golden_data(
+        use_commission_caps : u8,
+        leave_epoch : [u8; 8]
+    ) -> Vec<u8>
+    {
+        let mut data = vec![0xEE_u8; MANAGER_STATE_SIZE];
+        data[0..32].copy_from_slice(&[1_u8; 32]);
+        data[32..64].copy_from_slice(&[2_u8; 32]);
+        data[64..96].copy_from_slice(&[3_u8; 32]);
+        data[96..128].copy_from_slice(&[4_u8; 32]);
+        data[128] = use_commission_caps;
+        data[129] = 15;
+        data[130] = 3;
+        data[144..152].copy_from_slice(&leave_epoch);
+        data
+    }
+
+    #[test]
+    fn golden_bytes()
+    {
+        let state = ManagerState::decode(&golden_data(1, [0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01])).unwrap();
+
+        assert_eq!(state.withdraw_authority, Pubkey::new_from_array([1_u8; 32]));
+        assert_eq!(state.administrator, Pubkey::new_from_array([2_u8; 32]));
+        assert_eq!(state.operational_authority, Pubkey::new_from_array([3_u8; 32]));
+        assert_eq!(state.rewards_authority, Pubkey::new_from_array([4_u8; 32]));
+        assert_eq!(state.commission_caps, Some((15, 3)));
+        assert_eq!(state.leave_epoch, 0x0102_0304_0506_0708);
+
+        let state = ManagerState::decode(&golden_data(0, [0_u8; 8])).unwrap();
+
+        assert_eq!(state.commission_caps, None);
+        assert_eq!(state.leave_epoch, 0);
+    }
+
+    #[test]
+    fn rejects_wrong_size()
+    {
+        let data = golden_data(1, [0_u8; 8]);
+
+        assert!(ManagerState::decode(&data[..MANAGER_STATE_SIZE - 1]).is_none());
+        assert!(ManagerState::decode(&[data.as_slice(), &[0]].concat()).is_none());
+    }
+}
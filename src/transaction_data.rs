@@ -1,83 +1,593 @@
 // vamp program instruction data is C structs
 
-// rust is a pain in that it doesn't allow direct memory access to the C struct data, so use zerocopy, but that
-// requires manual padding bytes.  Derp.
+// The C structs are serialized and deserialized explicitly, field by field, at the offsets that the C compiler
+// lays them out at on-chain (little endian, with u64 fields aligned to 8 bytes).  This does not depend on the
+// layout of the Rust structs or the byte order of the host.
 
-// Note that this user of zerocopy only works for little endian systems.  But I'm not sure that anyone even uses
-// any big endian systems any more.
-
-#[repr(C)]
-#[derive(zerocopy::AsBytes, Debug)]
+#[derive(Debug)]
 pub struct EnterData
 {
     pub instruction_code : u8, // 0 = Enter
 
-    pub administrator : [u8; 32],
+    pub administrator : [u8; 32], // offset 1
 
-    pub use_commission_caps : bool,
+    pub use_commission_caps : bool, // offset 33
 
-    pub max_commission : u8,
+    pub max_commission : u8, // offset 34
 
-    pub max_commission_increase_per_epoch : u8
+    pub max_commission_increase_per_epoch : u8 // offset 35
 }
 
-#[repr(C)]
-#[derive(zerocopy::AsBytes)]
 pub struct SetLeaveEpochData
 {
     pub instruction_code : u8, // 1 = SetLeaveEpoch
 
-    pub padding : [u8; 7],
-
-    pub leave_epoch : u64
+    // 7 bytes of padding
+    pub leave_epoch : u64 // offset 8
 }
 
-#[repr(C)]
-#[derive(zerocopy::AsBytes)]
 pub struct LeaveData
 {
     pub instruction_code : u8 // 2 = Leave
 }
 
-#[repr(C)]
-#[derive(zerocopy::AsBytes)]
 pub struct SetAuthorityData
 {
     pub instruction_code : u8, // 3 = SetAdministrator, 4 = SetOperationAuthority, 5 = SetRewardsAuthority
-    pub new_authority : [u8; 32]
+    pub new_authority : [u8; 32]  // offset 1
 }
 
-#[repr(C)]
-#[derive(zerocopy::AsBytes)]
 pub struct SetVoteAuthorityData
 {
-    pub instruction_code : u8, // 6 = SetVoteAuthority
-    pub new_authority : [u8; 32]
+    pub instruction_code : u8,    // 6 = SetVoteAuthority
+    pub new_authority : [u8; 32]  // offset 1
 }
 
-#[repr(C)]
-#[derive(zerocopy::AsBytes)]
 pub struct SetValidatorIdentityData
 {
     pub instruction_code : u8 // 7 = SetValidatorIdentity
 }
 
-#[repr(C)]
-#[derive(zerocopy::AsBytes)]
 pub struct WithdrawData
 {
     pub instruction_code : u8, // 8 = Withdraw
 
-    pub padding : [u8; 7],
-
-    pub lamports : u64
+    // 7 bytes of padding
+    pub lamports : u64 // offset 8
 }
 
-#[repr(C)]
-#[derive(zerocopy::AsBytes)]
 pub struct SetCommissionData
 {
     pub instruction_code : u8, // 9 = SetCommission
 
-    pub new_commission : u8
+    pub new_commission : u8 // offset 1
+}
+
+// Encoding of the above structs into instruction data, and decoding of instruction data back into them.  Padding
+// bytes are encoded as zero and ignored when decoding.  Each decode returns None if the data is not exactly SIZE
+// bytes or does not have the expected instruction code.
+
+impl EnterData
+{
+    pub const SIZE : usize = 36;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let mut data = vec![0_u8; Self::SIZE];
+        data[0] = self.instruction_code;
+        data[1..33].copy_from_slice(&self.administrator);
+        data[33] = self.use_commission_caps as u8;
+        data[34] = self.max_commission;
+        data[35] = self.max_commission_increase_per_epoch;
+        data
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        let data : &[u8; Self::SIZE] = data.try_into().ok()?;
+
+        if (data[0] != 0) || (data[33] > 1) {
+            return None;
+        }
+
+        Some(EnterData {
+            instruction_code : data[0],
+            administrator : data[1..33].try_into().unwrap(),
+            use_commission_caps : data[33] == 1,
+            max_commission : data[34],
+            max_commission_increase_per_epoch : data[35]
+        })
+    }
+}
+
+impl SetLeaveEpochData
+{
+    pub const SIZE : usize = 16;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let mut data = vec![0_u8; Self::SIZE];
+        data[0] = self.instruction_code;
+        data[8..16].copy_from_slice(&self.leave_epoch.to_le_bytes());
+        data
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        let data : &[u8; Self::SIZE] = data.try_into().ok()?;
+
+        if data[0] != 1 {
+            return None;
+        }
+
+        Some(SetLeaveEpochData {
+            instruction_code : data[0],
+            leave_epoch : u64::from_le_bytes(data[8..16].try_into().unwrap())
+        })
+    }
+}
+
+impl LeaveData
+{
+    pub const SIZE : usize = 1;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        vec![self.instruction_code]
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        (data == [2]).then_some(LeaveData { instruction_code : 2 })
+    }
+}
+
+impl SetAuthorityData
+{
+    pub const SIZE : usize = 33;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let mut data = vec![0_u8; Self::SIZE];
+        data[0] = self.instruction_code;
+        data[1..33].copy_from_slice(&self.new_authority);
+        data
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        let data : &[u8; Self::SIZE] = data.try_into().ok()?;
+
+        if !(3..=5).contains(&data[0]) {
+            return None;
+        }
+
+        Some(SetAuthorityData { instruction_code : data[0], new_authority : data[1..33].try_into().unwrap() })
+    }
+}
+
+impl SetVoteAuthorityData
+{
+    pub const SIZE : usize = 33;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let mut data = vec![0_u8; Self::SIZE];
+        data[0] = self.instruction_code;
+        data[1..33].copy_from_slice(&self.new_authority);
+        data
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        let data : &[u8; Self::SIZE] = data.try_into().ok()?;
+
+        if data[0] != 6 {
+            return None;
+        }
+
+        Some(SetVoteAuthorityData { instruction_code : data[0], new_authority : data[1..33].try_into().unwrap() })
+    }
+}
+
+impl SetValidatorIdentityData
+{
+    pub const SIZE : usize = 1;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        vec![self.instruction_code]
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        (data == [7]).then_some(SetValidatorIdentityData { instruction_code : 7 })
+    }
+}
+
+impl WithdrawData
+{
+    pub const SIZE : usize = 16;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let mut data = vec![0_u8; Self::SIZE];
+        data[0] = self.instruction_code;
+        data[8..16].copy_from_slice(&self.lamports.to_le_bytes());
+        data
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        let data : &[u8; Self::SIZE] = data.try_into().ok()?;
+
+        if data[0] != 8 {
+            return None;
+        }
+
+        Some(WithdrawData {
+            instruction_code : data[0],
+            lamports : u64::from_le_bytes(data[8..16].try_into().unwrap())
+        })
+    }
+}
+
+impl SetCommissionData
+{
+    pub const SIZE : usize = 2;
+
+    pub fn encode(&self) -> Vec<u8>
+    {
+        vec![self.instruction_code, self.new_commission]
+    }
+
+    pub fn decode(data : &[u8]) -> Option<Self>
+    {
+        let data : &[u8; Self::SIZE] = data.try_into().ok()?;
+
+        if data[0] != 9 {
+            return None;
+        }
+
+        Some(SetCommissionData { instruction_code : data[0], new_commission : data[1] })
+    }
+}
+
+// A vamp program instruction, independent of the layout of its instruction data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VampInstruction
+{
+    Enter
+    {
+        administrator : [u8; 32],
+        use_commission_caps : bool,
+        max_commission : u8,
+        max_commission_increase_per_epoch : u8
+    },
+
+    SetLeaveEpoch
+    {
+        leave_epoch : u64
+    },
+
+    Leave,
+
+    SetAdministrator
+    {
+        new_administrator : [u8; 32]
+    },
+
+    SetOperationalAuthority
+    {
+        new_authority : [u8; 32]
+    },
+
+    SetRewardsAuthority
+    {
+        new_authority : [u8; 32]
+    },
+
+    SetVoteAuthority
+    {
+        new_authority : [u8; 32]
+    },
+
+    SetValidatorIdentity,
+
+    Withdraw
+    {
+        lamports : u64
+    },
+
+    SetCommission
+    {
+        new_commission : u8
+    }
+}
+
+impl VampInstruction
+{
+    pub fn instruction_code(&self) -> u8
+    {
+        match self {
+            VampInstruction::Enter { .. } => 0,
+            VampInstruction::SetLeaveEpoch { .. } => 1,
+            VampInstruction::Leave => 2,
+            VampInstruction::SetAdministrator { .. } => 3,
+            VampInstruction::SetOperationalAuthority { .. } => 4,
+            VampInstruction::SetRewardsAuthority { .. } => 5,
+            VampInstruction::SetVoteAuthority { .. } => 6,
+            VampInstruction::SetValidatorIdentity => 7,
+            VampInstruction::Withdraw { .. } => 8,
+            VampInstruction::SetCommission { .. } => 9
+        }
+    }
+
+    // Returns the instruction data for the instruction
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let instruction_code = self.instruction_code();
+
+        match *self {
+            VampInstruction::Enter {
+                administrator,
+                use_commission_caps,
+                max_commission,
+                max_commission_increase_per_epoch
+            } => EnterData {
+                instruction_code,
+                administrator,
+                use_commission_caps,
+                max_commission,
+                max_commission_increase_per_epoch
+            }
+            .encode(),
+            VampInstruction::SetLeaveEpoch { leave_epoch } => {
+                SetLeaveEpochData { instruction_code, leave_epoch }.encode()
+            },
+            VampInstruction::Leave => LeaveData { instruction_code }.encode(),
+            VampInstruction::SetAdministrator { new_administrator } => {
+                SetAuthorityData { instruction_code, new_authority : new_administrator }.encode()
+            },
+            VampInstruction::SetOperationalAuthority { new_authority } |
+            VampInstruction::SetRewardsAuthority { new_authority } => {
+                SetAuthorityData { instruction_code, new_authority }.encode()
+            },
+            VampInstruction::SetVoteAuthority { new_authority } => {
+                SetVoteAuthorityData { instruction_code, new_authority }.encode()
+            },
+            VampInstruction::SetValidatorIdentity => SetValidatorIdentityData { instruction_code }.encode(),
+            VampInstruction::Withdraw { lamports } => WithdrawData { instruction_code, lamports }.encode(),
+            VampInstruction::SetCommission { new_commission } => {
+                SetCommissionData { instruction_code, new_commission }.encode()
+            },
+        }
+    }
+
+    // Decodes instruction data.  Data with an unknown instruction code, or which is not exactly the size required by
+    // its instruction code, is rejected.  Padding bytes are ignored.
+    pub fn decode(data : &[u8]) -> Result<Self, String>
+    {
+        let instruction_code = *data.first().ok_or("Empty instruction data")?;
+
+        let size = match instruction_code {
+            0 => EnterData::SIZE,
+            1 => SetLeaveEpochData::SIZE,
+            2 => LeaveData::SIZE,
+            3..=5 => SetAuthorityData::SIZE,
+            6 => SetVoteAuthorityData::SIZE,
+            7 => SetValidatorIdentityData::SIZE,
+            8 => WithdrawData::SIZE,
+            9 => SetCommissionData::SIZE,
+            _ => return Err(format!("Unknown instruction code {}", instruction_code))
+        };
+
+        if data.len() != size {
+            return Err(format!(
+                "Instruction code {} requires {} bytes of instruction data, not {}",
+                instruction_code,
+                size,
+                data.len()
+            ));
+        }
+
+        Ok(match instruction_code {
+            0 => {
+                let data = EnterData::decode(data).ok_or("Invalid use_commission_caps value")?;
+                VampInstruction::Enter {
+                    administrator : data.administrator,
+                    use_commission_caps : data.use_commission_caps,
+                    max_commission : data.max_commission,
+                    max_commission_increase_per_epoch : data.max_commission_increase_per_epoch
+                }
+            },
+            1 => VampInstruction::SetLeaveEpoch { leave_epoch : SetLeaveEpochData::decode(data).unwrap().leave_epoch },
+            2 => VampInstruction::Leave,
+            3 => VampInstruction::SetAdministrator {
+                new_administrator : SetAuthorityData::decode(data).unwrap().new_authority
+            },
+            4 => VampInstruction::SetOperationalAuthority {
+                new_authority : SetAuthorityData::decode(data).unwrap().new_authority
+            },
+            5 => VampInstruction::SetRewardsAuthority {
+                new_authority : SetAuthorityData::decode(data).unwrap().new_authority
+            },
+            6 => VampInstruction::SetVoteAuthority {
+                new_authority : SetVoteAuthorityData::decode(data).unwrap().new_authority
+            },
+            7 => VampInstruction::SetValidatorIdentity,
+            8 => VampInstruction::Withdraw { lamports : WithdrawData::decode(data).unwrap().lamports },
+            _ => VampInstruction::SetCommission {
+                new_commission : SetCommissionData::decode(data).unwrap().new_commission
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn all_instructions() -> Vec<VampInstruction>
+    {
+        vec![
+            VampInstruction::Enter {
+                administrator : [1_u8; 32],
+                use_commission_caps : false,
+                max_commission : 0,
+                max_commission_increase_per_epoch : 0
+            },
+            VampInstruction::Enter {
+                administrator : [2_u8; 32],
+                use_commission_caps : true,
+                max_commission : 10,
+                max_commission_increase_per_epoch : 2
+            },
+            VampInstruction::SetLeaveEpoch { leave_epoch : 0 },
+            VampInstruction::SetLeaveEpoch { leave_epoch : 0x0102_0304_0506_0708 },
+            VampInstruction::SetLeaveEpoch { leave_epoch : u64::MAX },
+            VampInstruction::Leave,
+            VampInstruction::SetAdministrator { new_administrator : [3_u8; 32] },
+            VampInstruction::SetOperationalAuthority { new_authority : [4_u8; 32] },
+            VampInstruction::SetRewardsAuthority { new_authority : [5_u8; 32] },
+            VampInstruction::SetVoteAuthority { new_authority : [6_u8; 32] },
+            VampInstruction::SetValidatorIdentity,
+            VampInstruction::Withdraw { lamports : 0 },
+            VampInstruction::Withdraw { lamports : 1_500_000_000 },
+            VampInstruction::Withdraw { lamports : u64::MAX },
+            VampInstruction::SetCommission { new_commission : 0 },
+            VampInstruction::SetCommission { new_commission : 100 },
+        ]
+    }
+
+    #[test]
+    fn round_trip()
+    {
+        for instruction in all_instructions() {
+            assert_eq!(VampInstruction::decode(&instruction.encode()), Ok(instruction));
+        }
+    }
+
+    #[test]
+    fn covers_all_instruction_codes()
+    {
+        let mut codes = all_instructions().iter().map(|instruction| instruction.encode()[0]).collect::<Vec<u8>>();
+        codes.dedup();
+        assert_eq!(codes, (0..=9).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn encoded_sizes()
+    {
+        let sizes = [36, 16, 1, 33, 33, 33, 33, 1, 16, 2];
+
+        for instruction in all_instructions() {
+            assert_eq!(instruction.encode().len(), sizes[instruction.instruction_code() as usize]);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_lengths()
+    {
+        for instruction in all_instructions() {
+            let data = instruction.encode();
+            assert!(VampInstruction::decode(&data[..data.len() - 1]).is_err());
+            let mut longer = data.clone();
+            longer.push(0);
+            assert!(VampInstruction::decode(&longer).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_instruction_codes()
+    {
+        assert!(VampInstruction::decode(&[]).is_err());
+
+        for instruction_code in 10..=u8::MAX {
+            assert!(VampInstruction::decode(&[instruction_code]).is_err());
+            assert!(VampInstruction::decode(&[instruction_code, 0]).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_bool()
+    {
+        let mut data = VampInstruction::Enter {
+            administrator : [1_u8; 32],
+            use_commission_caps : true,
+            max_commission : 10,
+            max_commission_increase_per_epoch : 2
+        }
+        .encode();
+
+        data[33] = 2;
+
+        assert!(VampInstruction::decode(&data).is_err());
+    }
+
+    // Instruction data as laid out by the C structs of the on-chain program
+    #[test]
+    fn golden_bytes()
+    {
+        let key = |first : u8| {
+            let mut key = [0_u8; 32];
+            key.iter_mut().enumerate().for_each(|(i, b)| *b = first.wrapping_add(i as u8));
+            key
+        };
+
+        let with_key = |prefix : &[u8], key : [u8; 32], suffix : &[u8]| [prefix, &key, suffix].concat();
+
+        let cases = vec![
+            (
+                VampInstruction::Enter {
+                    administrator : key(0x10),
+                    use_commission_caps : true,
+                    max_commission : 10,
+                    max_commission_increase_per_epoch : 2
+                },
+                with_key(&[0], key(0x10), &[1, 10, 2])
+            ),
+            (
+                VampInstruction::Enter {
+                    administrator : key(0x20),
+                    use_commission_caps : false,
+                    max_commission : 0,
+                    max_commission_increase_per_epoch : 0
+                },
+                with_key(&[0], key(0x20), &[0, 0, 0])
+            ),
+            (VampInstruction::SetLeaveEpoch { leave_epoch : 0x0102_0304_0506_0708 }, vec![
+                1, 0, 0, 0, 0, 0, 0, 0, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+            ]),
+            (VampInstruction::Leave, vec![2]),
+            (VampInstruction::SetAdministrator { new_administrator : key(0x30) }, with_key(&[3], key(0x30), &[])),
+            (VampInstruction::SetOperationalAuthority { new_authority : key(0x40) }, with_key(&[4], key(0x40), &[])),
+            (VampInstruction::SetRewardsAuthority { new_authority : key(0x50) }, with_key(&[5], key(0x50), &[])),
+            (VampInstruction::SetVoteAuthority { new_authority : key(0x60) }, with_key(&[6], key(0x60), &[])),
+            (VampInstruction::SetValidatorIdentity, vec![7]),
+            (VampInstruction::Withdraw { lamports : 1_500_000_000 }, vec![
+                8, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x2F, 0x68, 0x59, 0x00, 0x00, 0x00, 0x00,
+            ]),
+            (VampInstruction::Withdraw { lamports : u64::MAX }, vec![
+                8, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            ]),
+            (VampInstruction::SetCommission { new_commission : 7 }, vec![9, 7]),
+        ];
+
+        for (instruction, bytes) in cases {
+            assert_eq!(instruction.encode(), bytes, "{:?}", instruction);
+            assert_eq!(VampInstruction::decode(&bytes), Ok(instruction));
+        }
+    }
+
+    #[test]
+    fn ignores_padding()
+    {
+        let mut data = VampInstruction::Withdraw { lamports : 42 }.encode();
+
+        data[1..8].copy_from_slice(&[0xFF_u8; 7]);
+
+        assert_eq!(VampInstruction::decode(&data), Ok(VampInstruction::Withdraw { lamports : 42 }));
+    }
 }
@@ -1,37 +1,104 @@
-pub const USAGE_MESSAGE : &str = "
+// The usage message is generated from the command specs in spec.rs; these are the text before and after the list of
+// commands, and the help text following each command's generated usage
+
+pub const USAGE_INTRO : &str = "
 solana-vamp is a utility program that can be used to interact with the Solana
 Vote Account Manager program.
 
-Usage:
-  solana-vamp enter                      -- To start using VAMP
-  solana-vamp set-leave-epoch            -- To set a leave epoch
-  solana-vamp leave                      -- To stop using SOLANA-VAMP
-  solana-vamp set-administrator          -- To set the administrator
-  solana-vamp set-operational-authority  -- To set the operational authority
-  solana-vamp set-rewards-authority      -- To set the rewards authority
-  solana-vamp set-vote-authority         -- To set the vote authority
-  solana-vamp set-validator-identity     -- To set the validator identity
-  solana-vamp withdraw                   -- To withdraw from a vote account
-  solana-vamp set-commission             -- To set commission
-  solana-vamp show                       -- To show managed state
-  solana-vamp help                       -- To print this help message
+";
 
+pub const USAGE_DETAILS : &str = "
 For help on a specific command, use 'solana-vamp help <COMMAND>', for example:
 
 $ solana-vamp help enter
+
+Arguments which are not supplied on the command line may be taken from, in
+order of precedence:
+
+  Environment variables: SOLANA_VAMP_URL, SOLANA_VAMP_COMMITMENT,
+      SOLANA_VAMP_FEE_PAYER, SOLANA_VAMP_VOTE_ACCOUNT, and the keypair files of
+      the authority which signs a command: SOLANA_VAMP_WITHDRAW_AUTHORITY,
+      SOLANA_VAMP_ADMINISTRATOR, SOLANA_VAMP_OPERATIONAL_AUTHORITY and
+      SOLANA_VAMP_REWARDS_AUTHORITY.
+
+  A profile selected by --profile <PROFILE> or SOLANA_VAMP_PROFILE: the file
+      ~/.config/solana-vamp/profiles/<PROFILE>, containing lines such as
+      'url = devnet' or 'rewards-authority = rewards_authority.json', which
+      set the same arguments as the environment variables.
+
+  The solana CLI config file ~/.config/solana/cli/config.yml, which sets the
+      URL and commitment only.
 ";
 
-pub const ENTER_USAGE_MESSAGE : &str = "
+// Shown by 'solana-vamp help record-rpc' or 'solana-vamp help replay-rpc' rather than in the usage message, since
+// these arguments are for testing solana-vamp itself
+pub const RPC_FIXTURE_HELP : &str = "
 Usage:
-  solana-vamp enter
-              --withdraw-authority <WITHDRAW_AUTHORITY_FILE>
-              --vote-acount <VOTE_ACCOUNT>
-              --administrator <ADMINISTRATOR>
-              [--max-commission <MAX_COMMISSION>
-               --max-commission-increase-per-epoch <MAX_INCREASE_PER_EPOCH>]
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
+  solana-vamp <COMMAND> [--record-rpc <FILE> | --replay-rpc <FILE>]
+
+RPC traffic can be captured for regression testing of the output of any
+command which makes RPC requests.
+
+  --record-rpc: Records every RPC request of the command, with its response,
+      to FILE, which is rewritten after each request.  Fixtures are recorded
+      against a local test validator, started with solana-test-validator, by
+      also supplying --url localhost.
+
+  --replay-rpc: Answers the command's RPC requests from a FILE written by
+      --record-rpc, instead of from an RPC endpoint.  A request that was not
+      recorded fails as if the RPC endpoint had returned an error.
+
+Examples:
+
+# Record the RPC traffic of showing a vote account on a local test validator,
+# and then show it again from the recording.
+
+$ solana-vamp show --url localhost --record-rpc show.json <VOTE_ACCOUNT>
+
+$ solana-vamp show --replay-rpc show.json <VOTE_ACCOUNT>
+";
+
+pub const KEYGEN_HELP : &str = "
+'solana-vamp keygen' generates a separate keypair for each of the
+administrator, operational authority, rewards authority, and vote authority
+of a new validator, and writes a profile that points solana-vamp at them.
+
+The keypairs are written to administrator.json, operational_authority.json,
+rewards_authority.json, and vote_authority.json in the output directory, each
+readable only by its owner, and the profile is written to the file 'profile'
+there.  The profile supplies the administrator, operational authority, and
+rewards authority arguments of other commands; copy it to
+~/.config/solana-vamp/profiles/<PROFILE> and select it as described by
+'solana-vamp help'.  The vote authority is not an argument that a profile can
+supply, so the profile instead notes the 'solana-vamp set-vote-authority'
+command that sets it.
+
+Existing files are never overwritten; if any of the files already exists,
+nothing is written.  The withdraw authority is not generated, since it is the
+withdraw authority of the vote account when it enters the program.
+
+The following arguments are required:
+
+  --out-dir: The directory to write the keypairs and profile to, which is
+      created if it does not exist.
+
+The following arguments may be optionally provided:
+
+  --seed-phrases: Generate each keypair from a seed phrase, which is printed
+      once and never stored.  The keypair can be recovered from its seed
+      phrase with 'solana-keygen recover', with an empty passphrase.
+
+Examples:
+
+# Generate the authorities of a new validator into ~/validator-keys, with seed
+# phrases, and install the profile as 'validator'.
+
+$ solana-vamp keygen --out-dir ~/validator-keys --seed-phrases
 
+$ cp ~/validator-keys/profile ~/.config/solana-vamp/profiles/validator
+";
+
+pub const ENTER_HELP : &str = "
 'solana-vamp enter' initiates the use of the Vote Account Manager program with
 a particular vote account.  This will bring the vote account under the control
 of the Vote Account Manager program.  While under control of the program,
@@ -77,6 +144,17 @@ The following arguments may be optionally provided:
       stored in the given file.  If this argument is not present, the
       --withdraw-authority will be used as the fee payer.
 
+  --interactive: Guides the user through each choice.  The vote account is
+      fetched and its current commission and withdraw authority are shown,
+      each choice is explained, and any of the above arguments that were not
+      supplied are prompted for.  The choices are checked: the
+      --withdraw-authority must be the withdraw authority of the vote
+      account, and if a maximum commission is to be enforced then both
+      --max-commission and --max-commission-increase-per-epoch must be given,
+      with --max-commission no lower than the current commission.  The
+      equivalent non-interactive command is then shown, and the transaction
+      is only submitted if the user confirms it.
+
   --url: Will set the URL of the RPC endpoint to send transactions to.  A full
       URL may be specified, and in addition, the following special values may
       be used:
@@ -84,7 +162,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Examples:
 
@@ -109,17 +189,15 @@ $ solana-vamp enter                                                           \\
       --administrator administrator.json                                      \\
       --max-commission 10                                                     \\
       --max-commission-increase-per-epoch 3
-";
 
-pub const SET_LEAVE_EPOCH_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp set-leave-epoch
-              --withdraw-authority <WITHDRAW_AUTHORITY_FILE>
-              --vote-acount <VOTE_ACCOUNT>
-              --leave-epoch <EPOCH>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
+# Be guided through putting the vote account under control of the Vote Account
+# Manager program, supplying only the vote account on the command line.
 
+$ solana-vamp enter --interactive                                             \\
+      --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz
+";
+
+pub const SET_LEAVE_EPOCH_HELP : &str = "
 'solana-vamp set-leave-epoch' sets the earliest epoch at which a 'leave'
 command may be issued to return control of the vote account to the withdraw
 authority.
@@ -152,7 +230,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 set-leave-epoch is only necessary if the Vote Account Manager program has been
 configured to restrict commission on the vote account.  If this is the case,
@@ -187,19 +267,12 @@ Example:
 # provided in the keypair file withdraw_authority.json.
 
 $ solana-vamp set-leave-epoch                                                 \\
-      --withdraw_authority withdraw_authority.json                            \\
+      --withdraw-authority withdraw_authority.json                            \\
       --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz             \\
       --leave-epoch 120
 ";
 
-pub const LEAVE_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp leave
-              --withdraw-authority <WITHDRAW_AUTHORITY_FILE>
-              --vote-acount <VOTE_ACCOUNT>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
-
+pub const LEAVE_HELP : &str = "
 'solana-vamp leave' removes the vote account from Vote Account Manager program
 control, and re-sets the vote account's withdraw authority to
 <WITHDRAW_AUTHORITY>.  Note that if the program is enforcing commission limits
@@ -230,7 +303,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Example:
 
@@ -243,15 +318,7 @@ $ solana-vamp leave                                                           \\
       --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz
 ";
 
-pub const SET_ADMINISTRATOR_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp set-administrator
-              --withdraw-authority <WITHDRAW_AUTHORITY_FILE>
-              --vote-account <VOTE_ACCOUNT>
-              --administrator <NEW_ADMINISTRATOR>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
-
+pub const SET_ADMINISTRATOR_HELP : &str = "
 'solana-vamp set-administrator' sets the pubkey of the new administrator
 account to be used to authenticate these commands:
     set-operational-authority
@@ -283,7 +350,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Example:
 
@@ -298,15 +367,7 @@ $ solana-vamp set-administrator                                               \\
       --administrator new_administrator.json
 ";
 
-pub const SET_OPERATIONAL_AUTHORITY_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp set-operational-authority
-              --administrator <ADMINISTRATOR_FILE>
-              --vote-account <VOTE_ACCOUNT>
-              --operational-authority <OPERATIONAL_AUTHORITY>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
-
+pub const SET_OPERATIONAL_AUTHORITY_HELP : &str = "
 'solana-vamp set-operational-authority' sets the pubkey of the new operational
 authority account to be used to authenticate these commands:
     set-vote-authority
@@ -339,7 +400,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Example:
 
@@ -354,15 +417,7 @@ $ solana-vamp set-operational-authority                                       \\
       --operational-authority new_operational_authority.json
 ";
 
-pub const SET_REWARDS_AUTHORITY_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp set-rewards-authority
-              --administrator <ADMINISTRATOR_FILE>
-              --vote-account <VOTE_ACCOUNT>
-              --rewards-authority <REWARDS_AUTHORITY>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
-
+pub const SET_REWARDS_AUTHORITY_HELP : &str = "
 'solana-vamp set-rewards-authority' sets the pubkey of the new rewards
 authority account to be used to authenticate these commands:
     withdraw
@@ -395,7 +450,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Example:
 
@@ -409,15 +466,7 @@ $ solana-vamp set-rewards-authority                                           \\
       --rewards-authority new_rewards_authority.json
 ";
 
-pub const SET_VOTE_AUTHORITY_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp set-vote-authority
-              --operational-authority <OPERATIONAL_AUTHORITY_FILE>
-              --vote-account <VOTE_ACCOUNT>
-              --vote-authority <NEW_VOTE_AUTHORITY>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
-
+pub const SET_VOTE_AUTHORITY_HELP : &str = "
 'solana-vamp set-vote-authority' sets the pubkey of the new vote authority
 account for the vote account.  This can also be accomplished by the normal
 'solana vote-authorize-voter' command, since the vote authority of the vote
@@ -451,7 +500,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Example:
 
@@ -466,15 +517,7 @@ $ solana-vamp set-vote-authority                                              \\
       --vote-authority new_vote_authority.json
 ";
 
-pub const SET_VALIDATOR_IDENTITY_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp set-validator-identity
-              --operational-authority <OPERATIONAL_AUTHORITY_FILE>
-              --vote-account <VOTE_ACCOUNT>
-              --validator-identity <NEW_VALIDATOR_IDENTITY_FILE>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
-
+pub const SET_VALIDATOR_IDENTITY_HELP : &str = "
 'solana-vamp set-validator-identity' sets the keypair of the new validator
 identity for the vote account.
 
@@ -504,7 +547,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Example:
 
@@ -519,16 +564,221 @@ $ solana-vamp set-validator-identity                                          \\
       --validator-identity new_validator_identity.json
 ";
 
-pub const WITHDRAW_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp withdraw
-              --rewards-authority <REWARDS_AUTHORITY>
-              --vote-account <VOTE_ACCOUNT>
-              --recipient <RECIPIENT_ACCOUNT>
-              [--amount <SOL_TO_WITHDRAW>]
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
+pub const ROTATE_HELP : &str = "
+'solana-vamp rotate' replaces any of the administrator, rewards authority,
+operational authority, vote authority, and validator identity of a vote
+account under control of the Vote Account Manager program in one command.
+
+Each authority is changed by the authority above it: the administrator by the
+withdraw authority, the rewards and operational authorities by the
+administrator, and the vote authority and validator identity by the
+operational authority.  The changes are made in that order, so if the
+administrator or operational authority is replaced, it is the new one that
+signs the changes beneath it.  A rotation that could not be completed is
+refused before anything is submitted: for example, a new administrator that
+must sign a later change has to be given as a keypair file rather than as a
+pubkey, and the keypair of each current authority that must sign is checked
+against the vote account.
+
+The changes are submitted as by 'solana-vamp batch', in a single transaction
+if they fit.  Alternatively, --export writes them to a batch script to be
+reviewed and then submitted with 'solana-vamp batch'.
+
+The following arguments are required:
+
+  --vote-account: Must be the pubkey of the vote account under program
+      control, or the path to a keypair file from which the vote account
+      pubkey will be loaded.
+
+At least one of the following arguments is required:
+
+  --new-administrator: The new administrator, as a pubkey or the path to a
+      keypair file from which the pubkey will be loaded.  Must be a keypair
+      file if the rewards or operational authority is also being rotated.
+
+  --new-rewards-authority: The new rewards authority, as a pubkey or the
+      path to a keypair file from which the pubkey will be loaded.
+
+  --new-operational-authority: The new operational authority, as a pubkey or
+      the path to a keypair file from which the pubkey will be loaded.  Must
+      be a keypair file if the vote authority or validator identity is also
+      being rotated.
+
+  --new-vote-authority: The new vote authority, as a pubkey or the path to a
+      keypair file from which the pubkey will be loaded.
+
+  --new-validator-identity: The path to the keypair file of the new
+      validator identity, which must sign its change.
+
+The following arguments are required when the authority must sign:
+
+  --withdraw-authority: The keypair of the withdraw authority, which signs
+      the change of administrator.
+
+  --administrator: The keypair of the current administrator, which signs the
+      changes of rewards and operational authority unless the administrator
+      is also being rotated.
+
+  --operational-authority: The keypair of the current operational authority,
+      which signs the changes of vote authority and validator identity
+      unless the operational authority is also being rotated.
+
+The following arguments may be optionally provided:
+
+  --export: Write the changes to the given batch script file instead of
+      submitting them.
+
+  --fee-payer: Will set the fee payer for the transactions to the keypair
+      stored in the given file.  If this argument is not present, the
+      authority which signs the first change will be used as the fee payer.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+Examples:
+
+# Replace the administrator of vote account
+# 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz, and have the new
+# administrator replace the rewards authority.
+
+$ solana-vamp rotate                                                          \\
+      --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz             \\
+      --withdraw-authority withdraw_authority.json                            \\
+      --new-administrator new_administrator.json                              \\
+      --new-rewards-authority 8YVrBHcBUeA4wUhcG1KcUd6bsfZYzxYNMKLpeHhJAwrb
+
+# Write the replacement of the vote authority and validator identity to
+# rotation.vamp for review, and then submit it.
+
+$ solana-vamp rotate                                                          \\
+      --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz             \\
+      --operational-authority operational_authority.json                      \\
+      --new-vote-authority new_vote_authority.json                            \\
+      --new-validator-identity new_identity.json                              \\
+      --export rotation.vamp
+
+$ solana-vamp batch rotation.vamp
+";
+
+pub const INCIDENT_HELP : &str = "
+'solana-vamp incident' responds to a compromised key.  It finds every vote
+account under control of the Vote Account Manager program in which the key is
+the withdraw authority, administrator, operational authority, rewards
+authority, vote authority, or validator identity, explains what an attacker
+holding the key can do with each role, and works out the fewest changes that
+take the roles away from the key.  Finding the vote authority means checking
+every vote account, which may take some time.
+
+Each role is revoked by the authority above it: the administrator is replaced
+by the withdraw authority; the rewards and operational authorities by the
+administrator, or by the new administrator if the administrator is also being
+replaced; and the vote authority and validator identity by the operational
+authority, or by the new operational authority if the operational authority
+is also being replaced.  Nothing is above the withdraw authority, which
+cannot be replaced while the vote account is under program control; a vote
+account whose withdraw authority is compromised is reported with the steps
+needed to leave the program, and no changes are made to it.
+
+Without any of the new authorities, only the report is printed, together with
+the arguments needed to revoke the key.  With them, the changes are submitted
+as by 'solana-vamp rotate', or written to a batch script by --export.
+
+Each vote account is revoked with the keypairs of its own authorities.  Vote
+accounts whose changes cannot be signed with the --withdraw-authority,
+--administrator, and --operational-authority keypairs supplied are reported
+and left unchanged, and the key can be revoked from them by running
+'solana-vamp incident' again with the keypairs of their authorities.
+
+The following arguments are required:
+
+  --compromised: The compromised key, as a pubkey or the path to a keypair
+      file from which the pubkey will be loaded.
+
+The following arguments are required to revoke the key:
+
+  --new-administrator: The new administrator of each vote account whose
+      administrator is the compromised key, as a pubkey or the path to a
+      keypair file from which the pubkey will be loaded.  Must be a keypair
+      file if the key is also the rewards or operational authority of such a
+      vote account.
+
+  --new-rewards-authority: The new rewards authority of each vote account
+      whose rewards authority is the compromised key, as a pubkey or the path
+      to a keypair file from which the pubkey will be loaded.
+
+  --new-operational-authority: The new operational authority of each vote
+      account whose operational authority is the compromised key, as a pubkey
+      or the path to a keypair file from which the pubkey will be loaded.
+      Must be a keypair file if the key is also the vote authority or
+      validator identity of such a vote account.
+
+  --new-vote-authority: The new vote authority of each vote account whose
+      vote authority is the compromised key, as a pubkey or the path to a
+      keypair file from which the pubkey will be loaded.
+
+  --new-validator-identity: The path to the keypair file of the new validator
+      identity of each vote account whose validator identity is the
+      compromised key, which must sign its change.
+
+  --withdraw-authority: The keypair of the withdraw authority, which signs
+      the replacement of the administrator.
+
+  --administrator: The keypair of the current administrator, which signs the
+      replacement of the rewards and operational authorities unless the
+      administrator is also being replaced.
+
+  --operational-authority: The keypair of the current operational authority,
+      which signs the replacement of the vote authority and validator
+      identity unless the operational authority is also being replaced.
+
+The following arguments may be optionally provided:
 
+  --export: Write the changes to the given batch script file instead of
+      submitting them.
+
+  --fee-payer: Will set the fee payer for the transactions to the keypair
+      stored in the given file.  If this argument is not present, the
+      authority which signs the first change will be used as the fee payer.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+Examples:
+
+# Report what key 8YVrBHcBUeA4wUhcG1KcUd6bsfZYzxYNMKLpeHhJAwrb controls.
+
+$ solana-vamp incident --compromised 8YVrBHcBUeA4wUhcG1KcUd6bsfZYzxYNMKLpeHhJAwrb
+
+# Write the replacement of the compromised administrator and rewards
+# authority to revoke.vamp for review, and then submit it.
+
+$ solana-vamp incident                                                        \\
+      --compromised 8YVrBHcBUeA4wUhcG1KcUd6bsfZYzxYNMKLpeHhJAwrb              \\
+      --withdraw-authority withdraw_authority.json                            \\
+      --new-administrator new_administrator.json                              \\
+      --new-rewards-authority new_rewards_authority.json                      \\
+      --export revoke.vamp
+
+$ solana-vamp batch revoke.vamp
+";
+
+pub const WITHDRAW_HELP : &str = "
 'solana-vamp withdraw' withdraws SOL from the vote account.  It will never
 withdraw below the rent exempt reserve of the vote account.
 
@@ -542,16 +792,41 @@ The following arguments are required:
       control, or the path to a keypair file from which the vote account
       pubkey will be loaded.
 
+Exactly one of the following arguments must be provided:
+
   --recipient: Must be the pubkey of the account into which the SOL will be
       withdrawn, or the path to a keypair file from which the recipient pubkey
       will be read.
 
+  --split: A comma separated list of RECIPIENT:PERCENT entries.  The SOL
+      withdrawn is divided between the recipients according to the
+      percentages, which may have up to two fractional digits and must total
+      100.  Each RECIPIENT is a pubkey or the path to a keypair file.  All
+      withdraws are made in a single transaction, so either every recipient is
+      paid or none are.  Each recipient's share is rounded down to a whole
+      lamport, and any lamports left over from rounding are sent to the first
+      recipient.
+
+  --split-file: The path to a file containing RECIPIENT:PERCENT entries, one
+      per line, as for --split.  Blank lines and lines beginning with # are
+      ignored.
+
 The following arguments may be optionally provided:
 
-  --amount: The quantity of SOL to withdraw from the vote account.  If this
-      value is not present, or is specified as '0', then the maximum amount of
-      SOL that can be withdrawn from the vote account while respecting rent
-      exempt minimums will be withdrawn.
+  --amount: The quantity of SOL to withdraw from the vote account, as a
+      decimal number with at most 9 fractional digits (the precision of a
+      lamport).  The following special values may also be used:
+        ALL: The maximum amount of SOL that can be withdrawn from the vote
+          account while respecting rent exempt minimums.
+        ALL-BUT <SOL>: The maximum amount of SOL that can be withdrawn, less
+          <SOL>, which is left in the vote account.
+      If neither --amount nor --lamports is present, or the amount is
+      specified as '0', then ALL is withdrawn.  When splitting, or when
+      ALL-BUT is used, the maximum is computed from the vote account's balance
+      before the transaction is submitted.
+
+  --lamports: The quantity of SOL to withdraw from the vote account, given as
+      a whole number of lamports.  May not be combined with --amount.
 
   --fee-payer: Will set the fee payer for the transaction to the keypair
       stored in the given file.  If this argument is not present, the
@@ -564,7 +839,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Examples:
 
@@ -578,23 +855,31 @@ $ solana-vamp withdraw                                                        \\
               --recipient user_key.json                                       \\
               --amount 10.05
 
+# Withdraw all available funds from the vote account, except for 1.5 SOL:
+
+$ solana-vamp withdraw                                                        \\
+              --rewards-authority rewards_authority.json                      \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --recipient user_key.json                                       \\
+              --amount ALL-BUT 1.5
+
 # Withdraw all available funds from the vote account:
 
 $ solana-vamp withdraw                                                        \\
               --rewards-authority rewards_authority.json                      \\
               --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
               --recipient user_key.json
-";
 
-pub const SET_COMMISSION_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp set-commission
-              --rewards-authority <REWARDS_AUTHORITY>
-              --vote-account <VOTE_ACCOUNT>
-              --commission <NEW_COMMISSION>
-              [--fee-payer <FEE_PAYER>]
-              [--url <RPC_ENDPOINT>]
+# Withdraw all available funds from the vote account, sending 60% to
+# partner_a.json and 40% to partner_b.json:
 
+$ solana-vamp withdraw                                                        \\
+              --rewards-authority rewards_authority.json                      \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --split partner_a.json:60,partner_b.json:40
+";
+
+pub const SET_COMMISSION_HELP : &str = "
 'solana-vamp set-commission' sets the vote account's commission to a new
 value.  If the Vote Account Manager program has been configured to enforce
 commission caps on the vote account, then NEW_COMMISSION must not violate
@@ -630,7 +915,9 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
 Example:
 
@@ -643,27 +930,94 @@ $ solana-vamp set-commission                                                  \\
               --commission 5
 ";
 
-pub const SHOW_USAGE_MESSAGE : &str = "
-Usage:
-  solana-vamp show
-              --vote-account <VOTE_ACCOUNT>
-              [--json]
-              [--url <RPC_ENDPOINT>]
+pub const BATCH_HELP : &str = "
+'solana-vamp batch' submits the commands of a script file together, packing
+their instructions into as few transactions as possible.  Commands which fit
+in a single transaction take effect together or not at all; for example,
+entering a vote account and setting its operational and rewards authorities
+can be done without there being any time at which the administrator holds
+every role.  If the commands do not all fit in one transaction, they are
+split in order across several, each of which is submitted only once the
+previous one has succeeded.  A keypair which signs several commands signs
+each transaction only once.
+
+The script has one command per line, written as it would be after
+'solana-vamp' on the command line, with its arguments separated by spaces.
+Blank lines and lines beginning with # are ignored.  The commands which may
+be used are enter (but not interactively), set-leave-epoch, leave,
+set-administrator, set-operational-authority, set-rewards-authority,
+set-vote-authority, set-validator-identity, and set-commission.  A command in
+the script may not have the --fee-payer, --url, --commitment, --profile,
+--record-rpc, or --replay-rpc arguments, which instead are given to the batch
+command and apply to every command of the script.
 
-'solana-vamp show' shows the currently configured values for a vote account
-under control of the Vote Account Manager program.
+The following arguments are required:
 
-The following arguments is required:
+  --script: The path to the script file.
 
-  --vote-account: Must be the pubkey of the vote account under program
-      control, or the path to a keypair file from which the vote account
-      pubkey will be loaded.
+The following arguments may be optionally provided:
+
+  --fee-payer: Will set the fee payer for every transaction to the keypair
+      stored in the given file.  If this argument is not present, the
+      keypair that would pay for the first command of the script if it were
+      run by itself will be used as the fee payer.  The fee payer also funds
+      enter and receives the lamports returned by leave.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+Example:
+
+# Put the vote account whose keypair is in vote_account.json under control of
+# the Vote Account Manager program and hand the operational and rewards roles
+# to their own keypairs, in a single transaction.  The script setup.vamp
+# contains:
+#
+#   enter withdraw.json vote_account.json administrator.json
+#   set-operational-authority administrator.json vote_account.json op.json
+#   set-rewards-authority administrator.json vote_account.json rewards.json
+
+$ solana-vamp batch setup.vamp
+";
+
+pub const SHOW_HELP : &str = "
+'solana-vamp show' shows the currently configured values for one or more vote
+accounts under control of the Vote Account Manager program.
+
+At least one vote account must be supplied, either via --vote-account or
+--vote-accounts-file:
+
+  --vote-account: Must be the pubkey of a vote account under program control,
+      or the path to a keypair file from which the vote account pubkey will be
+      loaded.  May be supplied multiple times.
+
+  --vote-accounts-file: Must be the path to a file containing vote account
+      pubkeys (or keypair file paths), one per line.  Blank lines and lines
+      beginning with # are ignored.
 
 The following arguments may be optionally provided:
 
   --json: The output format will be JSON; if this argument is not provided, it
       will be human readable lines.
 
+  --graph: Instead of the configured values, show the permission graph of
+      the vote accounts in Graphviz dot or Mermaid format: the withdraw
+      authority sets the administrator, the administrator sets the operational
+      and rewards authorities, the operational authority sets the vote
+      authority and validator identity, and the rewards authority sets the
+      commission and withdraws.  Each key is labeled with its pubkey and the
+      roles it holds.  A key that holds more than one role, in one vote
+      account or across several, is shown once and drawn in red.  May not be
+      combined with --json.
+
   --url: Will set the URL of the RPC endpoint to send transactions to.  A full
       URL may be specified, and in addition, the following special values may
       be used:
@@ -671,9 +1025,17 @@ The following arguments may be optionally provided:
         d, devnet: https://api.devnet.solana.com
         t, testnet: https://api.testnet.solana.com
         m, mainnet: https://api.mainnet-beta.solana.com
-      If --url is not supplied, then mainnet is used.
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
 
-Example:
+If more than one vote account is supplied, all accounts are fetched in batches
+and the output is a table with one row per vote account, which also includes
+each vote account's current commission.  With --json, the output is a JSON
+array with one object per vote account; vote accounts which are not managed by
+the Vote Account Manager program have \"managed\" set to false.
+
+Examples:
 
 # Show the configuration values for the vote account
 # 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz
@@ -705,4 +1067,508 @@ $ solana-vamp show                                                            \\
   \"max_commission\": 10,
   \"max_commission_increase_per_epoch\": 3
 }
+
+# Show a table of all vote accounts listed in the file fleet.txt:
+
+$ solana-vamp show --vote-accounts-file fleet.txt
+
+# Draw the permission graph of the same vote accounts with Graphviz, to find
+# keys shared between them:
+
+$ solana-vamp show --vote-accounts-file fleet.txt --graph dot                 \\
+  | dot -Tsvg > fleet.svg
+";
+
+pub const LINT_HELP : &str = "
+'solana-vamp lint' checks one or more vote accounts under control of the Vote
+Account Manager program for key reuse and risky settings.  No transactions are
+submitted.
+
+Each finding is printed on its own line, naming the rule that found it,
+followed by a count of warnings and errors.  Every rule gives warnings unless
+it is allowed or denied; a denied rule gives errors instead, and if there are
+any errors, 'solana-vamp lint' exits with status 1, so that it can be used to
+gate CI.
+
+The rules are:
+
+  unmanaged: A vote account which is not managed by the program.
+
+  shared-role: A key which holds more than one of the withdraw authority,
+      administrator, operational authority, and rewards authority roles of a
+      vote account.
+
+  fee-payer-is-withdraw-authority: The fee payer is the withdraw authority of
+      a vote account, so the withdraw authority keypair is used routinely.
+
+  vote-authority-is-validator-identity: The vote authority of a vote account
+      is its validator identity.
+
+  no-commission-caps: A vote account has no commission caps, so its
+      commission may be raised to 100% at once and it may leave the program at
+      any time.
+
+  leave-epoch-set: A leave epoch is set for a vote account.
+
+  shared-key: A key which holds roles in more than one of the vote accounts.
+
+At least one vote account must be supplied, either via --vote-account or
+--vote-accounts-file:
+
+  --vote-account: Must be the pubkey of a vote account under program control,
+      or the path to a keypair file from which the vote account pubkey will be
+      loaded.  May be supplied multiple times.
+
+  --vote-accounts-file: Must be the path to a file containing vote account
+      pubkeys (or keypair file paths), one per line.  Blank lines and lines
+      beginning with # are ignored.
+
+The following arguments may be optionally provided:
+
+  --allow: The name of a rule which is not to be applied, or all to allow
+      every rule.  May be supplied multiple times.
+
+  --deny: The name of a rule whose findings are errors, or all to deny every
+      rule.  May be supplied multiple times.  A rule named by --allow or
+      --deny takes precedence over all.
+
+  --json: The output will be a JSON object holding an array of findings and
+      the counts of warnings and errors; if this argument is not provided, it
+      will be human readable lines.
+
+  --fee-payer: The fee payer to check against the withdraw authority, as the
+      path to a keypair file.  It is not used to pay for anything.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+Examples:
+
+# Check every vote account listed in fleet.txt, failing on anything but a
+# leave epoch.
+
+$ solana-vamp lint --vote-accounts-file fleet.txt                             \\
+      --deny all --allow leave-epoch-set
+";
+
+pub const PLAN_COMMISSION_HELP : &str = "
+'solana-vamp plan-commission' computes how the commission of a vote account
+under control of the Vote Account Manager program may change in future epochs,
+given the vote account's current commission and the commission caps enforced
+by the program.  No transactions are submitted.
+
+The following arguments are required:
+
+  --vote-account: Must be the pubkey of the vote account under program
+      control, or the path to a keypair file from which the vote account
+      pubkey will be loaded.
+
+Exactly one of the following arguments must be provided:
+
+  --target: The commission to reach.  The epoch-by-epoch schedule of
+      set-commission values required to reach TARGET_COMMISSION as quickly as
+      the program allows is printed, along with the earliest epoch at which
+      TARGET_COMMISSION can be reached.  The schedule begins in the current
+      epoch.
+
+  --epoch: An epoch no earlier than the current epoch.  The highest commission
+      that the vote account could be charging at EPOCH is printed.
+
+The following arguments may be optionally provided:
+
+  --json: The output format will be JSON; if this argument is not provided, it
+      will be human readable lines.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+The vote account's current commission is assumed to be the commission that
+was in effect at the beginning of the current epoch.  If commission has
+already been increased during the current epoch, then the first step of the
+schedule may not be allowed until the following epoch.  If a leave epoch has
+been set, then the program does not allow commission to be changed at all.
+
+Examples:
+
+# Show how to raise the commission of vote account
+# 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz to 10%.
+
+$ solana-vamp plan-commission                                                 \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --target 10
+
+Current Epoch: 500
+Current Commission: 5
+Max Commission: 10
+Max Commission Increase per Epoch: 2
+
+Epoch 500: set commission to 7
+Epoch 501: set commission to 9
+Epoch 502: set commission to 10
+
+Commission 10 can be reached at epoch 502
+
+# Show the highest commission that the same vote account could be charging at
+# epoch 501.
+
+$ solana-vamp plan-commission                                                 \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --epoch 501
+";
+
+pub const RAMP_COMMISSION_HELP : &str = "
+'solana-vamp ramp-commission' changes the commission of a vote account to
+TARGET_COMMISSION, taking as many epochs as are required by the commission
+caps enforced by the Vote Account Manager program.  In each epoch, the largest
+change allowed by the program is made with a set-commission transaction, and
+then solana-vamp waits for the next epoch to begin.  solana-vamp exits once
+the vote account's commission is TARGET_COMMISSION.
+
+The following arguments are required:
+
+  --rewards-authority: Must be the keypair of the rewards authority of the
+      vote account.  Only this keypair retains authority to change commission
+      of the vote account.
+
+  --vote-account: Must be the pubkey of the vote account under program
+      control, or the path to a keypair file from which the vote account
+      pubkey will be loaded.
+
+  --target: The commission to reach.
+
+The following arguments may be optionally provided:
+
+  --state-file: The path of the file in which progress is recorded.  If this
+      argument is not present, ramp-commission-<VOTE_ACCOUNT>.state in the
+      current directory is used.
+
+  --poll-interval: The number of seconds to wait between checks for a new
+      epoch.  If this argument is not present, 60 seconds is used.
+
+  --fee-payer: Will set the fee payer for the transaction to the keypair
+      stored in the given file.  If this argument is not present, the
+      --rewards-authority will be used as the fee payer.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+The state file records the target and the most recent commission change, and
+is written before each set-commission transaction is submitted.  If
+ramp-commission is interrupted, running the same command again resumes the
+ramp: a change that was already made in the current epoch is not repeated,
+and a change that was submitted but did not take effect is retried.  The
+state file is removed once the target is reached.  A state file for a
+different vote account or target is never overwritten; remove it to start a
+new ramp.
+
+Example:
+
+# Raise the commission of vote account
+# 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz to 10%, as quickly as its
+# commission caps allow.  The rewards authority is provided in
+# rewards_authority.json.
+
+$ solana-vamp ramp-commission                                                 \\
+              --rewards-authority rewards_authority.json                      \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --target 10
+";
+
+pub const DAEMON_HELP : &str = "
+'solana-vamp daemon' runs until killed, periodically checking the balance of
+the vote account and withdrawing from it to RECIPIENT_ACCOUNT.  Every check
+and withdraw is logged to standard output with a UTC timestamp.
+
+The following arguments are required:
+
+  --rewards-authority: Must be the keypair of the rewards authority of the
+      vote account.  Only this keypair retains authority to withdraw from the
+      vote account.
+
+  --vote-account: Must be the pubkey of the vote account under program
+      control, or the path to a keypair file from which the vote account
+      pubkey will be loaded.
+
+  --recipient: Must be the pubkey of the account into which the SOL will be
+      withdrawn, or the path to a keypair file from which the recipient pubkey
+      will be read.
+
+The following arguments may be optionally provided:
+
+  --threshold: The minimum quantity of SOL to withdraw, as a decimal number
+      with at most 9 fractional digits.  No withdraw is made until at least
+      this much SOL can be withdrawn.  If this argument is not present, any
+      withdrawable SOL is withdrawn.
+
+  --buffer: The quantity of SOL to always leave in the vote account, in
+      addition to its rent exempt minimum, as a decimal number with at most 9
+      fractional digits.  If this argument is not present, only the rent
+      exempt minimum is left in the vote account.
+
+  --every-epoch: If present, the vote account is checked only once per epoch,
+      at the first check after each new epoch begins, rather than at every
+      poll.
+
+  --poll-interval: The number of seconds to wait between checks.  If this
+      argument is not present, 60 seconds is used.
+
+  --fee-payer: Will set the fee payer for the transaction to the keypair
+      stored in the given file.  If this argument is not present, the
+      --rewards-authority will be used as the fee payer.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+If an RPC request or transaction fails, the failure is logged and retried,
+waiting twice as long after each consecutive failure, up to 10 minutes.
+
+Examples:
+
+# Withdraw from vote account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz to
+# user_key.json whenever at least 5 SOL can be withdrawn, always leaving 1 SOL
+# in the vote account.  The rewards authority is provided in
+# rewards_authority.json.
+
+$ solana-vamp daemon                                                          \\
+              --rewards-authority rewards_authority.json                      \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --recipient user_key.json                                       \\
+              --threshold 5                                                   \\
+              --buffer 1
+
+# Run against a local test validator, checking every 5 seconds.
+
+$ solana-vamp daemon                                                          \\
+              --url localhost                                                 \\
+              --rewards-authority rewards_authority.json                      \\
+              --vote-account vote_account.json                                \\
+              --recipient user_key.json                                       \\
+              --poll-interval 5
+";
+
+pub const WITHDRAW_AND_STAKE_HELP : &str = "
+'solana-vamp withdraw-and-stake' withdraws SOL from the vote account and uses
+it to create a new stake account which is delegated to the same vote account.
+This is all done in a single transaction: the SOL is withdrawn to the fee
+payer, which then funds the new stake account.
+
+The following arguments are required:
+
+  --rewards-authority: Must be the keypair of the rewards authority of the
+      vote account.  Only this keypair retains authority to withdraw from the
+      vote account.
+
+  --vote-account: Must be the pubkey of the vote account under program
+      control, or the path to a keypair file from which the vote account
+      pubkey will be loaded.
+
+The following arguments may be optionally provided:
+
+  --amount: The quantity of SOL to withdraw and stake, as for
+      'solana-vamp withdraw', including the special values ALL and
+      ALL-BUT <SOL>.  If neither --amount nor --lamports is present, then ALL
+      is used.  The amount must be enough to fund a rent exempt stake account
+      and the minimum stake delegation.
+
+  --lamports: The quantity of SOL to withdraw and stake, given as a whole
+      number of lamports.  May not be combined with --amount.
+
+  --stake-account: The path to the keypair file of the new stake account.
+      The keypair is only used to create the stake account.
+
+  --seed: A seed from which the new stake account address is derived, using
+      the fee payer as the base address, as for 'solana create-stake-account
+      --seed'.  May not be combined with --stake-account.  If neither
+      --stake-account nor --seed is present, a new stake account keypair is
+      generated and discarded after use.
+
+  --stake-authority: The path to the keypair file of the stake authority and
+      withdraw authority of the new stake account.  If this argument is not
+      present, the fee payer is used.
+
+  --fee-payer: Will set the fee payer for the transaction to the keypair
+      stored in the given file.  If this argument is not present, the
+      --rewards-authority will be used as the fee payer.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+Example:
+
+# Withdraw all available funds from vote account
+# 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz and stake them back to it, in a
+# stake account derived from the fee payer with seed \"epoch-512\".  The
+# rewards authority is provided in rewards_authority.json.
+
+$ solana-vamp withdraw-and-stake                                              \\
+              --rewards-authority rewards_authority.json                      \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --fee-payer fee_payer.json                                      \\
+              --seed epoch-512
+";
+
+pub const HISTORY_HELP : &str = "
+'solana-vamp history' prints a timeline of the Vote Account Manager program
+instructions that have been issued for a vote account: entering, leaving,
+authority changes, withdrawals and commission changes.  For each instruction
+the time, slot, whether the transaction succeeded, the accounts that signed
+the instruction, the parameters of the instruction, and the transaction
+signature are shown, oldest first.  No transactions are submitted.
+
+The following arguments are required:
+
+  --vote-account: Must be the pubkey of the vote account, or the path to a
+      keypair file from which the vote account pubkey will be loaded.
+
+The following arguments may be optionally provided:
+
+  --limit: The number of most recent transactions to examine.  If this
+      argument is not provided, 100 is used.
+
+  --json: The output format will be a JSON array; if this argument is not
+      provided, it will be human readable lines.
+
+  --url: Will set the URL of the RPC endpoint to send transactions to.  A full
+      URL may be specified, and in addition, the following special values may
+      be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+Transactions are found via the vote account's manager state account, so only
+transactions that include a Vote Account Manager program instruction are
+examined.  Older transactions may not be available from RPC endpoints that do
+not keep full transaction history.
+
+Example:
+
+# Show the last 20 Vote Account Manager transactions for vote account
+# 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz.
+
+$ solana-vamp history                                                         \\
+              --vote-account 3yP1VFUXzgND1UoLiVeu5AST46Ze6FVnR4DH7DDrgYTz     \\
+              --limit 20
+";
+
+pub const DECODE_HELP : &str = "
+'solana-vamp decode' prints the contents of a transaction, describing each
+Vote Account Manager program instruction within it: the action that it takes,
+its parameters, and each of its accounts named by role (vote account manager
+state account, vote account, withdraw authority, and so on).  It is intended
+for reviewing a transaction before signing it.  No transactions are
+submitted.
+
+The following argument is required:
+
+  SIGNATURE | TRANSACTION | MESSAGE: One of:
+      - The signature of a transaction already on the ledger, which will be
+        fetched from the RPC endpoint
+      - A serialized transaction, encoded as base64 or base58
+      - A serialized transaction message, encoded as base64 or base58
+
+The following arguments may be optionally provided:
+
+  --url: Will set the URL of the RPC endpoint to fetch transactions and
+      address lookup tables from.  A full URL may be specified, and in
+      addition, the following special values may be used:
+        l, localhost: http://localhost:8899
+        d, devnet: https://api.devnet.solana.com
+        t, testnet: https://api.testnet.solana.com
+        m, mainnet: https://api.mainnet-beta.solana.com
+      If --url is not supplied, then the URL is taken from the environment or
+      configuration as described by 'solana-vamp help', and otherwise mainnet
+      is used.
+
+For serialized transactions, each required signature is shown along with
+whether it is missing or invalid.  Instructions for other programs are listed
+but not decoded.  A warning is printed for any Vote Account Manager
+instruction whose data cannot be decoded, which has too few accounts, or whose
+manager state account does not belong to its vote account.
+
+Example:
+
+# Review a transaction proposed for signing by a multisig member
+
+$ solana-vamp decode AQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA...
+";
+
+pub const COMPLETIONS_HELP : &str = "
+'solana-vamp completions' prints a script which completes solana-vamp command
+names, argument names, and file paths for arguments which are keypair files or
+other files, in the given shell.  The script is generated from the same
+definitions of the commands as the usage messages of 'solana-vamp help'.
+
+The following argument is required:
+
+  bash | zsh | fish: The shell for which to print a completion script.
+
+Examples:
+
+# Enable completion in the current bash shell
+
+$ source <(solana-vamp completions bash)
+
+# Install completion for zsh, in a directory listed in $fpath
+
+$ solana-vamp completions zsh > ~/.zsh/completions/_solana-vamp
+
+# Install completion for fish
+
+$ solana-vamp completions fish > ~/.config/fish/completions/solana-vamp.fish
+";
+
+pub const MAN_HELP : &str = "
+'solana-vamp man' prints a man page, in roff format, describing every
+solana-vamp command.  The man page is generated from the same text as the
+usage messages of 'solana-vamp help'.
+
+Example:
+
+# Install the man page
+
+$ solana-vamp man > /usr/local/share/man/man1/solana-vamp.1
 ";
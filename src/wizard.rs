@@ -0,0 +1,468 @@
+// Guided entry of a vote account into the Vote Account Manager program, for 'solana-vamp enter --interactive'.  The
+// vote account is fetched so that its current state can be shown, and each choice is explained and checked before
+// the equivalent non-interactive command is shown and the user is asked whether to submit it.
+
+use crate::rpc::Rpc;
+use crate::{get_multiple_accounts, load_keypair_helper, make_pubkey_helper};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::vote::state::VoteState;
+use solana_vamp::instruction::get_vote_account_manager_state_account;
+use std::io::{BufRead, Write};
+
+// The arguments of enter that were supplied on the command line or taken from the environment
+pub struct EnterArgs
+{
+    pub vote_account : Option<Pubkey>,
+
+    pub withdraw_authority : Option<String>,
+
+    pub administrator : Option<String>,
+
+    pub max_commission : Option<u8>,
+
+    pub max_commission_increase_per_epoch : Option<u8>,
+
+    pub fee_payer : Option<String>
+}
+
+// The arguments of enter as confirmed by the user
+#[derive(Debug, PartialEq, Eq)]
+pub struct EnterChoices
+{
+    pub vote_account : Pubkey,
+
+    // The path to the withdraw authority keypair
+    pub withdraw_authority : String,
+
+    pub administrator : Pubkey,
+
+    // (max commission, max commission increase per epoch)
+    pub commission_caps : Option<(u8, u8)>,
+
+    // The path to the fee payer keypair, if not the withdraw authority
+    pub fee_payer : Option<String>
+}
+
+const WITHDRAW_AUTHORITY_EXPLANATION : &str = "
+The withdraw authority of the vote account signs the enter transaction.  The
+vote account's withdraw authority is then replaced by one controlled by the
+program, but this keypair must be kept: it is needed to set a leave epoch, to
+leave, and to set the administrator.
+";
+
+const ADMINISTRATOR_EXPLANATION : &str = "
+The administrator initially has authority for all actions except
+set-leave-epoch, leave, and set-administrator.  It may be a pubkey, or the
+path to a keypair file from which the pubkey will be loaded.
+";
+
+const COMMISSION_CAPS_EXPLANATION : &str = "
+The program can enforce a maximum commission, and a maximum increase in
+commission per epoch, on the vote account.  These caps are a commitment to
+stakers: once set, the vote account cannot leave control by the program until
+a leave epoch has been set with set-leave-epoch and that epoch has been
+reached.
+";
+
+pub fn enter(
+    rpc_client : &dyn Rpc,
+    program_id : &Pubkey,
+    url : &str,
+    supplied : EnterArgs,
+    input : &mut dyn BufRead,
+    output : &mut dyn Write
+) -> Result<Option<EnterChoices>, String>
+{
+    let mut prompter = Prompter { input, output };
+
+    let vote_account = match supplied.vote_account {
+        Some(vote_account) => vote_account,
+        None => prompter.ask("Vote account (pubkey or keypair file): ", make_pubkey_helper)?
+    };
+
+    let vote_account_manager_state_account = get_vote_account_manager_state_account(program_id, &vote_account);
+
+    let accounts = get_multiple_accounts(rpc_client, &[vote_account_manager_state_account, vote_account]);
+
+    if accounts[0].is_some() {
+        return Err(format!("{} is already managed by the Vote Account Manager program", vote_account));
+    }
+
+    let vote_state = accounts[1]
+        .as_ref()
+        .and_then(|account| VoteState::deserialize(&account.data).ok())
+        .ok_or_else(|| format!("{} is not a vote account", vote_account))?;
+
+    prompter.say(&format!(
+        "\nVote account {}\n  Commission: {}%\n  Withdraw authority: {}\n",
+        vote_account, vote_state.commission, vote_state.authorized_withdrawer
+    ))?;
+
+    prompter.say(WITHDRAW_AUTHORITY_EXPLANATION)?;
+
+    let check_withdraw_authority = |path : &str| -> Result<String, String> {
+        let pubkey = load_keypair_helper(path)?.pubkey();
+        if pubkey != vote_state.authorized_withdrawer {
+            return Err(format!(
+                "{} holds {}, which is not the withdraw authority {}",
+                path, pubkey, vote_state.authorized_withdrawer
+            ));
+        }
+        Ok(path.to_string())
+    };
+
+    let withdraw_authority = match supplied.withdraw_authority {
+        Some(withdraw_authority) => check_withdraw_authority(&withdraw_authority)?,
+        None => prompter.ask("Withdraw authority keypair file: ", check_withdraw_authority)?
+    };
+
+    prompter.say(ADMINISTRATOR_EXPLANATION)?;
+
+    let administrator = match supplied.administrator {
+        Some(administrator) => {
+            make_pubkey_helper(&administrator).map_err(|e| format!("Invalid administrator {}: {}", administrator, e))?
+        },
+        None => prompter.ask("Administrator (pubkey or keypair file): ", make_pubkey_helper)?
+    };
+
+    prompter.say(COMMISSION_CAPS_EXPLANATION)?;
+
+    let check_max_commission = |max_commission : u8| -> Result<u8, String> {
+        if max_commission < vote_state.commission {
+            return Err(format!(
+                "The max commission must be at least the current commission of {}%",
+                vote_state.commission
+            ));
+        }
+        Ok(max_commission)
+    };
+
+    // Both caps are set or neither is; a cap supplied on the command line means that both are to be set
+    let commission_caps = match (supplied.max_commission, supplied.max_commission_increase_per_epoch) {
+        (None, None) if !prompter.confirm("Enforce commission caps? [y/n] ")? => None,
+        (max_commission, max_commission_increase_per_epoch) => {
+            let max_commission = match max_commission {
+                Some(max_commission) => check_max_commission(max_commission)?,
+                None => prompter.ask("Max commission (percent): ", |s| check_max_commission(parse_percent(s)?))?
+            };
+            let max_commission_increase_per_epoch = match max_commission_increase_per_epoch {
+                Some(max_commission_increase_per_epoch) => max_commission_increase_per_epoch,
+                None => prompter.ask("Max commission increase per epoch (percent): ", parse_percent)?
+            };
+            Some((max_commission, max_commission_increase_per_epoch))
+        }
+    };
+
+    let choices = EnterChoices {
+        vote_account,
+        withdraw_authority,
+        administrator,
+        commission_caps,
+        fee_payer : supplied.fee_payer
+    };
+
+    prompter.say(&format!("\nThe equivalent command is:\n\n$ {}\n\n", equivalent_command(&choices, url)))?;
+
+    if prompter.confirm("Submit this transaction? [y/n] ")? {
+        Ok(Some(choices))
+    }
+    else {
+        prompter.say("Not submitted.\n")?;
+        Ok(None)
+    }
+}
+
+fn parse_percent(s : &str) -> Result<u8, String>
+{
+    match s.parse::<u8>() {
+        Ok(percent) if percent <= 100 => Ok(percent),
+        _ => Err(format!("{} is not a percentage from 0 to 100", s))
+    }
+}
+
+fn equivalent_command(
+    choices : &EnterChoices,
+    url : &str
+) -> String
+{
+    let mut args = vec![
+        ("--withdraw-authority", choices.withdraw_authority.clone()),
+        ("--vote-account", choices.vote_account.to_string()),
+        ("--administrator", choices.administrator.to_string()),
+    ];
+
+    if let Some((max_commission, max_commission_increase_per_epoch)) = choices.commission_caps {
+        args.push(("--max-commission", max_commission.to_string()));
+        args.push(("--max-commission-increase-per-epoch", max_commission_increase_per_epoch.to_string()));
+    }
+
+    if let Some(fee_payer) = &choices.fee_payer {
+        args.push(("--fee-payer", fee_payer.clone()));
+    }
+
+    args.push(("--url", url.to_string()));
+
+    args.iter().fold("solana-vamp enter".to_string(), |command, (flag, value)| {
+        format!("{} {} {}", command, flag, shell_quote(value))
+    })
+}
+
+// Quotes a value for a POSIX shell, if it needs quoting
+fn shell_quote(value : &str) -> String
+{
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c)) {
+        value.to_string()
+    }
+    else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+struct Prompter<'a>
+{
+    input : &'a mut dyn BufRead,
+
+    output : &'a mut dyn Write
+}
+
+impl Prompter<'_>
+{
+    fn say(
+        &mut self,
+        text : &str
+    ) -> Result<(), String>
+    {
+        self.output.write_all(text.as_bytes()).and_then(|_| self.output.flush()).map_err(|e| e.to_string())
+    }
+
+    // Prompts until a line of input is accepted by parse, showing the reason for each rejection
+    fn ask<T>(
+        &mut self,
+        prompt : &str,
+        parse : impl Fn(&str) -> Result<T, String>
+    ) -> Result<T, String>
+    {
+        loop {
+            self.say(prompt)?;
+
+            let mut line = String::new();
+
+            if self.input.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+                return Err("Input ended before all choices were made; nothing was submitted".to_string());
+            }
+
+            match parse(line.trim()) {
+                Ok(value) => return Ok(value),
+                Err(e) => self.say(&format!("  {}\n", e))?
+            }
+        }
+    }
+
+    fn confirm(
+        &mut self,
+        prompt : &str
+    ) -> Result<bool, String>
+    {
+        self.ask(prompt, |answer| match answer.to_ascii_lowercase().as_str() {
+            "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err("Please answer y or n".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::rpc::mock::{KeypairFile, MockRpc};
+    use crate::VAMP_PROGRAM_PUBKEY;
+    use std::str::FromStr;
+
+    struct Setup
+    {
+        rpc : MockRpc,
+
+        program_id : Pubkey,
+
+        vote_account : Pubkey,
+
+        // The keypair file of the withdraw authority of the vote account
+        withdraw_authority : KeypairFile,
+
+        // The keypair file of some other keypair
+        other_keypair : KeypairFile
+    }
+
+    fn setup(commission : u8) -> Setup
+    {
+        let rpc = MockRpc::new();
+        let vote_account = Pubkey::new_unique();
+        let withdraw_authority = KeypairFile::new();
+
+        rpc.set_vote_account(vote_account, withdraw_authority.pubkey(), commission, 1_000_000_000);
+
+        Setup {
+            rpc,
+            program_id : Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap(),
+            vote_account,
+            withdraw_authority,
+            other_keypair : KeypairFile::new()
+        }
+    }
+
+    fn nothing_supplied() -> EnterArgs
+    {
+        EnterArgs {
+            vote_account : None,
+            withdraw_authority : None,
+            administrator : None,
+            max_commission : None,
+            max_commission_increase_per_epoch : None,
+            fee_payer : None
+        }
+    }
+
+    // Runs the wizard with the given lines of input, returning its result and output
+    fn run(
+        setup : &Setup,
+        supplied : EnterArgs,
+        lines : &[&str]
+    ) -> (Result<Option<EnterChoices>, String>, String)
+    {
+        let input = lines.iter().map(|line| format!("{}\n", line)).collect::<String>();
+
+        let mut output = vec![];
+
+        let result =
+            enter(&setup.rpc, &setup.program_id, "http://localhost:8899", supplied, &mut input.as_bytes(), &mut output);
+
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn prompts_for_and_checks_each_choice()
+    {
+        let setup = setup(7);
+        let administrator = Pubkey::new_unique();
+
+        let (result, output) = run(&setup, nothing_supplied(), &[
+            &setup.vote_account.to_string(),
+            &setup.other_keypair.path,
+            &setup.withdraw_authority.path,
+            "not an administrator",
+            &administrator.to_string(),
+            "maybe",
+            "y",
+            "5",
+            "101",
+            "10",
+            "3",
+            "y"
+        ]);
+
+        assert_eq!(
+            result.unwrap(),
+            Some(EnterChoices {
+                vote_account : setup.vote_account,
+                withdraw_authority : setup.withdraw_authority.path.clone(),
+                administrator,
+                commission_caps : Some((10, 3)),
+                fee_payer : None
+            })
+        );
+
+        assert!(output.contains("  Commission: 7%\n"));
+        assert!(output.contains("which is not the withdraw authority"));
+        assert!(output.contains("Please answer y or n"));
+        assert!(output.contains("The max commission must be at least the current commission of 7%"));
+        assert!(output.contains("101 is not a percentage from 0 to 100"));
+        assert!(output.contains(&format!(
+            "$ solana-vamp enter --withdraw-authority {} --vote-account {} --administrator {} --max-commission 10 \
+             --max-commission-increase-per-epoch 3 --url http://localhost:8899\n",
+            setup.withdraw_authority.path, setup.vote_account, administrator
+        )));
+    }
+
+    #[test]
+    fn supplied_arguments_are_checked_and_not_prompted_for()
+    {
+        let setup = setup(7);
+        let administrator = Pubkey::new_unique();
+
+        let supplied_with = |max_commission| EnterArgs {
+            vote_account : Some(setup.vote_account),
+            withdraw_authority : Some(setup.withdraw_authority.path.clone()),
+            administrator : Some(administrator.to_string()),
+            max_commission,
+            fee_payer : Some("fee payer.json".to_string()),
+            ..nothing_supplied()
+        };
+
+        // A cap supplied alone requires the other, which is prompted for
+        let (result, output) = run(&setup, supplied_with(Some(8)), &["2", "y"]);
+
+        assert_eq!(result.unwrap().unwrap().commission_caps, Some((8, 2)));
+        assert!(!output.contains("Enforce commission caps?"));
+        assert!(output.contains(" --fee-payer 'fee payer.json' "));
+
+        let (result, _) = run(&setup, supplied_with(Some(6)), &[]);
+
+        assert_eq!(result.unwrap_err(), "The max commission must be at least the current commission of 7%");
+
+        let (result, _) = run(
+            &setup,
+            EnterArgs { withdraw_authority : Some(setup.other_keypair.path.clone()), ..supplied_with(None) },
+            &[]
+        );
+
+        assert!(result.unwrap_err().contains("which is not the withdraw authority"));
+    }
+
+    #[test]
+    fn nothing_is_chosen_unless_confirmed()
+    {
+        let setup = setup(7);
+
+        let supplied = EnterArgs {
+            vote_account : Some(setup.vote_account),
+            withdraw_authority : Some(setup.withdraw_authority.path.clone()),
+            administrator : Some(Pubkey::new_unique().to_string()),
+            ..nothing_supplied()
+        };
+
+        let (result, output) = run(&setup, supplied, &["n", "n"]);
+
+        assert_eq!(result.unwrap(), None);
+        assert!(output.contains("Not submitted."));
+
+        let (result, _) = run(&setup, EnterArgs { vote_account : Some(setup.vote_account), ..nothing_supplied() }, &[]);
+
+        assert_eq!(result.unwrap_err(), "Input ended before all choices were made; nothing was submitted");
+    }
+
+    #[test]
+    fn managed_vote_accounts_are_rejected()
+    {
+        let setup = setup(7);
+
+        let vote_account_manager_state_account =
+            get_vote_account_manager_state_account(&setup.program_id, &setup.vote_account);
+
+        setup.rpc.set_manager_state(
+            setup.program_id,
+            vote_account_manager_state_account,
+            Pubkey::new_unique(),
+            None,
+            0
+        );
+
+        let (result, _) = run(&setup, EnterArgs { vote_account : Some(setup.vote_account), ..nothing_supplied() }, &[]);
+
+        assert_eq!(
+            result.unwrap_err(),
+            format!("{} is already managed by the Vote Account Manager program", setup.vote_account)
+        );
+    }
+}
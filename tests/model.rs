@@ -0,0 +1,312 @@
+// Property tests which apply random sequences of instructions, built by the instruction builders, to the model of
+// the vamp program, and check that the program's rules always hold
+
+use proptest::prelude::*;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_vamp::instruction;
+use solana_vamp::model::{Model, ModelError, VoteAccountModel};
+use solana_vamp::transaction_data::VampInstruction;
+
+const RENT_EXEMPT_MINIMUM : u64 = 2_700_000;
+
+// The parties that may sign instructions; instructions are built with the key of one of these as the authority
+const KEY_COUNT : u8 = 4;
+
+fn key(i : u8) -> Pubkey
+{
+    Pubkey::new_from_array([i + 1; 32])
+}
+
+#[derive(Clone, Debug)]
+enum Op
+{
+    Enter(u8, u8, Option<(u8, u8)>),
+    SetLeaveEpoch(u8, u64),
+    Leave(u8),
+    SetAdministrator(u8, u8),
+    SetOperationalAuthority(u8, u8),
+    SetRewardsAuthority(u8, u8),
+    SetVoteAuthority(u8, u8),
+    SetValidatorIdentity(u8, u8),
+    Withdraw(u8, u64),
+    SetCommission(u8, u8),
+    AdvanceEpochs(u64)
+}
+
+fn any_key() -> impl Strategy<Value = u8>
+{
+    0..KEY_COUNT
+}
+
+fn any_op() -> impl Strategy<Value = Op>
+{
+    prop_oneof![
+        (any_key(), any_key(), proptest::option::of((0_u8..=110, 0_u8..=20)))
+            .prop_map(|(withdraw_authority, administrator, caps)| Op::Enter(withdraw_authority, administrator, caps)),
+        (any_key(), 0_u64..10).prop_map(|(k, offset)| Op::SetLeaveEpoch(k, offset)),
+        any_key().prop_map(Op::Leave),
+        (any_key(), any_key()).prop_map(|(k, n)| Op::SetAdministrator(k, n)),
+        (any_key(), any_key()).prop_map(|(k, n)| Op::SetOperationalAuthority(k, n)),
+        (any_key(), any_key()).prop_map(|(k, n)| Op::SetRewardsAuthority(k, n)),
+        (any_key(), any_key()).prop_map(|(k, n)| Op::SetVoteAuthority(k, n)),
+        (any_key(), any_key()).prop_map(|(k, n)| Op::SetValidatorIdentity(k, n)),
+        (any_key(), 0_u64..2_000_000).prop_map(|(k, lamports)| Op::Withdraw(k, lamports)),
+        (any_key(), 0_u8..=110).prop_map(|(k, commission)| Op::SetCommission(k, commission)),
+        (0_u64..4).prop_map(Op::AdvanceEpochs)
+    ]
+}
+
+fn new_model(
+    commission : u8,
+    lamports : u64
+) -> Model
+{
+    Model::new(Pubkey::new_unique(), Pubkey::new_unique(), 100, VoteAccountModel {
+        node_pubkey : key(0),
+        authorized_voter : key(0),
+        authorized_withdrawer : key(0),
+        commission,
+        lamports : RENT_EXEMPT_MINIMUM + lamports,
+        rent_exempt_minimum : RENT_EXEMPT_MINIMUM
+    })
+}
+
+// Builds the instruction for an op, or returns None for ops which are not instructions
+fn build(
+    model : &Model,
+    op : &Op
+) -> Option<Instruction>
+{
+    let program_id = model.program_id;
+    let vote_account = model.vote_account;
+    let manager = model.manager_state_account();
+    let recipient = Pubkey::new_from_array([0xFF; 32]);
+
+    Some(match *op {
+        Op::Enter(k, administrator, caps) => {
+            instruction::enter(program_id, vote_account, manager, recipient, key(k), key(administrator), caps)
+        },
+        Op::SetLeaveEpoch(k, offset) => {
+            instruction::set_leave_epoch(program_id, vote_account, manager, key(k), model.epoch + offset)
+        },
+        Op::Leave(k) => instruction::leave(program_id, vote_account, manager, key(k), recipient),
+        Op::SetAdministrator(k, n) => {
+            instruction::set_authority(program_id, vote_account, manager, key(k), VampInstruction::SetAdministrator {
+                new_administrator : key(n).to_bytes()
+            })
+        },
+        Op::SetOperationalAuthority(k, n) => instruction::set_authority(
+            program_id,
+            vote_account,
+            manager,
+            key(k),
+            VampInstruction::SetOperationalAuthority { new_authority : key(n).to_bytes() }
+        ),
+        Op::SetRewardsAuthority(k, n) => instruction::set_authority(
+            program_id,
+            vote_account,
+            manager,
+            key(k),
+            VampInstruction::SetRewardsAuthority { new_authority : key(n).to_bytes() }
+        ),
+        Op::SetVoteAuthority(k, n) => {
+            instruction::set_vote_authority(program_id, vote_account, manager, key(k), key(n))
+        },
+        Op::SetValidatorIdentity(k, n) => {
+            instruction::set_validator_identity(program_id, vote_account, manager, key(k), key(n))
+        },
+        Op::Withdraw(k, lamports) => {
+            instruction::withdraw(program_id, vote_account, manager, key(k), recipient, lamports)
+        },
+        Op::SetCommission(k, commission) => {
+            instruction::set_commission(program_id, vote_account, manager, key(k), commission)
+        },
+        Op::AdvanceEpochs(_) => return None
+    })
+}
+
+// The key which must sign an op for the program to accept it, if the vote account is managed
+fn required_signer(
+    model : &Model,
+    op : &Op
+) -> Option<Pubkey>
+{
+    let manager = model.manager.as_ref()?;
+
+    match op {
+        Op::SetLeaveEpoch(..) | Op::Leave(_) | Op::SetAdministrator(..) => Some(manager.withdraw_authority),
+        Op::SetOperationalAuthority(..) | Op::SetRewardsAuthority(..) => Some(manager.administrator),
+        Op::SetVoteAuthority(..) | Op::SetValidatorIdentity(..) => Some(manager.operational_authority),
+        Op::Withdraw(..) | Op::SetCommission(..) => Some(manager.rewards_authority),
+        Op::Enter(..) | Op::AdvanceEpochs(_) => None
+    }
+}
+
+fn signer(op : &Op) -> Option<Pubkey>
+{
+    match *op {
+        Op::Enter(k, ..) |
+        Op::SetLeaveEpoch(k, _) |
+        Op::Leave(k) |
+        Op::SetAdministrator(k, _) |
+        Op::SetOperationalAuthority(k, _) |
+        Op::SetRewardsAuthority(k, _) |
+        Op::SetVoteAuthority(k, _) |
+        Op::SetValidatorIdentity(k, _) |
+        Op::Withdraw(k, _) |
+        Op::SetCommission(k, _) => Some(key(k)),
+        Op::AdvanceEpochs(_) => None
+    }
+}
+
+proptest! {
+    #[test]
+    fn rules_hold_for_any_sequence(
+        commission in 0_u8..=100,
+        lamports in 0_u64..5_000_000,
+        ops in proptest::collection::vec(any_op(), 1..60)
+    ) {
+        let mut model = new_model(commission, lamports);
+
+        let total = model.vote.lamports;
+
+        let mut withdrawn = 0;
+
+        for op in &ops {
+            if let Op::AdvanceEpochs(epochs) = op {
+                model.advance_to_epoch(model.epoch + epochs);
+                continue;
+            }
+
+            let instruction = build(&model, op).unwrap();
+
+            let before = model.clone();
+
+            match model.process(&instruction) {
+                Ok(lamports) => {
+                    withdrawn += lamports;
+
+                    // Only the authority recorded in the manager state may act on a managed vote account
+                    if let Some(required) = required_signer(&before, op) {
+                        prop_assert_eq!(signer(op), Some(required));
+                    }
+
+                    if let Op::SetCommission(_, new_commission) = *op {
+                        let manager = before.manager.as_ref().unwrap();
+                        prop_assert_eq!(manager.leave_epoch, 0);
+                        if let Some((max_commission, max_increase)) = manager.commission_caps {
+                            prop_assert!(
+                                (new_commission <= before.vote.commission) ||
+                                    ((new_commission <= max_commission) &&
+                                        (new_commission as u16 <=
+                                            before.epoch_start_commission as u16 + max_increase as u16))
+                            );
+                        }
+                    }
+
+                    if let Op::SetLeaveEpoch(_, offset) = *op {
+                        prop_assert!(offset >= 2);
+                    }
+
+                    if let Op::Leave(_) = op {
+                        let manager = before.manager.as_ref().unwrap();
+                        if manager.commission_caps.is_some() {
+                            prop_assert!((manager.leave_epoch != 0) && (before.epoch >= manager.leave_epoch));
+                        }
+                    }
+                },
+                Err(_) => {
+                    // Rejected instructions change nothing
+                    prop_assert_eq!(&model, &before);
+                }
+            }
+
+            // The vote account never goes below its rent exempt minimum, and lamports are never created or lost
+            prop_assert!(model.vote.lamports >= RENT_EXEMPT_MINIMUM);
+            prop_assert_eq!(model.vote.lamports + withdrawn, total);
+
+            // The program holds the withdraw authority exactly while the vote account is managed
+            prop_assert_eq!(
+                model.vote.authorized_withdrawer == model.manager_state_account(),
+                model.manager.is_some()
+            );
+
+            prop_assert!(model.vote.commission <= 100);
+        }
+    }
+
+    #[test]
+    fn enter_and_leave_restores_withdraw_authority(
+        withdraw_authority in any_key(),
+        administrator in any_key(),
+        caps in proptest::option::of((0_u8..=100, 1_u8..=20)),
+        leave_offset in 2_u64..10
+    ) {
+        let mut model = new_model(5, 0);
+        model.vote.authorized_withdrawer = key(withdraw_authority);
+
+        let enter = build(&model, &Op::Enter(withdraw_authority, administrator, caps)).unwrap();
+
+        prop_assert!(model.process(&enter).is_ok());
+        prop_assert_eq!(model.process(&enter), Err(ModelError::AlreadyManaged));
+
+        let manager = model.manager.clone().unwrap();
+        prop_assert_eq!(manager.operational_authority, key(administrator));
+        prop_assert_eq!(manager.rewards_authority, key(administrator));
+
+        if caps.is_some() {
+            prop_assert_eq!(
+                model.process(&build(&model, &Op::Leave(withdraw_authority)).unwrap()),
+                Err(ModelError::LeaveNotAllowed)
+            );
+            prop_assert!(
+                model.process(&build(&model, &Op::SetLeaveEpoch(withdraw_authority, leave_offset)).unwrap()).is_ok()
+            );
+            model.advance_to_epoch(model.epoch + leave_offset);
+        }
+
+        prop_assert!(model.process(&build(&model, &Op::Leave(withdraw_authority)).unwrap()).is_ok());
+        prop_assert_eq!(model.vote.authorized_withdrawer, key(withdraw_authority));
+        prop_assert!(model.manager.is_none());
+    }
+
+    #[test]
+    fn withdraw_zero_takes_everything_above_rent_exempt_minimum(lamports in 0_u64..u32::MAX as u64) {
+        let mut model = new_model(5, lamports);
+
+        model.process(&build(&model, &Op::Enter(0, 1, None)).unwrap()).unwrap();
+
+        prop_assert_eq!(model.process(&build(&model, &Op::Withdraw(1, 0)).unwrap()), Ok(lamports));
+        prop_assert_eq!(model.vote.lamports, RENT_EXEMPT_MINIMUM);
+        let withdraw = build(&model, &Op::Withdraw(1, 1)).unwrap();
+
+        prop_assert_eq!(model.process(&withdraw), Err(ModelError::InsufficientFunds));
+    }
+}
+
+#[test]
+fn rejects_misplaced_accounts()
+{
+    let mut model = new_model(5, 0);
+
+    let mut enter = build(&model, &Op::Enter(0, 1, None)).unwrap();
+    enter.accounts.swap(5, 6);
+    assert!(matches!(model.process(&enter), Err(ModelError::InvalidAccounts(_))));
+
+    let mut enter = build(&model, &Op::Enter(0, 1, None)).unwrap();
+    enter.accounts[0].pubkey = Pubkey::new_unique();
+    assert!(matches!(model.process(&enter), Err(ModelError::InvalidAccounts(_))));
+
+    let mut enter = build(&model, &Op::Enter(0, 1, None)).unwrap();
+    enter.accounts[3].is_signer = false;
+    assert_eq!(model.process(&enter), Err(ModelError::MissingSignature("withdraw authority")));
+
+    let mut enter = build(&model, &Op::Enter(0, 1, None)).unwrap();
+    enter.accounts.pop();
+    assert!(matches!(model.process(&enter), Err(ModelError::InvalidAccounts(_))));
+
+    let mut enter = build(&model, &Op::Enter(0, 1, None)).unwrap();
+    enter.program_id = Pubkey::new_unique();
+    assert_eq!(model.process(&enter), Err(ModelError::WrongProgram));
+}
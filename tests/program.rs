@@ -0,0 +1,519 @@
+// End-to-end tests of the instructions that solana-vamp issues, run against the Vote Account Manager program in a
+// solana-program-test bank.
+//
+// The program is loaded from the shared object file named by the VAMP_PROGRAM_SO environment variable, for example:
+//
+// $ VAMP_PROGRAM_SO=/path/to/vamp.so cargo test --test program
+//
+// If VAMP_PROGRAM_SO is not set, these tests skip themselves with a note and pass.  The same variable also enables
+// the bin's bank tests, which drive the solana-vamp commands themselves against the program.
+
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use solana_sdk::vote::instruction as vote_instruction;
+use solana_sdk::vote::state::{VoteInit, VoteState};
+use solana_vamp::instruction::{self, get_vote_account_manager_state_account};
+use solana_vamp::state::ManagerState;
+use solana_vamp::transaction_data::VampInstruction;
+use std::str::FromStr;
+
+const VAMP_PROGRAM_PUBKEY : &str = "vamp3angna1CBRcV6KqoxyaYw3mPybHEeoPLtmpS99N";
+
+const LAMPORTS_PER_SOL : u64 = 1_000_000_000;
+
+// Commission of the vote account when it is created
+const INITIAL_COMMISSION : u8 = 5;
+
+struct Validator
+{
+    vote_account : Pubkey,
+
+    manager : Pubkey,
+
+    identity : Keypair,
+
+    withdraw_authority : Keypair,
+
+    administrator : Keypair
+}
+
+// Starts a bank with the program loaded, or returns None if VAMP_PROGRAM_SO is not set
+async fn start() -> Option<ProgramTestContext>
+{
+    let Ok(path) = std::env::var("VAMP_PROGRAM_SO")
+    else {
+        eprintln!("VAMP_PROGRAM_SO is not set; skipping");
+        return None;
+    };
+
+    let data = std::fs::read(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+
+    let mut program_test = ProgramTest::default();
+
+    program_test.add_account(program_id(), Account {
+        lamports : Rent::default().minimum_balance(data.len()),
+        data,
+        owner : bpf_loader::id(),
+        executable : true,
+        rent_epoch : 0
+    });
+
+    Some(program_test.start_with_context().await)
+}
+
+fn program_id() -> Pubkey
+{
+    Pubkey::from_str(VAMP_PROGRAM_PUBKEY).unwrap()
+}
+
+async fn process(
+    context : &mut ProgramTestContext,
+    instructions : &[Instruction],
+    signers : &[&Keypair]
+) -> Result<(), BanksClientError>
+{
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+    let mut all_signers = vec![&payer];
+    all_signers.extend(signers);
+
+    // A new blockhash each time, so that retrying an identical transaction is not rejected as a duplicate
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all_signers, blockhash);
+
+    context.banks_client.process_transaction(tx).await
+}
+
+async fn current_epoch(context : &mut ProgramTestContext) -> u64
+{
+    context.banks_client.get_sysvar::<Clock>().await.unwrap().epoch
+}
+
+async fn warp_to_epoch(
+    context : &mut ProgramTestContext,
+    epoch : u64
+)
+{
+    let slot = context.genesis_config().epoch_schedule.get_first_slot_in_epoch(epoch);
+
+    context.warp_to_slot(slot).unwrap();
+}
+
+async fn lamports(
+    context : &mut ProgramTestContext,
+    pubkey : &Pubkey
+) -> u64
+{
+    context.banks_client.get_balance(*pubkey).await.unwrap()
+}
+
+async fn manager_state(
+    context : &mut ProgramTestContext,
+    manager : &Pubkey
+) -> ManagerState
+{
+    let account = context.banks_client.get_account(*manager).await.unwrap().expect("manager state account");
+
+    ManagerState::decode(&account.data).expect("manager state")
+}
+
+async fn vote_state(
+    context : &mut ProgramTestContext,
+    vote_account : &Pubkey
+) -> VoteState
+{
+    let account = context.banks_client.get_account(*vote_account).await.unwrap().expect("vote account");
+
+    VoteState::deserialize(&account.data).unwrap()
+}
+
+// Creates a vote account, funded with extra lamports beyond its rent exempt minimum
+async fn create_vote_account(context : &mut ProgramTestContext) -> Validator
+{
+    let vote_account = Keypair::new();
+
+    let identity = Keypair::new();
+
+    let withdraw_authority = Keypair::new();
+
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let instructions = vote_instruction::create_account(
+        &context.payer.pubkey(),
+        &vote_account.pubkey(),
+        &VoteInit {
+            node_pubkey : identity.pubkey(),
+            authorized_voter : identity.pubkey(),
+            authorized_withdrawer : withdraw_authority.pubkey(),
+            commission : INITIAL_COMMISSION
+        },
+        rent.minimum_balance(VoteState::size_of()) + LAMPORTS_PER_SOL
+    );
+
+    process(context, &instructions, &[&vote_account, &identity]).await.unwrap();
+
+    Validator {
+        vote_account : vote_account.pubkey(),
+        manager : get_vote_account_manager_state_account(&program_id(), &vote_account.pubkey()),
+        identity,
+        withdraw_authority,
+        administrator : Keypair::new()
+    }
+}
+
+async fn enter(
+    context : &mut ProgramTestContext,
+    validator : &Validator,
+    commission_caps : Option<(u8, u8)>
+) -> Result<(), BanksClientError>
+{
+    let instruction = instruction::enter(
+        program_id(),
+        validator.vote_account,
+        validator.manager,
+        context.payer.pubkey(),
+        validator.withdraw_authority.pubkey(),
+        validator.administrator.pubkey(),
+        commission_caps
+    );
+
+    process(context, &[instruction], &[&validator.withdraw_authority]).await
+}
+
+async fn set_commission(
+    context : &mut ProgramTestContext,
+    validator : &Validator,
+    rewards_authority : &Keypair,
+    commission : u8
+) -> Result<(), BanksClientError>
+{
+    let instruction = instruction::set_commission(
+        program_id(),
+        validator.vote_account,
+        validator.manager,
+        rewards_authority.pubkey(),
+        commission
+    );
+
+    process(context, &[instruction], &[rewards_authority]).await
+}
+
+async fn set_leave_epoch(
+    context : &mut ProgramTestContext,
+    validator : &Validator,
+    leave_epoch : u64
+) -> Result<(), BanksClientError>
+{
+    let instruction = instruction::set_leave_epoch(
+        program_id(),
+        validator.vote_account,
+        validator.manager,
+        validator.withdraw_authority.pubkey(),
+        leave_epoch
+    );
+
+    process(context, &[instruction], &[&validator.withdraw_authority]).await
+}
+
+async fn leave(
+    context : &mut ProgramTestContext,
+    validator : &Validator
+) -> Result<(), BanksClientError>
+{
+    let instruction = instruction::leave(
+        program_id(),
+        validator.vote_account,
+        validator.manager,
+        validator.withdraw_authority.pubkey(),
+        context.payer.pubkey()
+    );
+
+    process(context, &[instruction], &[&validator.withdraw_authority]).await
+}
+
+#[tokio::test]
+async fn enter_takes_control_of_vote_account()
+{
+    let Some(mut context) = start().await
+    else {
+        return;
+    };
+
+    let validator = create_vote_account(&mut context).await;
+
+    // Only the current withdraw authority can enter
+    let imposter = Validator { withdraw_authority : Keypair::new(), ..create_vote_account(&mut context).await };
+    assert!(enter(&mut context, &imposter, None).await.is_err());
+
+    enter(&mut context, &validator, Some((10, 2))).await.unwrap();
+
+    let state = manager_state(&mut context, &validator.manager).await;
+    assert_eq!(state.withdraw_authority, validator.withdraw_authority.pubkey());
+    assert_eq!(state.administrator, validator.administrator.pubkey());
+    assert_eq!(state.operational_authority, validator.administrator.pubkey());
+    assert_eq!(state.rewards_authority, validator.administrator.pubkey());
+    assert_eq!(state.commission_caps, Some((10, 2)));
+    assert_eq!(state.leave_epoch, 0);
+
+    let vote_state = vote_state(&mut context, &validator.vote_account).await;
+    assert_ne!(vote_state.authorized_withdrawer, validator.withdraw_authority.pubkey());
+
+    // Entering a second time is not allowed
+    assert!(enter(&mut context, &validator, Some((10, 2))).await.is_err());
+}
+
+#[tokio::test]
+async fn authority_changes()
+{
+    let Some(mut context) = start().await
+    else {
+        return;
+    };
+
+    let validator = create_vote_account(&mut context).await;
+
+    enter(&mut context, &validator, None).await.unwrap();
+
+    let set_authority = |authority : &Keypair, data : VampInstruction| {
+        instruction::set_authority(program_id(), validator.vote_account, validator.manager, authority.pubkey(), data)
+    };
+
+    // set-administrator requires the withdraw authority
+    let administrator = Keypair::new();
+    let data = VampInstruction::SetAdministrator { new_administrator : administrator.pubkey().to_bytes() };
+    assert!(process(&mut context, &[set_authority(&validator.administrator, data)], &[&validator.administrator])
+        .await
+        .is_err());
+    process(&mut context, &[set_authority(&validator.withdraw_authority, data)], &[&validator.withdraw_authority])
+        .await
+        .unwrap();
+    assert_eq!(manager_state(&mut context, &validator.manager).await.administrator, administrator.pubkey());
+
+    // set-operational-authority and set-rewards-authority require the administrator, which is now the new one
+    let operational_authority = Keypair::new();
+    let data = VampInstruction::SetOperationalAuthority { new_authority : operational_authority.pubkey().to_bytes() };
+    assert!(process(&mut context, &[set_authority(&validator.administrator, data)], &[&validator.administrator])
+        .await
+        .is_err());
+    process(&mut context, &[set_authority(&administrator, data)], &[&administrator]).await.unwrap();
+
+    let rewards_authority = Keypair::new();
+    let data = VampInstruction::SetRewardsAuthority { new_authority : rewards_authority.pubkey().to_bytes() };
+    process(&mut context, &[set_authority(&administrator, data)], &[&administrator]).await.unwrap();
+
+    let state = manager_state(&mut context, &validator.manager).await;
+    assert_eq!(state.operational_authority, operational_authority.pubkey());
+    assert_eq!(state.rewards_authority, rewards_authority.pubkey());
+
+    // set-vote-authority requires the operational authority
+    let vote_authority = Keypair::new();
+    let set_vote_authority = |authority : &Keypair| {
+        instruction::set_vote_authority(
+            program_id(),
+            validator.vote_account,
+            validator.manager,
+            authority.pubkey(),
+            vote_authority.pubkey()
+        )
+    };
+    assert!(process(&mut context, &[set_vote_authority(&administrator)], &[&administrator]).await.is_err());
+    process(&mut context, &[set_vote_authority(&operational_authority)], &[&operational_authority]).await.unwrap();
+
+    // set-validator-identity requires the operational authority and the new identity
+    let identity = Keypair::new();
+    let set_validator_identity = |authority : &Keypair| {
+        instruction::set_validator_identity(
+            program_id(),
+            validator.vote_account,
+            validator.manager,
+            authority.pubkey(),
+            identity.pubkey()
+        )
+    };
+    assert!(process(&mut context, &[set_validator_identity(&administrator)], &[&administrator, &identity])
+        .await
+        .is_err());
+    process(&mut context, &[set_validator_identity(&operational_authority)], &[&operational_authority, &identity])
+        .await
+        .unwrap();
+    assert_eq!(vote_state(&mut context, &validator.vote_account).await.node_pubkey, identity.pubkey());
+    assert_ne!(identity.pubkey(), validator.identity.pubkey());
+
+    // withdraw requires the rewards authority
+    let recipient = Keypair::new().pubkey();
+    let withdraw = |authority : &Keypair| {
+        instruction::withdraw(
+            program_id(),
+            validator.vote_account,
+            validator.manager,
+            authority.pubkey(),
+            recipient,
+            LAMPORTS_PER_SOL / 2
+        )
+    };
+    assert!(process(&mut context, &[withdraw(&administrator)], &[&administrator]).await.is_err());
+    process(&mut context, &[withdraw(&rewards_authority)], &[&rewards_authority]).await.unwrap();
+    assert_eq!(lamports(&mut context, &recipient).await, LAMPORTS_PER_SOL / 2);
+}
+
+// As submitted by 'solana-vamp batch': enter and the authority changes that follow it, in a single transaction
+#[tokio::test]
+async fn enter_and_set_authorities_atomically()
+{
+    let Some(mut context) = start().await
+    else {
+        return;
+    };
+
+    let validator = create_vote_account(&mut context).await;
+
+    let operational_authority = Keypair::new().pubkey();
+    let rewards_authority = Keypair::new().pubkey();
+
+    let set_authority = |data : VampInstruction| {
+        instruction::set_authority(
+            program_id(),
+            validator.vote_account,
+            validator.manager,
+            validator.administrator.pubkey(),
+            data
+        )
+    };
+
+    let instructions = [
+        instruction::enter(
+            program_id(),
+            validator.vote_account,
+            validator.manager,
+            context.payer.pubkey(),
+            validator.withdraw_authority.pubkey(),
+            validator.administrator.pubkey(),
+            None
+        ),
+        set_authority(VampInstruction::SetOperationalAuthority { new_authority : operational_authority.to_bytes() }),
+        set_authority(VampInstruction::SetRewardsAuthority { new_authority : rewards_authority.to_bytes() })
+    ];
+
+    process(&mut context, &instructions, &[&validator.withdraw_authority, &validator.administrator]).await.unwrap();
+
+    let state = manager_state(&mut context, &validator.manager).await;
+    assert_eq!(state.administrator, validator.administrator.pubkey());
+    assert_eq!(state.operational_authority, operational_authority);
+    assert_eq!(state.rewards_authority, rewards_authority);
+}
+
+#[tokio::test]
+async fn commission_caps()
+{
+    let Some(mut context) = start().await
+    else {
+        return;
+    };
+
+    let validator = create_vote_account(&mut context).await;
+
+    enter(&mut context, &validator, Some((10, 2))).await.unwrap();
+
+    let rewards_authority = &validator.administrator;
+
+    // Only the rewards authority may set commission
+    assert!(set_commission(&mut context, &validator, &validator.withdraw_authority, 6).await.is_err());
+
+    // At most 2 above the commission at the start of the epoch
+    assert!(set_commission(&mut context, &validator, rewards_authority, INITIAL_COMMISSION + 3).await.is_err());
+    set_commission(&mut context, &validator, rewards_authority, INITIAL_COMMISSION + 2).await.unwrap();
+    assert!(set_commission(&mut context, &validator, rewards_authority, INITIAL_COMMISSION + 3).await.is_err());
+    assert_eq!(vote_state(&mut context, &validator.vote_account).await.commission, INITIAL_COMMISSION + 2);
+
+    // Decreases are always allowed
+    set_commission(&mut context, &validator, rewards_authority, 0).await.unwrap();
+    set_commission(&mut context, &validator, rewards_authority, INITIAL_COMMISSION + 2).await.unwrap();
+
+    // In the next epoch, a further 2 is allowed, but never more than 10
+    let epoch = current_epoch(&mut context).await;
+    warp_to_epoch(&mut context, epoch + 1).await;
+    set_commission(&mut context, &validator, rewards_authority, INITIAL_COMMISSION + 4).await.unwrap();
+
+    let epoch = current_epoch(&mut context).await;
+    warp_to_epoch(&mut context, epoch + 1).await;
+    assert!(set_commission(&mut context, &validator, rewards_authority, 11).await.is_err());
+    set_commission(&mut context, &validator, rewards_authority, 10).await.unwrap();
+    assert_eq!(vote_state(&mut context, &validator.vote_account).await.commission, 10);
+}
+
+#[tokio::test]
+async fn leave_epoch_and_leave()
+{
+    let Some(mut context) = start().await
+    else {
+        return;
+    };
+
+    let validator = create_vote_account(&mut context).await;
+
+    enter(&mut context, &validator, Some((10, 2))).await.unwrap();
+
+    // With commission caps, leave requires a leave epoch
+    assert!(leave(&mut context, &validator).await.is_err());
+
+    // The leave epoch must be at least 2 epochs in the future
+    let epoch = current_epoch(&mut context).await;
+    assert!(set_leave_epoch(&mut context, &validator, epoch + 1).await.is_err());
+    set_leave_epoch(&mut context, &validator, epoch + 2).await.unwrap();
+    assert_eq!(manager_state(&mut context, &validator.manager).await.leave_epoch, epoch + 2);
+
+    // No commission changes once a leave epoch is set
+    assert!(set_commission(&mut context, &validator, &validator.administrator, INITIAL_COMMISSION).await.is_err());
+
+    // Leave is not allowed before the leave epoch
+    warp_to_epoch(&mut context, epoch + 1).await;
+    assert!(leave(&mut context, &validator).await.is_err());
+
+    warp_to_epoch(&mut context, epoch + 2).await;
+    leave(&mut context, &validator).await.unwrap();
+
+    let vote_state = vote_state(&mut context, &validator.vote_account).await;
+    assert_eq!(vote_state.authorized_withdrawer, validator.withdraw_authority.pubkey());
+}
+
+#[tokio::test]
+async fn leave_without_commission_caps()
+{
+    let Some(mut context) = start().await
+    else {
+        return;
+    };
+
+    let validator = create_vote_account(&mut context).await;
+
+    enter(&mut context, &validator, None).await.unwrap();
+
+    // Only the withdraw authority can leave
+    let imposter = Validator {
+        vote_account : validator.vote_account,
+        manager : validator.manager,
+        identity : Keypair::new(),
+        withdraw_authority : Keypair::new(),
+        administrator : Keypair::new()
+    };
+    assert!(leave(&mut context, &imposter).await.is_err());
+
+    // Without commission caps, no leave epoch is required
+    leave(&mut context, &validator).await.unwrap();
+
+    let vote_state = vote_state(&mut context, &validator.vote_account).await;
+    assert_eq!(vote_state.authorized_withdrawer, validator.withdraw_authority.pubkey());
+
+    // The vote account can be put back under program control
+    enter(&mut context, &validator, Some((10, 2))).await.unwrap();
+    assert_eq!(manager_state(&mut context, &validator.manager).await.commission_caps, Some((10, 2)));
+}
@@ -0,0 +1,163 @@
+// Regression tests of command output, run against RPC fixtures in tests/fixtures.  Each fixture is served back by
+// --replay-rpc, and the output of the command is compared against the .out file of the same test.
+//
+// To re-record a fixture, run the command against a local test validator with --record-rpc, for example:
+//
+//   solana-vamp -u localhost --record-rpc tests/fixtures/show.json show <VOTE_ACCOUNT>
+//
+// The fixtures checked in were written by hand in the format that --record-rpc writes, rather than recorded, and are
+// to be replaced by recordings against a test validator with the program deployed.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::process::Command;
+
+const VOTE_ACCOUNT_1 : &str = "k7FaK87WHGVXzkaoHb7CdVPgkKDQhZ29VLDeBVbDfYn";
+const VOTE_ACCOUNT_2 : &str = "p2Yicb86aZig616Eav2VWG9vuXR5mEqhtzshZYBxzsV";
+const VOTE_ACCOUNT_3 : &str = "swqrv48gsrwpBFbftEwnP2vB4jckpvfGJfXkwaniLCC";
+
+fn fixture(name : &str) -> String
+{
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn run(args : &[&str]) -> String
+{
+    let output = Command::new(env!("CARGO_BIN_EXE_solana-vamp")).args(args).output().unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn check(
+    fixture_name : &str,
+    expected : &str,
+    args : &[&str]
+)
+{
+    let replay = fixture(fixture_name);
+
+    let output = run(&[&["--replay-rpc", replay.as_str()], args].concat());
+
+    assert_eq!(output, std::fs::read_to_string(fixture(expected)).unwrap());
+}
+
+#[test]
+fn show()
+{
+    check("show.json", "show.out", &["show", VOTE_ACCOUNT_1]);
+}
+
+#[test]
+fn show_multiple()
+{
+    check("show.json", "show_multiple.out", &["show", VOTE_ACCOUNT_1, VOTE_ACCOUNT_2, VOTE_ACCOUNT_3]);
+}
+
+#[test]
+fn show_multiple_json()
+{
+    check("show.json", "show_multiple_json.out", &["show", "--json", VOTE_ACCOUNT_1, VOTE_ACCOUNT_2, VOTE_ACCOUNT_3]);
+}
+
+#[test]
+fn history()
+{
+    check("history.json", "history.out", &["history", VOTE_ACCOUNT_1]);
+}
+
+#[test]
+fn history_json()
+{
+    check("history.json", "history_json.out", &["history", "--json", VOTE_ACCOUNT_1]);
+}
+
+#[test]
+fn missing_response_is_reported()
+{
+    let output = Command::new(env!("CARGO_BIN_EXE_solana-vamp"))
+        .args(["--replay-rpc", &fixture("history.json"), "show", VOTE_ACCOUNT_1])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No response for getMultipleAccounts"));
+}
+
+// Serves the responses of a fixture over HTTP, as an RPC node would, so that --record-rpc can be checked to record
+// exactly the traffic that --replay-rpc replays
+fn serve(fixture_name : &str) -> String
+{
+    let entries =
+        serde_json::from_str::<Vec<serde_json::Value>>(&std::fs::read_to_string(fixture(fixture_name)).unwrap())
+            .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let entries = entries.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.unwrap());
+                loop {
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let line = line.trim_end().to_ascii_lowercase();
+                        if line.is_empty() {
+                            break;
+                        }
+                        if let Some(value) = line.strip_prefix("content-length:") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                    let mut body = vec![0_u8; content_length];
+                    reader.read_exact(&mut body).unwrap();
+                    let request = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+                    let entry = entries
+                        .iter()
+                        .find(|entry| (entry["method"] == request["method"]) && (entry["params"] == request["params"]))
+                        .unwrap();
+                    let response =
+                        serde_json::json!({ "jsonrpc" : "2.0", "result" : entry["result"], "id" : request["id"] })
+                            .to_string();
+                    write!(
+                        reader.get_mut(),
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                        response.len(),
+                        response
+                    )
+                    .unwrap();
+                }
+            });
+        }
+    });
+
+    url
+}
+
+#[test]
+fn record_then_replay()
+{
+    let url = serve("show.json");
+
+    let recorded = std::env::temp_dir().join(format!("solana-vamp-record-{}.json", std::process::id()));
+
+    let recorded = recorded.to_str().unwrap();
+
+    let args = ["show", VOTE_ACCOUNT_1, VOTE_ACCOUNT_2, VOTE_ACCOUNT_3];
+
+    let output = run(&[&["-u", url.as_str(), "--record-rpc", recorded], args.as_slice()].concat());
+
+    assert_eq!(output, std::fs::read_to_string(fixture("show_multiple.out")).unwrap());
+
+    assert_eq!(run(&[&["--replay-rpc", recorded], args.as_slice()].concat()), output);
+
+    std::fs::remove_file(recorded).unwrap();
+}